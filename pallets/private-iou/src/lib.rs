@@ -5,30 +5,129 @@ extern crate alloc;
 #[cfg(test)]
 mod mock;
 
-use alloc::{vec, vec::Vec};
+use alloc::vec::Vec;
 use codec::MaxEncodedLen;
-use frame_support::pallet_prelude::{Decode, Encode};
+use core::marker::PhantomData;
+use frame_support::{
+	pallet_prelude::{Decode, Encode},
+	CloneNoBound, DebugNoBound, DefaultNoBound, EqNoBound, PartialEqNoBound,
+};
 use scale_info::TypeInfo;
 use tornado::{
-	ark_serialize::CanonicalDeserialize, config::types::*, crypto::proofsystem::ProofSystem,
+	ark_serialize::{CanonicalDeserialize, CanonicalSerialize},
+	circuit::{
+		verify_claim, verify_mint, verify_partial_claim, verify_transfer, ClaimInput, MintInput,
+		PartialClaimInput, TransferInput,
+	},
+	config::{types::*, DefaultHeight, MerkleTreeConfiguration, Scalar, VoidNumberTree},
+	crypto::{
+		arkworks::Fp,
+		merkle_tree::{self, single_path, sparse::NodeMap, Node},
+	},
 };
 
 pub use pallet::*;
+pub use tornado::config::{Height, MerkleTreeHeight};
 
-#[derive(Clone, Debug, Decode, Default, Encode, MaxEncodedLen, Eq, PartialEq, TypeInfo)]
-pub struct UtxoMerkleTreePath {
+#[derive(
+	CloneNoBound,
+	DebugNoBound,
+	Decode,
+	DefaultNoBound,
+	Encode,
+	MaxEncodedLen,
+	EqNoBound,
+	PartialEqNoBound,
+	TypeInfo,
+)]
+#[scale_info(skip_type_params(H))]
+pub struct UtxoMerkleTreePath<H>
+where
+	H: MerkleTreeHeight,
+{
 	pub leaf_digest: Option<HashDigest>,
-	pub current_path: CurrentPath,
+	pub current_path: CurrentPath<H>,
+}
+
+/// Leaf Digest and Current Path of a Single Path UTXO Merkle Tree
+type UtxoMerkleTreeState<H> =
+	(Option<Scalar>, merkle_tree::CurrentPath<MerkleTreeConfiguration<H>>);
+
+impl<H> UtxoMerkleTreePath<H>
+where
+	H: MerkleTreeHeight,
+{
+	/// Builds a new [`UtxoMerkleTreePath`] from the `leaf_digest` and the `current_path` of a
+	/// single path merkle tree.
+	#[inline]
+	fn from_tree(state: &UtxoMerkleTreeState<H>) -> Self {
+		let (leaf_digest, current_path) = state;
+		Self {
+			leaf_digest: leaf_digest.as_ref().map(into_digest),
+			current_path: CurrentPath::new(
+				into_digest(&current_path.sibling_digest),
+				current_path.leaf_index().0 as u32,
+				current_path.inner_path.path.iter().map(into_digest).collect(),
+			),
+		}
+	}
+
+	/// Converts `self` back into the leaf digest and the current path of a single path merkle
+	/// tree, returning `None` if any of its digests is malformed.
+	#[inline]
+	fn to_tree(&self) -> Option<UtxoMerkleTreeState<H>> {
+		let leaf_digest = match &self.leaf_digest {
+			Some(digest) => Some(from_digest(digest)?),
+			_ => None,
+		};
+		Some((
+			leaf_digest,
+			merkle_tree::CurrentPath::new(
+				from_digest(&self.current_path.sibling_digest)?,
+				Node(self.current_path.leaf_index as usize),
+				self.current_path.inner_path.iter().map(from_digest).collect::<Option<_>>()?,
+			),
+		))
+	}
 }
 
-#[derive(Clone, Debug, Decode, Default, Encode, Eq, PartialEq, TypeInfo)]
-pub struct CurrentPath {
+#[derive(
+	CloneNoBound,
+	DebugNoBound,
+	Decode,
+	DefaultNoBound,
+	Encode,
+	EqNoBound,
+	PartialEqNoBound,
+	TypeInfo,
+)]
+#[scale_info(skip_type_params(H))]
+pub struct CurrentPath<H>
+where
+	H: MerkleTreeHeight,
+{
 	pub sibling_digest: HashDigest,
 	pub leaf_index: u32,
 	pub inner_path: Vec<HashDigest>,
+	#[codec(skip)]
+	__: PhantomData<H>,
+}
+
+impl<H> CurrentPath<H>
+where
+	H: MerkleTreeHeight,
+{
+	/// Builds a new [`CurrentPath`] from its `sibling_digest`, `leaf_index`, and `inner_path`.
+	#[inline]
+	pub fn new(sibling_digest: HashDigest, leaf_index: u32, inner_path: Vec<HashDigest>) -> Self {
+		Self { sibling_digest, leaf_index, inner_path, __: PhantomData }
+	}
 }
 
-impl MaxEncodedLen for CurrentPath {
+impl<H> MaxEncodedLen for CurrentPath<H>
+where
+	H: MerkleTreeHeight,
+{
 	#[inline]
 	fn max_encoded_len() -> usize {
 		0_usize
@@ -36,14 +135,17 @@ impl MaxEncodedLen for CurrentPath {
 			.saturating_add(u32::max_encoded_len())
 			.saturating_add(
 				// NOTE: We know that these paths don't exceed the path length.
-				HashDigest::max_encoded_len().saturating_mul(MERKLE_TREE_DEPTH),
+				HashDigest::max_encoded_len().saturating_mul(H::HEIGHT),
 			)
 	}
 }
 
 #[inline]
-pub fn is_valid_mint(amount: Balance, utxo: Utxo, proof: ZKP) -> bool {
-	let (_, (_, verifying_key), _) = tornado::parameters::generate();
+pub fn is_valid_mint<H>(amount: Balance, utxo: Utxo, proof: ZKP) -> bool
+where
+	H: MerkleTreeHeight,
+{
+	let (_, verifying_key) = tornado::parameters::mint_keys::<H>();
 	let utxo = match CanonicalDeserialize::deserialize(utxo.as_slice()) {
 		Ok(utxo) => utxo,
 		_ => return false,
@@ -52,20 +154,24 @@ pub fn is_valid_mint(amount: Balance, utxo: Utxo, proof: ZKP) -> bool {
 		Ok(proof) => proof,
 		_ => return false,
 	};
-	match tornado::config::ProofSystem::verify(&verifying_key, &vec![amount.into(), utxo], &proof) {
+	let input = MintInput { value: Fp(amount.into()), utxo };
+	match verify_mint::<tornado::config::Config<H>>(&verifying_key, &input, &proof) {
 		Ok(true) => true,
 		_ => false,
 	}
 }
 
 #[inline]
-pub fn is_valid_claim(
+pub fn is_valid_claim<H>(
 	amount: Balance,
 	merkle_root: MerkleRoot,
 	void_number: VoidNumber,
 	proof: ZKP,
-) -> bool {
-	let (_, _, (_, verifying_key)) = tornado::parameters::generate();
+) -> bool
+where
+	H: MerkleTreeHeight,
+{
+	let (_, verifying_key) = tornado::parameters::claim_keys::<H>();
 	let merkle_root = match CanonicalDeserialize::deserialize(merkle_root.as_slice()) {
 		Ok(merkle_root) => merkle_root,
 		_ => return false,
@@ -78,20 +184,103 @@ pub fn is_valid_claim(
 		Ok(proof) => proof,
 		_ => return false,
 	};
-	match tornado::config::ProofSystem::verify(
-		&verifying_key,
-		&vec![amount.into(), merkle_root, void_number],
-		&proof,
-	) {
+	let input = ClaimInput { value: Fp(amount.into()), root: merkle_root, void_number };
+	match verify_claim::<tornado::config::Config<H>>(&verifying_key, &input, &proof) {
+		Ok(true) => true,
+		_ => false,
+	}
+}
+
+#[inline]
+pub fn is_valid_transfer<H>(
+	merkle_root: MerkleRoot,
+	void_numbers: [VoidNumber; 2],
+	utxos: [Utxo; 2],
+	proof: ZKP,
+) -> bool
+where
+	H: MerkleTreeHeight,
+{
+	let (_, verifying_key) = tornado::parameters::transfer_keys::<H>();
+	let [root, void_number_0, void_number_1, utxo_0, utxo_1] =
+		match [merkle_root, void_numbers[0], void_numbers[1], utxos[0], utxos[1]]
+			.map(|digest| from_digest(&digest))
+		{
+			[Some(root), Some(void_number_0), Some(void_number_1), Some(utxo_0), Some(utxo_1)] =>
+				[root, void_number_0, void_number_1, utxo_0, utxo_1],
+			_ => return false,
+		};
+	let proof = match CanonicalDeserialize::deserialize(proof.as_slice()) {
+		Ok(proof) => proof,
+		_ => return false,
+	};
+	let input = TransferInput { root, void_number_0, void_number_1, utxo_0, utxo_1 };
+	match verify_transfer::<tornado::config::Config<H>>(&verifying_key, &input, &proof) {
 		Ok(true) => true,
 		_ => false,
 	}
 }
 
+#[inline]
+pub fn is_valid_partial_claim<H>(
+	amount: Balance,
+	merkle_root: MerkleRoot,
+	void_number: VoidNumber,
+	change_utxo: Utxo,
+	proof: ZKP,
+) -> bool
+where
+	H: MerkleTreeHeight,
+{
+	let (_, verifying_key) = tornado::parameters::partial_claim_keys::<H>();
+	let [root, void_number, change_utxo] =
+		match [merkle_root, void_number, change_utxo].map(|digest| from_digest(&digest)) {
+			[Some(root), Some(void_number), Some(change_utxo)] => [root, void_number, change_utxo],
+			_ => return false,
+		};
+	let proof = match CanonicalDeserialize::deserialize(proof.as_slice()) {
+		Ok(proof) => proof,
+		_ => return false,
+	};
+	let input = PartialClaimInput { amount: Fp(amount.into()), root, void_number, change_utxo };
+	match verify_partial_claim::<tornado::config::Config<H>>(&verifying_key, &input, &proof) {
+		Ok(true) => true,
+		_ => false,
+	}
+}
+
+/// Converts `scalar` into its raw digest.
+#[inline]
+fn into_digest(scalar: &Scalar) -> HashDigest {
+	let mut digest = HashDigest::default();
+	scalar
+		.serialize(digest.as_mut_slice())
+		.expect("Scalars are not allowed to be longer than a digest.");
+	digest
+}
+
+/// Converts a raw `digest` back into a scalar.
+#[inline]
+fn from_digest(digest: &HashDigest) -> Option<Scalar> {
+	CanonicalDeserialize::deserialize(digest.as_slice()).ok()
+}
+
+/// Packs the key bits of a void number tree node into the key of its storage entry.
+#[inline]
+fn node_key(key: &[bool]) -> (u16, HashDigest) {
+	let mut bytes = HashDigest::default();
+	for (i, bit) in key.iter().enumerate() {
+		if *bit {
+			bytes[i / 8] |= 1 << (i % 8);
+		}
+	}
+	(key.len() as u16, bytes)
+}
+
 #[frame_support::pallet]
 pub mod pallet {
 	use super::*;
-	use frame_support::pallet_prelude::*;
+	use frame_support::{pallet_prelude::*, transactional};
 	use frame_system::pallet_prelude::*;
 
 	/// Configure the pallet by specifying the parameters and types on which it depends.
@@ -99,6 +288,9 @@ pub mod pallet {
 	pub trait Config: frame_system::Config {
 		/// Because this pallet emits events, it depends on the runtime's definition of an event.
 		type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
+
+		/// Height of the UTXO merkle tree, which also chooses the claim circuit and its keys.
+		type MerkleTreeHeight: MerkleTreeHeight + 'static;
 	}
 
 	#[pallet::pallet]
@@ -111,6 +303,20 @@ pub mod pallet {
 	#[pallet::storage]
 	pub type VoidNumberSet<T> = StorageMap<_, Twox64Concat, VoidNumber, (), ValueQuery>;
 
+	/// Void Number Tree Nodes
+	///
+	/// Stores the nodes of the sparse merkle tree of spent void numbers, keyed by the length and
+	/// the packed key bits of their subtree. See [`VoidNumberTreeStorage`].
+	#[pallet::storage]
+	pub type VoidNumberTreeNodes<T> =
+		StorageMap<_, Blake2_128Concat, (u16, HashDigest), HashDigest, OptionQuery>;
+
+	/// Void Number Tree Root
+	///
+	/// Root of the sparse merkle tree of spent void numbers, which is empty until the first claim.
+	#[pallet::storage]
+	pub type VoidNumberRoot<T> = StorageValue<_, MerkleRoot, OptionQuery>;
+
 	#[pallet::storage]
 	pub type UtxoInsertionOrder<T> = StorageMap<_, Twox64Concat, u64, Utxo, ValueQuery>;
 
@@ -119,7 +325,66 @@ pub mod pallet {
 		StorageMap<_, Twox64Concat, T::AccountId, Balance, ValueQuery>;
 
 	#[pallet::storage]
-	pub type Accumulator<T: Config> = StorageValue<_, (MerkleRoot, UtxoMerkleTreePath)>;
+	pub type Accumulator<T: Config> =
+		StorageValue<_, (MerkleRoot, UtxoMerkleTreePath<T::MerkleTreeHeight>)>;
+
+	/// UTXO Merkle Tree Roots
+	///
+	/// Every root that the UTXO merkle tree has had, so that proofs against a root remain valid
+	/// after more UTXOs are inserted.
+	#[pallet::storage]
+	pub type AccumulatorRoots<T> = StorageMap<_, Twox64Concat, MerkleRoot, (), ValueQuery>;
+
+	/// Void Number Tree Storage
+	///
+	/// Node map of the sparse merkle tree of spent void numbers, backed by
+	/// [`VoidNumberTreeNodes`].
+	pub struct VoidNumberTreeStorage<T>(PhantomData<T>);
+
+	impl<T> Default for VoidNumberTreeStorage<T> {
+		#[inline]
+		fn default() -> Self {
+			Self(PhantomData)
+		}
+	}
+
+	impl<T> VoidNumberTreeStorage<T> {
+		/// Returns the digest stored at `key`.
+		#[inline]
+		fn get(key: &[bool]) -> Option<Scalar> {
+			VoidNumberTreeNodes::<T>::get(node_key(key)).map(|digest| {
+				from_digest(&digest).expect("Stored digests are not allowed to be malformed.")
+			})
+		}
+
+		/// Sets the digest stored at `key` to `digest`.
+		#[inline]
+		fn set(key: &[bool], digest: &Scalar) {
+			VoidNumberTreeNodes::<T>::insert(node_key(key), into_digest(digest));
+		}
+	}
+
+	impl<T> NodeMap<MerkleTreeConfiguration> for VoidNumberTreeStorage<T> {
+		#[inline]
+		fn get_leaf(&self, key: &[bool]) -> Option<Scalar> {
+			Self::get(key)
+		}
+
+		#[inline]
+		fn set_leaf(&mut self, key: &[bool], leaf_digest: Scalar) {
+			Self::set(key, &leaf_digest)
+		}
+
+		#[inline]
+		fn get_inner(&self, key: &[bool]) -> Option<Scalar> {
+			Self::get(key)
+		}
+
+		#[inline]
+		fn set_inner(&mut self, key: &[bool], inner_digest: Scalar) {
+			Self::set(key, &inner_digest)
+		}
+	}
 
 	#[pallet::event]
 	#[pallet::generate_deposit(pub(super) fn deposit_event)]
@@ -128,15 +393,107 @@ pub mod pallet {
 		PublicTransfer(T::AccountId, T::AccountId, Balance),
 		PrivateIOUMint(T::AccountId, Balance, Utxo),
 		PrivateIOUClaimed(T::AccountId, Balance),
+		PrivateTransfer(T::AccountId, Utxo, Utxo),
+		PrivateIOUPartiallyClaimed(T::AccountId, Balance, Utxo),
 	}
 
 	#[pallet::error]
 	pub enum Error<T> {
 		NotEnoughBalance,
 		DuplicateUtxo,
+		InvalidUtxo,
+		UnknownMerkleRoot,
+		MerkleTreeFull,
 		DuplicateVoidNumber,
+		InvalidVoidNumber,
 		InvalidMintZKP,
 		InvalidClaimZKP,
+		InvalidTransferZKP,
+		InvalidPartialClaimZKP,
+	}
+
+	impl<T: Config> Pallet<T> {
+		/// Records every void number in `void_numbers` as spent, in the void number set and in the
+		/// void number tree, failing with [`Error::InvalidVoidNumber`] if any of them is zero or
+		/// malformed. Nothing is written unless every void number can be spent.
+		#[inline]
+		fn spend_void_numbers(void_numbers: &[VoidNumber]) -> DispatchResult {
+			for (i, void_number) in void_numbers.iter().enumerate() {
+				ensure!(
+					!VoidNumberSet::<T>::contains_key(void_number) &&
+						!void_numbers[..i].contains(void_number),
+					Error::<T>::DuplicateVoidNumber
+				);
+			}
+			let keys = void_numbers
+				.iter()
+				.map(|void_number| from_digest(void_number).filter(|key| *key != Scalar::default()))
+				.collect::<Option<Vec<_>>>()
+				.ok_or(Error::<T>::InvalidVoidNumber)?;
+			let mut void_number_tree = VoidNumberTree::from_map(
+				tornado::parameters::parameters::<DefaultHeight>().merkle_tree_parameters,
+				VoidNumberTreeStorage::<T>::default(),
+			);
+			for (void_number, key) in void_numbers.iter().zip(&keys) {
+				VoidNumberSet::<T>::insert(void_number, ());
+				void_number_tree.insert(key);
+			}
+			VoidNumberRoot::<T>::put(into_digest(&void_number_tree.root()));
+			Ok(())
+		}
+
+		/// Appends every UTXO in `utxos` to the UTXO set and to the UTXO merkle tree, recording
+		/// every root that the tree goes through in [`AccumulatorRoots`]. Nothing is written unless
+		/// every UTXO can be appended.
+		#[inline]
+		fn insert_utxos(utxos: &[Utxo]) -> DispatchResult {
+			for (i, utxo) in utxos.iter().enumerate() {
+				ensure!(
+					!UTXOSet::<T>::contains_key(utxo) && !utxos[..i].contains(utxo),
+					Error::<T>::DuplicateUtxo
+				);
+			}
+			let leaves = utxos
+				.iter()
+				.map(from_digest)
+				.collect::<Option<Vec<_>>>()
+				.ok_or(Error::<T>::InvalidUtxo)?;
+			let (mut leaf_digest, mut current_path) = match Accumulator::<T>::get() {
+				Some((_, path)) =>
+					path.to_tree().expect("Stored paths are not allowed to be malformed."),
+				_ => Default::default(),
+			};
+			let parameters =
+				tornado::parameters::parameters::<T::MerkleTreeHeight>().merkle_tree_parameters;
+			let mut roots = Vec::with_capacity(leaves.len());
+			for leaf in &leaves {
+				roots.push(
+					single_path::raw::insert(
+						&parameters,
+						&mut leaf_digest,
+						&mut current_path,
+						parameters.digest(leaf),
+					)
+					.ok_or(Error::<T>::MerkleTreeFull)?,
+				);
+			}
+			let first_index = (current_path.leaf_index().0 + 1 - utxos.len()) as u64;
+			for (index, utxo) in (first_index..).zip(utxos) {
+				UTXOSet::<T>::insert(utxo, ());
+				UtxoInsertionOrder::<T>::insert(index, utxo);
+			}
+			let roots = roots.iter().map(into_digest).collect::<Vec<_>>();
+			for root in &roots {
+				AccumulatorRoots::<T>::insert(root, ());
+			}
+			if let Some(root) = roots.last() {
+				Accumulator::<T>::put((
+					*root,
+					UtxoMerkleTreePath::from_tree(&(leaf_digest, current_path)),
+				));
+			}
+			Ok(())
+		}
 	}
 
 	#[pallet::call]
@@ -168,7 +525,7 @@ pub mod pallet {
 			Ok(())
 		}
 
-		#[pallet::weight(200_000_000_000 + T::DbWeight::get().reads_writes(1,1))]
+		#[pallet::weight(200_000_000_000 + T::DbWeight::get().reads_writes(3, 5))]
 		pub fn mint_private_iou(
 			origin: OriginFor<T>,
 			amount: Balance,
@@ -180,14 +537,23 @@ pub mod pallet {
 			if previous_balance < amount {
 				return Err(Error::<T>::NotEnoughBalance.into())
 			}
-			ensure!(is_valid_mint(amount, utxo, proof), Error::<T>::InvalidMintZKP);
-			ensure!(!UTXOSet::<T>::contains_key(utxo), Error::<T>::DuplicateUtxo);
+			ensure!(
+				is_valid_mint::<T::MerkleTreeHeight>(amount, utxo, proof),
+				Error::<T>::InvalidMintZKP
+			);
+			Self::insert_utxos(&[utxo])?;
 			PublicBalance::<T>::insert(&who, previous_balance - amount);
 			Self::deposit_event(Event::<T>::PrivateIOUMint(who, amount, utxo));
 			Ok(())
 		}
 
-		#[pallet::weight(200_000_000_000 + T::DbWeight::get().reads_writes(1,1))]
+		#[pallet::weight(
+			200_000_000_000
+				+ T::DbWeight::get().reads_writes(
+					3 + VOID_NUMBER_TREE_DEPTH as u64,
+					3 + VOID_NUMBER_TREE_DEPTH as u64,
+				)
+		)]
 		pub fn claim_private_iou(
 			origin: OriginFor<T>,
 			amount: Balance,
@@ -198,16 +564,91 @@ pub mod pallet {
 			let who = ensure_signed(origin)?;
 			let previous_balance = PublicBalance::<T>::get(&who);
 			ensure!(
-				is_valid_claim(amount, merkle_root, void_number, proof),
-				Error::<T>::InvalidClaimZKP
+				AccumulatorRoots::<T>::contains_key(merkle_root),
+				Error::<T>::UnknownMerkleRoot
 			);
 			ensure!(
-				!VoidNumberSet::<T>::contains_key(void_number),
-				Error::<T>::DuplicateVoidNumber
+				is_valid_claim::<T::MerkleTreeHeight>(amount, merkle_root, void_number, proof),
+				Error::<T>::InvalidClaimZKP
 			);
+			Self::spend_void_numbers(&[void_number])?;
 			PublicBalance::<T>::insert(&who, previous_balance + amount);
 			Self::deposit_event(Event::<T>::PrivateIOUClaimed(who, amount));
 			Ok(())
 		}
+
+		#[pallet::weight(
+			200_000_000_000
+				+ T::DbWeight::get().reads_writes(
+					5 + VOID_NUMBER_TREE_DEPTH as u64,
+					7 + VOID_NUMBER_TREE_DEPTH as u64,
+				)
+		)]
+		#[transactional]
+		pub fn partial_claim_private_iou(
+			origin: OriginFor<T>,
+			amount: Balance,
+			merkle_root: MerkleRoot,
+			void_number: VoidNumber,
+			change_utxo: Utxo,
+			proof: ZKP,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			let previous_balance = PublicBalance::<T>::get(&who);
+			ensure!(
+				AccumulatorRoots::<T>::contains_key(merkle_root),
+				Error::<T>::UnknownMerkleRoot
+			);
+			ensure!(
+				is_valid_partial_claim::<T::MerkleTreeHeight>(
+					amount,
+					merkle_root,
+					void_number,
+					change_utxo,
+					proof
+				),
+				Error::<T>::InvalidPartialClaimZKP
+			);
+			ensure!(!UTXOSet::<T>::contains_key(change_utxo), Error::<T>::DuplicateUtxo);
+			Self::spend_void_numbers(&[void_number])?;
+			Self::insert_utxos(&[change_utxo])?;
+			PublicBalance::<T>::insert(&who, previous_balance + amount);
+			Self::deposit_event(Event::<T>::PrivateIOUPartiallyClaimed(who, amount, change_utxo));
+			Ok(())
+		}
+
+		#[pallet::weight(
+			200_000_000_000
+				+ T::DbWeight::get().reads_writes(
+					6 + 2 * VOID_NUMBER_TREE_DEPTH as u64,
+					10 + 2 * VOID_NUMBER_TREE_DEPTH as u64,
+				)
+		)]
+		#[transactional]
+		pub fn private_transfer(
+			origin: OriginFor<T>,
+			merkle_root: MerkleRoot,
+			void_numbers: [VoidNumber; 2],
+			utxos: [Utxo; 2],
+			proof: ZKP,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			ensure!(
+				AccumulatorRoots::<T>::contains_key(merkle_root),
+				Error::<T>::UnknownMerkleRoot
+			);
+			ensure!(
+				is_valid_transfer::<T::MerkleTreeHeight>(merkle_root, void_numbers, utxos, proof),
+				Error::<T>::InvalidTransferZKP
+			);
+			ensure!(
+				utxos[0] != utxos[1] && !utxos.iter().any(UTXOSet::<T>::contains_key),
+				Error::<T>::DuplicateUtxo
+			);
+			Self::spend_void_numbers(&void_numbers)?;
+			Self::insert_utxos(&utxos)?;
+			Self::deposit_event(Event::<T>::PrivateTransfer(who, utxos[0], utxos[1]));
+			Ok(())
+		}
 	}
 }
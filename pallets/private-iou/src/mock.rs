@@ -51,6 +51,7 @@ impl system::Config for Test {
 
 impl private_iou::Config for Test {
 	type Event = Event;
+	type MerkleTreeHeight = private_iou::Height<10>;
 }
 
 // Build genesis storage according to the mock runtime.
@@ -260,6 +260,7 @@ impl pallet_sudo::Config for Runtime {
 
 impl pallet_private_iou::Config for Runtime {
 	type Event = Event;
+	type MerkleTreeHeight = pallet_private_iou::Height<20>;
 }
 
 // Create the runtime by composing the FRAME pallets that were previously configured.
@@ -0,0 +1,383 @@
+//! ECLAIR Derive Macros
+//!
+//! This crate implements `#[derive(Variable)]` and `#[derive(Constant)]` for structs whose fields
+//! are allocated one by one into the same compiler. The generated code refers to the allocation
+//! traits through the `::tornado` path, so the `tornado` crate has to be in scope under that name.
+//!
+//! # Attributes
+//!
+//! Both macros read an `#[eclair(..)]` attribute on the struct with the following keys:
+//!
+//! - `compiler = "..."`: the compiler type the struct is allocated in (required)
+//! - `type = "..."`: the underlying type the struct is allocated from (required)
+//! - `bound = "..."`: extra `where` predicates added to the generated `impl`
+//!
+//! For [`Variable`](macro@Variable) the struct attribute also accepts `mode = "..."` with one of
+//! `derived`, `public` or `secret`, which defaults to `derived`. Every field is allocated with its
+//! own `#[eclair(public)]`, `#[eclair(secret)]` or `#[eclair(derived)]` attribute, and falls back
+//! to the struct mode when it is not `derived`.
+//!
+//! # Allocation Order
+//!
+//! Fields are allocated in declaration order, which means that the order of the public fields of a
+//! struct is the order of the public inputs of any circuit that allocates it.
+//!
+//! # Public Inputs
+//!
+//! Adding `public_input = "Name"` to the struct attribute of [`Variable`](macro@Variable) also
+//! generates a struct `Name` with the same generics, holding the underlying value of every public
+//! field in allocation order. The generated struct has the following items:
+//!
+//! - `FIELDS`: the names of the public fields in allocation order
+//! - `from_known`: extracts the public fields from the underlying type
+//! - `to_field_elements`: returns the public fields as an array, ready to be passed to a verifier
+//!
+//! Since the struct and the allocation are generated from the same fields, a verifier built on top
+//! of `to_field_elements` cannot disagree with the circuit about the layout of its public input.
+//! Fields allocated with `derived` are not allowed in this case, since their public parts are not
+//! known to the macro.
+
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::{quote, ToTokens};
+use syn::{
+	parse_macro_input, parse_quote, punctuated::Punctuated, spanned::Spanned, Attribute, Data,
+	DeriveInput, Error, Fields, Ident, Lit, Meta, NestedMeta, Result, Token, Type, WherePredicate,
+};
+
+/// Allocation Mode
+#[derive(Clone, Copy, Eq, PartialEq)]
+enum Mode {
+	/// Derived Mode
+	Derived,
+
+	/// Public Mode
+	Public,
+
+	/// Secret Mode
+	Secret,
+}
+
+impl Mode {
+	/// Parses a [`Mode`] from its attribute `name`.
+	#[inline]
+	fn from_name(name: &str) -> Option<Self> {
+		match name {
+			"derived" => Some(Self::Derived),
+			"public" => Some(Self::Public),
+			"secret" => Some(Self::Secret),
+			_ => None,
+		}
+	}
+}
+
+impl ToTokens for Mode {
+	#[inline]
+	fn to_tokens(&self, tokens: &mut TokenStream2) {
+		tokens.extend(match self {
+			Self::Derived => quote!(::tornado::crypto::eclair::alloc::mode::Derived),
+			Self::Public => quote!(::tornado::crypto::eclair::alloc::mode::Public),
+			Self::Secret => quote!(::tornado::crypto::eclair::alloc::mode::Secret),
+		})
+	}
+}
+
+/// Struct Attributes
+#[derive(Default)]
+struct StructAttributes {
+	/// Compiler Type
+	compiler: Option<Type>,
+
+	/// Underlying Type
+	ty: Option<Type>,
+
+	/// Default Allocation Mode
+	mode: Option<Mode>,
+
+	/// Public Input Struct Name
+	public_input: Option<Ident>,
+
+	/// Extra `where` Predicates
+	bound: Punctuated<WherePredicate, Token![,]>,
+}
+
+impl StructAttributes {
+	/// Parses the `#[eclair(..)]` attributes of a struct, accepting `mode` and `public_input` only
+	/// if `is_variable` is `true`.
+	#[inline]
+	fn parse(attrs: &[Attribute], is_variable: bool) -> Result<Self> {
+		let mut result = Self::default();
+		for meta in eclair_meta(attrs)? {
+			let name_value = match meta {
+				NestedMeta::Meta(Meta::NameValue(name_value)) => name_value,
+				other => return Err(Error::new(other.span(), "expected `key = \"...\"`")),
+			};
+			let value = match &name_value.lit {
+				Lit::Str(value) => value,
+				other => return Err(Error::new(other.span(), "expected a string literal")),
+			};
+			let key = name_value.path.get_ident().map(ToString::to_string).unwrap_or_default();
+			match key.as_str() {
+				"compiler" => result.compiler = Some(value.parse()?),
+				"type" => result.ty = Some(value.parse()?),
+				"bound" => result.bound = value.parse_with(Punctuated::parse_terminated)?,
+				"public_input" if is_variable => result.public_input = Some(value.parse()?),
+				"mode" if is_variable => {
+					result.mode = Some(
+						Mode::from_name(&value.value())
+							.ok_or_else(|| Error::new(value.span(), "unknown allocation mode"))?,
+					)
+				},
+				_ => return Err(Error::new(name_value.path.span(), "unknown `eclair` attribute")),
+			}
+		}
+		Ok(result)
+	}
+
+	/// Returns the compiler type, failing if it was not given.
+	#[inline]
+	fn compiler(&self) -> Result<&Type> {
+		self.compiler.as_ref().ok_or_else(|| {
+			Error::new(Span::call_site(), "missing `#[eclair(compiler = \"...\")]` attribute")
+		})
+	}
+
+	/// Returns the underlying type, failing if it was not given.
+	#[inline]
+	fn ty(&self) -> Result<&Type> {
+		self.ty.as_ref().ok_or_else(|| {
+			Error::new(Span::call_site(), "missing `#[eclair(type = \"...\")]` attribute")
+		})
+	}
+}
+
+/// Collects the nested items of every `#[eclair(..)]` attribute in `attrs`.
+#[inline]
+fn eclair_meta(attrs: &[Attribute]) -> Result<Vec<NestedMeta>> {
+	let mut result = Vec::new();
+	for attr in attrs.iter().filter(|attr| attr.path.is_ident("eclair")) {
+		match attr.parse_meta()? {
+			Meta::List(list) => result.extend(list.nested),
+			other => return Err(Error::new(other.span(), "expected `#[eclair(..)]`")),
+		}
+	}
+	Ok(result)
+}
+
+/// Parses the allocation mode of a field, falling back to `default` if there is none.
+#[inline]
+fn field_mode(attrs: &[Attribute], default: Mode, span: Span) -> Result<Mode> {
+	let mut result = None;
+	for meta in eclair_meta(attrs)? {
+		let mode = match &meta {
+			NestedMeta::Meta(Meta::Path(path)) => {
+				path.get_ident().and_then(|ident| Mode::from_name(&ident.to_string()))
+			},
+			_ => None,
+		};
+		match (mode, result) {
+			(Some(mode), None) => result = Some(mode),
+			(Some(_), Some(_)) => return Err(Error::new(meta.span(), "duplicate allocation mode")),
+			_ => {
+				return Err(Error::new(
+					meta.span(),
+					"expected one of `derived`, `public` or `secret`",
+				))
+			},
+		}
+	}
+	match (result, default) {
+		(Some(mode), _) => Ok(mode),
+		(None, Mode::Derived) => Err(Error::new(
+			span,
+			"missing `#[eclair(public)]` or `#[eclair(secret)]` attribute on a field of a derived \
+			 variable",
+		)),
+		(None, mode) => Ok(mode),
+	}
+}
+
+/// Returns the named fields of the struct in `input`.
+#[inline]
+fn named_fields(input: &DeriveInput) -> Result<&Punctuated<syn::Field, Token![,]>> {
+	match &input.data {
+		Data::Struct(data) => match &data.fields {
+			Fields::Named(fields) => Ok(&fields.named),
+			_ => {
+				Err(Error::new(input.ident.span(), "only structs with named fields are supported"))
+			},
+		},
+		_ => Err(Error::new(input.ident.span(), "only structs are supported")),
+	}
+}
+
+/// Builds the `where` clause of a derived `impl` from the struct generics and `bound`.
+#[inline]
+fn where_clause(
+	input: &DeriveInput,
+	bound: &Punctuated<WherePredicate, Token![,]>,
+) -> syn::WhereClause {
+	let mut result = input.generics.where_clause.clone().unwrap_or_else(|| parse_quote!(where));
+	result.predicates.extend(bound.iter().cloned());
+	result
+}
+
+/// Variable Derive Macro
+///
+/// Implements `Variable<Mode, Compiler>` for a struct by allocating each field with its own mode.
+/// See the [crate-level documentation](crate) for the accepted attributes.
+#[proc_macro_derive(Variable, attributes(eclair))]
+pub fn derive_variable(input: TokenStream) -> TokenStream {
+	let input = parse_macro_input!(input as DeriveInput);
+	expand_variable(&input).unwrap_or_else(Error::into_compile_error).into()
+}
+
+/// Expands the [`Variable`](macro@Variable) derive macro.
+#[inline]
+fn expand_variable(input: &DeriveInput) -> Result<TokenStream2> {
+	let attributes = StructAttributes::parse(&input.attrs, true)?;
+	let compiler = attributes.compiler()?;
+	let ty = attributes.ty()?;
+	let mode = attributes.mode.unwrap_or(Mode::Derived);
+	let mut unknown = Vec::new();
+	let mut known = Vec::new();
+	let mut public = Vec::new();
+	for field in named_fields(input)? {
+		let name = &field.ident;
+		let field_ty = &field.ty;
+		let field_mode = field_mode(&field.attrs, mode, field.span())?;
+		if attributes.public_input.is_some() && field_mode == Mode::Derived {
+			return Err(Error::new(
+				field.span(),
+				"`derived` fields are not allowed in a variable with a `public_input`",
+			));
+		}
+		let variable = quote!(<#field_ty as ::tornado::crypto::eclair::alloc::Variable<
+			#field_mode,
+			#compiler,
+		>>);
+		unknown.push(quote!(#name: #variable::new_unknown(compiler)));
+		known.push(quote!(#name: #variable::new_known(&this.#name, compiler)));
+		if field_mode == Mode::Public {
+			public.push((name, quote!(#variable::Type)));
+		}
+	}
+	let name = &input.ident;
+	let (impl_generics, ty_generics, _) = input.generics.split_for_impl();
+	let where_clause = where_clause(input, &attributes.bound);
+	let public_input = match &attributes.public_input {
+		Some(public_input) => expand_public_input(input, public_input, ty, &where_clause, &public),
+		_ => quote!(),
+	};
+	Ok(quote! {
+		impl #impl_generics ::tornado::crypto::eclair::alloc::Variable<#mode, #compiler>
+			for #name #ty_generics #where_clause
+		{
+			type Type = #ty;
+
+			#[inline]
+			fn new_unknown(compiler: &mut #compiler) -> Self {
+				Self { #(#unknown,)* }
+			}
+
+			#[inline]
+			fn new_known(this: &Self::Type, compiler: &mut #compiler) -> Self {
+				Self { #(#known,)* }
+			}
+		}
+
+		#public_input
+	})
+}
+
+/// Expands the public input struct called `public_input` of the variable in `input`, whose
+/// underlying type is `ty`, from its `public` fields and their underlying types.
+#[inline]
+fn expand_public_input(
+	input: &DeriveInput,
+	public_input: &Ident,
+	ty: &Type,
+	where_clause: &syn::WhereClause,
+	public: &[(&Option<Ident>, TokenStream2)],
+) -> TokenStream2 {
+	let vis = &input.vis;
+	let generics = &input.generics;
+	let (impl_generics, ty_generics, _) = generics.split_for_impl();
+	let doc = format!("Public Input of [`{}`]", input.ident);
+	let count = public.len();
+	let names = public.iter().map(|(name, _)| name).collect::<Vec<_>>();
+	let types = public.iter().map(|(_, ty)| ty).collect::<Vec<_>>();
+	let fields = names.iter().map(|name| name.as_ref().map(ToString::to_string));
+	quote! {
+		#[doc = #doc]
+		#vis struct #public_input #generics #where_clause {
+			#(pub #names: #types,)*
+		}
+
+		impl #impl_generics #public_input #ty_generics #where_clause {
+			/// Names of the Public Fields in Allocation Order
+			pub const FIELDS: [&'static str; #count] = [#(#fields,)*];
+
+			/// Extracts the public fields of `this`.
+			#[inline]
+			pub fn from_known(this: &#ty) -> Self
+			where
+				#(#types: Clone,)*
+			{
+				Self { #(#names: this.#names.clone(),)* }
+			}
+
+			/// Returns the public fields of `self` in allocation order.
+			#[inline]
+			pub fn to_field_elements<__F>(&self) -> [__F; #count]
+			where
+				#(#types: Clone + Into<__F>,)*
+			{
+				[#(self.#names.clone().into(),)*]
+			}
+		}
+	}
+}
+
+/// Constant Derive Macro
+///
+/// Implements `Constant<Compiler>` for a struct by allocating each field as a constant. See the
+/// [crate-level documentation](crate) for the accepted attributes.
+#[proc_macro_derive(Constant, attributes(eclair))]
+pub fn derive_constant(input: TokenStream) -> TokenStream {
+	let input = parse_macro_input!(input as DeriveInput);
+	expand_constant(&input).unwrap_or_else(Error::into_compile_error).into()
+}
+
+/// Expands the [`Constant`](macro@Constant) derive macro.
+#[inline]
+fn expand_constant(input: &DeriveInput) -> Result<TokenStream2> {
+	let attributes = StructAttributes::parse(&input.attrs, false)?;
+	let compiler = attributes.compiler()?;
+	let ty = attributes.ty()?;
+	let constant = named_fields(input)?.iter().map(|field| {
+		let name = &field.ident;
+		let field_ty = &field.ty;
+		quote! {
+			#name: <#field_ty as ::tornado::crypto::eclair::alloc::Constant<#compiler>>::new_constant(
+				&this.#name,
+				compiler,
+			)
+		}
+	});
+	let name = &input.ident;
+	let (impl_generics, ty_generics, _) = input.generics.split_for_impl();
+	let where_clause = where_clause(input, &attributes.bound);
+	Ok(quote! {
+		impl #impl_generics ::tornado::crypto::eclair::alloc::Constant<#compiler>
+			for #name #ty_generics #where_clause
+		{
+			type Type = #ty;
+
+			#[inline]
+			fn new_constant(this: &Self::Type, compiler: &mut #compiler) -> Self {
+				Self { #(#constant,)* }
+			}
+		}
+	})
+}
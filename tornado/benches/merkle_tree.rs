@@ -0,0 +1,78 @@
+//! Merkle Tree Insertion Benchmarks
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use tornado::{
+	config::{ConstraintField, DefaultHeight, MerkleTreeConfiguration, Scalar},
+	crypto::{
+		arkworks::Fp,
+		merkle_tree::{full::Full, single_path::SinglePath, MerkleTree, Parameters, Tree},
+	},
+	parameters::parameters,
+};
+
+/// Leaf Counts to Benchmark
+const LEAF_COUNTS: [usize; 3] = [1 << 8, 1 << 12, 1 << 16];
+
+/// Returns `count` distinct leaves.
+#[inline]
+fn leaves(count: usize) -> Vec<Scalar> {
+	(0..count as u64).map(|i| Fp(ConstraintField::from(i))).collect()
+}
+
+/// Inserts every leaf in `leaves` into a new tree with one push per leaf.
+#[inline]
+fn push_each<T>(parameters: &Parameters<MerkleTreeConfiguration>, leaves: &[Scalar]) -> Scalar
+where
+	T: Tree<MerkleTreeConfiguration>,
+{
+	let mut tree = MerkleTree::<_, T>::new(parameters.clone());
+	for leaf in leaves {
+		tree.push(leaf);
+	}
+	*tree.root()
+}
+
+/// Inserts every leaf in `leaves` into a new tree with one batch insertion.
+#[inline]
+fn extend<T>(parameters: &Parameters<MerkleTreeConfiguration>, leaves: &[Scalar]) -> Scalar
+where
+	T: Tree<MerkleTreeConfiguration>,
+{
+	let mut tree = MerkleTree::<_, T>::new(parameters.clone());
+	tree.extend_slice(leaves);
+	*tree.root()
+}
+
+/// Benchmarks repeated pushes against batch insertion on the tree backend `T` named `name`.
+#[inline]
+fn bench_backend<T>(c: &mut Criterion, name: &str)
+where
+	T: Tree<MerkleTreeConfiguration>,
+{
+	let parameters = parameters::<DefaultHeight>().merkle_tree_parameters;
+	let mut group = c.benchmark_group(format!("merkle_tree/{}", name));
+	group.sample_size(10);
+	for count in LEAF_COUNTS {
+		let leaves = leaves(count);
+		group.bench_with_input(BenchmarkId::new("push", count), &leaves, |b, leaves| {
+			b.iter(|| push_each::<T>(&parameters, black_box(leaves)))
+		});
+		group.bench_with_input(BenchmarkId::new("extend", count), &leaves, |b, leaves| {
+			b.iter(|| extend::<T>(&parameters, black_box(leaves)))
+		});
+	}
+	group.finish();
+}
+
+/// Benchmarks insertion into a full tree.
+fn full(c: &mut Criterion) {
+	bench_backend::<Full<MerkleTreeConfiguration>>(c, "full");
+}
+
+/// Benchmarks insertion into a single path tree.
+fn single_path(c: &mut Criterion) {
+	bench_backend::<SinglePath<MerkleTreeConfiguration>>(c, "single_path");
+}
+
+criterion_group!(benches, full, single_path);
+criterion_main!(benches);
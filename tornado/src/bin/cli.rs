@@ -3,7 +3,22 @@
 use anyhow::{bail, Result};
 use clap::{Parser, Subcommand};
 use rand_core::OsRng;
-use tornado::{config::types::Key, crypto::rand::Rand};
+use std::{fs, path::PathBuf};
+use tornado::{
+	circuit::{
+		claim_compiler, claim_cost, claim_witness_compiler, mint_compiler, mint_cost,
+		mint_witness_compiler, partial_claim_compiler, partial_claim_cost,
+		partial_claim_witness_compiler, transfer_compiler, transfer_cost,
+		transfer_witness_compiler, Claim, ClaimInput, Mint, MintInput, PartialClaim,
+		PartialClaimInput, Transfer, TransferInput,
+	},
+	config::{
+		types::Key, Config, ConstraintField, CostConfig, DefaultHeight, Parameters, RescueHash,
+		Scalar,
+	},
+	crypto::{accumulator::Accumulator, arkworks::Fp, merkle_tree::MerkleTree, rand::Rand},
+	parameters,
+};
 
 #[derive(Parser)]
 pub struct Args {
@@ -15,6 +30,8 @@ pub struct Args {
 pub enum Command {
 	Mint,
 	Claim { key: String },
+	Stats,
+	Circom { directory: PathBuf },
 }
 
 fn main() -> Result<()> {
@@ -35,6 +52,99 @@ fn main() -> Result<()> {
 			},
 			_ => bail!("Unable to parse claim key."),
 		},
+		Command::Stats => {
+			let parameters = parameters::parameters();
+			let rescue_parameters: Parameters<DefaultHeight, RescueHash> = rng.gen();
+			println!("Mint:");
+			println!("Public Input: {}", MintInput::<Config>::FIELDS.join(", "));
+			print!("{}", mint_compiler::<Config>(&parameters).statistics());
+			println!("Estimated Cost:");
+			print!("{}", mint_cost::<CostConfig>(&parameters));
+			println!("Estimated Cost with Rescue-Prime:");
+			print!("{}", mint_cost::<CostConfig<DefaultHeight, RescueHash>>(&rescue_parameters));
+			println!("Claim:");
+			println!("Public Input: {}", ClaimInput::<Config>::FIELDS.join(", "));
+			print!("{}", claim_compiler::<Config>(&parameters).statistics());
+			println!("Estimated Cost:");
+			print!("{}", claim_cost::<CostConfig>(&parameters));
+			println!("Estimated Cost with Rescue-Prime:");
+			print!("{}", claim_cost::<CostConfig<DefaultHeight, RescueHash>>(&rescue_parameters));
+			println!("Partial Claim:");
+			println!("Public Input: {}", PartialClaimInput::<Config>::FIELDS.join(", "));
+			print!("{}", partial_claim_compiler::<Config>(&parameters).statistics());
+			println!("Estimated Cost:");
+			print!("{}", partial_claim_cost::<CostConfig>(&parameters));
+			println!("Estimated Cost with Rescue-Prime:");
+			print!(
+				"{}",
+				partial_claim_cost::<CostConfig<DefaultHeight, RescueHash>>(&rescue_parameters)
+			);
+			println!("Transfer:");
+			println!("Public Input: {}", TransferInput::<Config>::FIELDS.join(", "));
+			print!("{}", transfer_compiler::<Config>(&parameters).statistics());
+			println!("Estimated Cost:");
+			print!("{}", transfer_cost::<CostConfig>(&parameters));
+			println!("Estimated Cost with Rescue-Prime:");
+			print!(
+				"{}",
+				transfer_cost::<CostConfig<DefaultHeight, RescueHash>>(&rescue_parameters)
+			);
+		},
+		Command::Circom { directory } => {
+			let parameters = parameters::parameters();
+			let [key, change_key, receiver_key_0, receiver_key_1] = [(); 4].map(|_| rng.gen());
+			let value = |value: u64| -> Scalar { Fp(ConstraintField::from(value)) };
+			let mint = Mint::<Config>::new(&parameters, key, value(100));
+			let change = Mint::<Config>::new(&parameters, change_key, value(60));
+			let mut accumulator = MerkleTree::new(parameters.merkle_tree_parameters.clone());
+			accumulator.insert(&mint.utxo);
+			accumulator.insert(&change.utxo);
+			let claim = Claim::<Config>::new(&parameters, &accumulator, key, value(100))
+				.expect("The claimed UTXO was inserted into the accumulator.");
+			let partial_claim = PartialClaim::<Config>::new(
+				&parameters,
+				&accumulator,
+				key,
+				value(100),
+				value(40),
+				change_key,
+				value(60),
+			)
+			.expect("The claimed UTXO was inserted into the accumulator.");
+			let transfer = Transfer::<Config>::new(
+				&parameters,
+				&accumulator,
+				[(key, value(100)), (change_key, value(60))],
+				[(receiver_key_0, value(80)), (receiver_key_1, value(80))],
+			)
+			.expect("The sender UTXOs were inserted into the accumulator.");
+			for (name, mut compiler) in [
+				("mint", mint_witness_compiler::<Config>(&parameters, &mint)),
+				("claim", claim_witness_compiler::<Config>(&parameters, &claim)),
+				(
+					"partial_claim",
+					partial_claim_witness_compiler::<Config>(&parameters, &partial_claim),
+				),
+				("transfer", transfer_witness_compiler::<Config>(&parameters, &transfer)),
+			] {
+				let mut bytes = Vec::new();
+				compiler
+					.circom_constraints()
+					.write(&mut bytes)
+					.expect("Writing to a vector is not allowed to fail.");
+				let path = directory.join(format!("{}.r1cs", name));
+				fs::write(&path, bytes)?;
+				println!("Wrote {}", path.display());
+				let mut bytes = Vec::new();
+				compiler
+					.circom_witness()
+					.write(&mut bytes)
+					.expect("Writing to a vector is not allowed to fail.");
+				let path = directory.join(format!("{}.wtns", name));
+				fs::write(&path, bytes)?;
+				println!("Wrote {}", path.display());
+			}
+		},
 	}
 	Ok(())
 }
@@ -1,10 +1,15 @@
 //! Subtornado Circuits
 
 use crate::crypto::{
-	accumulator::Accumulator,
-	eclair::alloc::{
-		mode::{Derived, Public, Secret},
-		Allocate, Allocator, Constant, Variable,
+	accumulator::{Accumulator, Model},
+	cost::{Cost, CostCompiler},
+	eclair::{
+		alloc::{
+			mode::{Public, Secret},
+			Allocate, Allocator, Constant, Variable,
+		},
+		num::{AssertWithinBitRange, UInt64},
+		Namespace,
 	},
 	proofsystem::ProofSystem,
 };
@@ -16,6 +21,8 @@ pub trait Parameters<COM = ()> {
 
 	fn assert_eq(&self, lhs: &Self::Field, rhs: &Self::Field, compiler: &mut COM);
 
+	fn add(&self, lhs: &Self::Field, rhs: &Self::Field, compiler: &mut COM) -> Self::Field;
+
 	fn utxo(&self, key: &Self::Field, value: &Self::Field, compiler: &mut COM) -> Self::Field;
 
 	fn void_number(&self, key: &Self::Field, utxo: &Self::Field, compiler: &mut COM)
@@ -30,8 +37,41 @@ pub trait Parameters<COM = ()> {
 	);
 }
 
+pub trait NativeParameters: Parameters {
+	fn verify_membership(
+		&self,
+		utxo: &Self::Field,
+		root: &Self::Field,
+		membership_proof: &Self::MembershipProof,
+	) -> bool;
+}
+
 pub trait Configuration {
-	type Compiler;
+	type Compiler: Namespace + AssertWithinBitRange<Self::FieldVar, 64>;
+	type Field: Clone + PartialEq;
+	type MembershipProof;
+	type Parameters: NativeParameters<Field = Self::Field, MembershipProof = Self::MembershipProof>;
+	type AccumulatorModel: Model<
+		Item = Self::Field,
+		Witness = Self::MembershipProof,
+		Output = Self::Field,
+	>;
+	type Accumulator: Accumulator<Item = Self::Field, Model = Self::AccumulatorModel>;
+	type FieldVar: Variable<Public, Self::Compiler, Type = Self::Field>
+		+ Variable<Secret, Self::Compiler, Type = Self::Field>;
+	type MembershipProofVar: Variable<Secret, Self::Compiler, Type = Self::MembershipProof>;
+	type ParametersVar: Parameters<
+			Self::Compiler,
+			Field = Self::FieldVar,
+			MembershipProof = Self::MembershipProofVar,
+		> + Constant<Self::Compiler, Type = Self::Parameters>;
+}
+
+/// Proof System Configuration
+///
+/// Extends a circuit [`Configuration`] with the proof system used to build and verify proofs of
+/// its circuits.
+pub trait ProofSystemConfiguration: Configuration {
 	type ProvingKey;
 	type VerifyingKey;
 	type Proof;
@@ -41,24 +81,67 @@ pub trait Configuration {
 		ProvingKey = Self::ProvingKey,
 		VerifyingKey = Self::VerifyingKey,
 		Proof = Self::Proof,
+		Input = Self::Field,
 		Error = Self::Error,
 	>;
-	type Field;
-	type MembershipProof;
-	type Parameters: Parameters<Field = Self::Field, MembershipProof = Self::MembershipProof>;
-	type Accumulator: Accumulator<
-		Self::Field,
-		Root = Self::Field,
-		MembershipProof = Self::MembershipProof,
-	>;
-	type FieldVar: Variable<Public, Self::Compiler, Type = Self::Field>
-		+ Variable<Secret, Self::Compiler, Type = Self::Field>;
-	type MembershipProofVar: Variable<Secret, Self::Compiler, Type = Self::MembershipProof>;
-	type ParametersVar: Parameters<
-			Self::Compiler,
-			Field = Self::FieldVar,
-			MembershipProof = Self::MembershipProofVar,
-		> + Constant<Self::Compiler, Type = Self::Parameters>;
+}
+
+/// Witness Check Error
+#[derive(Debug)]
+pub enum CheckError<E> {
+	/// UTXO does not match the key and value
+	Utxo,
+
+	/// UTXO is not a member of the accumulator with the given root
+	Membership,
+
+	/// Void number does not match the key and UTXO
+	VoidNumber,
+
+	/// Spent and created values are not equal
+	Balance,
+
+	/// Circuit is not satisfied by the witness
+	Circuit(E),
+}
+
+/// Asserts that `utxo` is the UTXO of `key` and `value`.
+#[inline]
+fn assert_utxo<C>(
+	parameters: &C::ParametersVar,
+	key: &C::FieldVar,
+	value: &C::FieldVar,
+	utxo: &C::FieldVar,
+	compiler: &mut C::Compiler,
+) where
+	C: Configuration,
+{
+	let computed_utxo =
+		compiler.namespace("utxo", |compiler| parameters.utxo(key, value, compiler));
+	parameters.assert_eq(utxo, &computed_utxo, compiler)
+}
+
+/// Asserts that the UTXO of `key` and `value` is a member of the accumulator with the given `root`
+/// and that `void_number` is its void number.
+#[inline]
+fn assert_spend<C>(
+	parameters: &C::ParametersVar,
+	key: &C::FieldVar,
+	value: &C::FieldVar,
+	root: &C::FieldVar,
+	membership_proof: &C::MembershipProofVar,
+	void_number: &C::FieldVar,
+	compiler: &mut C::Compiler,
+) where
+	C: Configuration,
+{
+	let utxo = compiler.namespace("utxo", |compiler| parameters.utxo(key, value, compiler));
+	compiler.namespace("membership", |compiler| {
+		parameters.assert_membership(&utxo, root, membership_proof, compiler)
+	});
+	let computed_void_number =
+		compiler.namespace("void number", |compiler| parameters.void_number(key, &utxo, compiler));
+	parameters.assert_eq(void_number, &computed_void_number, compiler);
 }
 
 pub struct Mint<C>
@@ -80,12 +163,19 @@ where
 	}
 }
 
+#[derive(Variable)]
+#[eclair(compiler = "C::Compiler", type = "Mint<C>", public_input = "MintInput")]
 pub struct MintVar<C>
 where
 	C: Configuration,
 {
+	#[eclair(secret)]
 	pub key: C::FieldVar,
-	pub value: C::FieldVar,
+
+	#[eclair(public)]
+	pub value: UInt64<C::FieldVar>,
+
+	#[eclair(public)]
 	pub utxo: C::FieldVar,
 }
 
@@ -95,55 +185,37 @@ where
 {
 	#[inline]
 	pub fn assert_valid(&self, parameters: &C::ParametersVar, compiler: &mut C::Compiler) {
-		parameters.assert_eq(
-			&self.utxo,
-			&parameters.utxo(&self.key, &self.value, compiler),
-			compiler,
-		)
+		assert_utxo::<C>(parameters, &self.key, self.value.value(), &self.utxo, compiler)
 	}
 }
 
-impl<C> Variable<Derived, C::Compiler> for MintVar<C>
+/// Returns the cost of the mint circuit with the given `parameters`.
+#[inline]
+pub fn mint_cost<C>(parameters: &C::Parameters) -> Cost
 where
-	C: Configuration,
+	C: Configuration<Compiler = CostCompiler>,
 {
-	type Type = Mint<C>;
-
-	#[inline]
-	fn new_unknown(compiler: &mut C::Compiler) -> Self {
-		Self {
-			key: compiler.allocate_unknown::<Secret, _>(),
-			value: compiler.allocate_unknown::<Secret, _>(),
-			utxo: compiler.allocate_unknown::<Public, _>(),
-		}
-	}
-
-	#[inline]
-	fn new_known(this: &Self::Type, compiler: &mut C::Compiler) -> Self {
-		Self {
-			key: this.key.as_known::<Secret, _>(compiler),
-			value: this.value.as_known::<Secret, _>(compiler),
-			utxo: this.utxo.as_known::<Public, _>(compiler),
-		}
-	}
+	let mut compiler = CostCompiler::new();
+	MintVar::<C>::assert_valid(
+		&compiler.allocate_unknown(),
+		&parameters.as_constant(&mut compiler),
+		&mut compiler,
+	);
+	compiler.cost()
 }
 
 pub struct MintPost<C>
 where
-	C: Configuration,
+	C: ProofSystemConfiguration,
 {
-	pub utxo: C::Field,
+	pub input: MintInput<C>,
 	pub proof: C::Proof,
 }
 
 #[inline]
-pub fn mint_keys<C, R>(
-	parameters: &C::Parameters,
-	rng: &mut R,
-) -> Result<(C::ProvingKey, C::VerifyingKey), C::Error>
+pub fn mint_compiler<C>(parameters: &C::Parameters) -> C::Compiler
 where
-	C: Configuration,
-	R: CryptoRng + RngCore + ?Sized,
+	C: ProofSystemConfiguration,
 {
 	let mut compiler = C::ProofSystem::for_compile();
 	MintVar::<C>::assert_valid(
@@ -151,7 +223,34 @@ where
 		&parameters.as_constant(&mut compiler),
 		&mut compiler,
 	);
-	C::ProofSystem::compile(compiler, rng)
+	compiler
+}
+
+/// Returns the mint circuit in prove mode with the witness given by `data`.
+#[inline]
+pub fn mint_witness_compiler<C>(parameters: &C::Parameters, data: &Mint<C>) -> C::Compiler
+where
+	C: ProofSystemConfiguration,
+{
+	let mut compiler = C::ProofSystem::for_prove();
+	MintVar::<C>::assert_valid(
+		&data.as_known(&mut compiler),
+		&parameters.as_constant(&mut compiler),
+		&mut compiler,
+	);
+	compiler
+}
+
+#[inline]
+pub fn mint_keys<C, R>(
+	parameters: &C::Parameters,
+	rng: &mut R,
+) -> Result<(C::ProvingKey, C::VerifyingKey), C::Error>
+where
+	C: ProofSystemConfiguration,
+	R: CryptoRng + RngCore + ?Sized,
+{
+	C::ProofSystem::compile(mint_compiler::<C>(parameters), rng)
 }
 
 #[inline]
@@ -163,17 +262,38 @@ pub fn mint<C, R>(
 	rng: &mut R,
 ) -> Result<MintPost<C>, C::Error>
 where
-	C: Configuration,
+	C: ProofSystemConfiguration,
 	R: CryptoRng + RngCore + ?Sized,
 {
 	let data = Mint::new(parameters, key, value);
-	let mut compiler = C::ProofSystem::for_prove();
-	MintVar::<C>::assert_valid(
-		&data.as_known(&mut compiler),
-		&parameters.as_constant(&mut compiler),
-		&mut compiler,
-	);
-	Ok(MintPost { utxo: data.utxo, proof: C::ProofSystem::prove(proving_key, compiler, rng)? })
+	let compiler = mint_witness_compiler(parameters, &data);
+	Ok(MintPost {
+		input: MintInput::from_known(&data),
+		proof: C::ProofSystem::prove(proving_key, compiler, rng)?,
+	})
+}
+
+#[inline]
+pub fn verify_mint<C>(
+	verifying_key: &C::VerifyingKey,
+	input: &MintInput<C>,
+	proof: &C::Proof,
+) -> Result<bool, C::Error>
+where
+	C: ProofSystemConfiguration,
+{
+	C::ProofSystem::verify(verifying_key, &input.to_field_elements(), proof)
+}
+
+#[inline]
+pub fn check_mint<C>(parameters: &C::Parameters, data: &Mint<C>) -> Result<(), CheckError<C::Error>>
+where
+	C: ProofSystemConfiguration,
+{
+	if parameters.utxo(&data.key, &data.value, &mut ()) != data.utxo {
+		return Err(CheckError::Utxo)
+	}
+	C::ProofSystem::check(&mint_witness_compiler(parameters, data)).map_err(CheckError::Circuit)
 }
 
 pub struct Claim<C>
@@ -199,7 +319,7 @@ where
 		value: C::Field,
 	) -> Option<Self> {
 		let utxo = parameters.utxo(&key, &value, &mut ());
-		let (root, membership_proof) = accumulator.membership_proof(&utxo)?;
+		let (membership_proof, root) = accumulator.prove(&utxo)?.into_parts();
 		Some(Self {
 			void_number: parameters.void_number(&key, &utxo, &mut ()),
 			key,
@@ -210,14 +330,25 @@ where
 	}
 }
 
+#[derive(Variable)]
+#[eclair(compiler = "C::Compiler", type = "Claim<C>", public_input = "ClaimInput")]
 pub struct ClaimVar<C>
 where
 	C: Configuration,
 {
+	#[eclair(secret)]
 	pub key: C::FieldVar,
-	pub value: C::FieldVar,
+
+	#[eclair(public)]
+	pub value: UInt64<C::FieldVar>,
+
+	#[eclair(public)]
 	pub root: C::FieldVar,
+
+	#[eclair(secret)]
 	pub membership_proof: C::MembershipProofVar,
+
+	#[eclair(public)]
 	pub void_number: C::FieldVar,
 }
 
@@ -227,95 +358,869 @@ where
 {
 	#[inline]
 	pub fn assert_valid(&self, parameters: &C::ParametersVar, compiler: &mut C::Compiler) {
-		let utxo = parameters.utxo(&self.key, &self.value, compiler);
-		parameters.assert_membership(&utxo, &self.root, &self.membership_proof, compiler);
-		parameters.assert_eq(
+		assert_spend::<C>(
+			parameters,
+			&self.key,
+			self.value.value(),
+			&self.root,
+			&self.membership_proof,
 			&self.void_number,
-			&parameters.void_number(&self.key, &utxo, compiler),
 			compiler,
-		);
+		)
+	}
+}
+
+/// Returns the cost of the claim circuit with the given `parameters`.
+#[inline]
+pub fn claim_cost<C>(parameters: &C::Parameters) -> Cost
+where
+	C: Configuration<Compiler = CostCompiler>,
+{
+	let mut compiler = CostCompiler::new();
+	ClaimVar::<C>::assert_valid(
+		&compiler.allocate_unknown(),
+		&parameters.as_constant(&mut compiler),
+		&mut compiler,
+	);
+	compiler.cost()
+}
+
+pub struct ClaimPost<C>
+where
+	C: ProofSystemConfiguration,
+{
+	pub input: ClaimInput<C>,
+	pub proof: C::Proof,
+}
+
+#[inline]
+pub fn claim_compiler<C>(parameters: &C::Parameters) -> C::Compiler
+where
+	C: ProofSystemConfiguration,
+{
+	let mut compiler = C::ProofSystem::for_compile();
+	ClaimVar::<C>::assert_valid(
+		&compiler.allocate_unknown(),
+		&parameters.as_constant(&mut compiler),
+		&mut compiler,
+	);
+	compiler
+}
+
+/// Returns the claim circuit in prove mode with the witness given by `data`.
+#[inline]
+pub fn claim_witness_compiler<C>(parameters: &C::Parameters, data: &Claim<C>) -> C::Compiler
+where
+	C: ProofSystemConfiguration,
+{
+	let mut compiler = C::ProofSystem::for_prove();
+	ClaimVar::<C>::assert_valid(
+		&data.as_known(&mut compiler),
+		&parameters.as_constant(&mut compiler),
+		&mut compiler,
+	);
+	compiler
+}
+
+#[inline]
+pub fn claim_keys<C, R>(
+	parameters: &C::Parameters,
+	rng: &mut R,
+) -> Result<(C::ProvingKey, C::VerifyingKey), C::Error>
+where
+	C: ProofSystemConfiguration,
+	R: CryptoRng + RngCore + ?Sized,
+{
+	C::ProofSystem::compile(claim_compiler::<C>(parameters), rng)
+}
+
+#[inline]
+pub fn claim<C, R>(
+	proving_key: &C::ProvingKey,
+	parameters: &C::Parameters,
+	accumulator: &C::Accumulator,
+	key: C::Field,
+	value: C::Field,
+	rng: &mut R,
+) -> Result<ClaimPost<C>, C::Error>
+where
+	C: ProofSystemConfiguration,
+	R: CryptoRng + RngCore + ?Sized,
+{
+	let data = Claim::new(parameters, accumulator, key, value).expect("FIXME");
+	let compiler = claim_witness_compiler(parameters, &data);
+	Ok(ClaimPost {
+		input: ClaimInput::from_known(&data),
+		proof: C::ProofSystem::prove(proving_key, compiler, rng)?,
+	})
+}
+
+#[inline]
+pub fn verify_claim<C>(
+	verifying_key: &C::VerifyingKey,
+	input: &ClaimInput<C>,
+	proof: &C::Proof,
+) -> Result<bool, C::Error>
+where
+	C: ProofSystemConfiguration,
+{
+	C::ProofSystem::verify(verifying_key, &input.to_field_elements(), proof)
+}
+
+#[inline]
+pub fn check_claim<C>(
+	parameters: &C::Parameters,
+	data: &Claim<C>,
+) -> Result<(), CheckError<C::Error>>
+where
+	C: ProofSystemConfiguration,
+{
+	let utxo = parameters.utxo(&data.key, &data.value, &mut ());
+	if !parameters.verify_membership(&utxo, &data.root, &data.membership_proof) {
+		return Err(CheckError::Membership)
 	}
+	if parameters.void_number(&data.key, &utxo, &mut ()) != data.void_number {
+		return Err(CheckError::VoidNumber)
+	}
+	C::ProofSystem::check(&claim_witness_compiler(parameters, data)).map_err(CheckError::Circuit)
 }
 
-impl<C> Variable<Derived, C::Compiler> for ClaimVar<C>
+/// Partial Claim
+///
+/// Spends a UTXO from the accumulator with the given `root`, withdrawing the public `amount` and
+/// depositing the rest of its value into a new change UTXO, so that the withdrawal does not reveal
+/// the value of the spent UTXO.
+pub struct PartialClaim<C>
 where
 	C: Configuration,
 {
-	type Type = Claim<C>;
+	pub key: C::Field,
+	pub value: C::Field,
+	pub amount: C::Field,
+	pub root: C::Field,
+	pub membership_proof: C::MembershipProof,
+	pub void_number: C::Field,
+	pub change_key: C::Field,
+	pub change_value: C::Field,
+	pub change_utxo: C::Field,
+}
 
+impl<C> PartialClaim<C>
+where
+	C: Configuration,
+{
+	/// Builds a new [`PartialClaim`] which spends the UTXO of `key` and `value` from the
+	/// `accumulator`, withdrawing `amount` and sending `change_value` to the UTXO of `change_key`,
+	/// returning `None` if the UTXO is not in the `accumulator`.
+	///
+	/// The values are not checked to be balanced, see [`check_partial_claim`].
 	#[inline]
-	fn new_unknown(compiler: &mut C::Compiler) -> Self {
-		Self {
-			key: compiler.allocate_unknown::<Secret, _>(),
-			value: compiler.allocate_unknown::<Secret, _>(),
-			root: compiler.allocate_unknown::<Public, _>(),
-			membership_proof: compiler.allocate_unknown::<Secret, _>(),
-			void_number: compiler.allocate_unknown::<Public, _>(),
-		}
+	pub fn new(
+		parameters: &C::Parameters,
+		accumulator: &C::Accumulator,
+		key: C::Field,
+		value: C::Field,
+		amount: C::Field,
+		change_key: C::Field,
+		change_value: C::Field,
+	) -> Option<Self> {
+		let utxo = parameters.utxo(&key, &value, &mut ());
+		let (membership_proof, root) = accumulator.prove(&utxo)?.into_parts();
+		Some(Self {
+			void_number: parameters.void_number(&key, &utxo, &mut ()),
+			change_utxo: parameters.utxo(&change_key, &change_value, &mut ()),
+			key,
+			value,
+			amount,
+			root,
+			membership_proof,
+			change_key,
+			change_value,
+		})
 	}
+}
+
+#[derive(Variable)]
+#[eclair(compiler = "C::Compiler", type = "PartialClaim<C>", public_input = "PartialClaimInput")]
+pub struct PartialClaimVar<C>
+where
+	C: Configuration,
+{
+	#[eclair(secret)]
+	pub key: C::FieldVar,
 
+	#[eclair(secret)]
+	pub value: UInt64<C::FieldVar>,
+
+	#[eclair(public)]
+	pub amount: UInt64<C::FieldVar>,
+
+	#[eclair(public)]
+	pub root: C::FieldVar,
+
+	#[eclair(secret)]
+	pub membership_proof: C::MembershipProofVar,
+
+	#[eclair(public)]
+	pub void_number: C::FieldVar,
+
+	#[eclair(secret)]
+	pub change_key: C::FieldVar,
+
+	#[eclair(secret)]
+	pub change_value: UInt64<C::FieldVar>,
+
+	#[eclair(public)]
+	pub change_utxo: C::FieldVar,
+}
+
+impl<C> PartialClaimVar<C>
+where
+	C: Configuration,
+{
+	/// Asserts that the UTXO is spent from the accumulator, that the change UTXO is well-formed,
+	/// and that the spent value is the sum of the withdrawn amount and the change value.
+	///
+	/// The amount and the change value are both range checked to 64 bits when they are
+	/// allocated, so their sum cannot wrap around the modulus of the field and the amount is never
+	/// larger than the spent value.
 	#[inline]
-	fn new_known(this: &Self::Type, compiler: &mut C::Compiler) -> Self {
-		Self {
-			key: this.key.as_known::<Secret, _>(compiler),
-			value: this.value.as_known::<Secret, _>(compiler),
-			root: this.root.as_known::<Public, _>(compiler),
-			membership_proof: this.membership_proof.as_known::<Secret, _>(compiler),
-			void_number: this.void_number.as_known::<Public, _>(compiler),
-		}
+	pub fn assert_valid(&self, parameters: &C::ParametersVar, compiler: &mut C::Compiler) {
+		assert_spend::<C>(
+			parameters,
+			&self.key,
+			self.value.value(),
+			&self.root,
+			&self.membership_proof,
+			&self.void_number,
+			compiler,
+		);
+		compiler.namespace("change", |compiler| {
+			assert_utxo::<C>(
+				parameters,
+				&self.change_key,
+				self.change_value.value(),
+				&self.change_utxo,
+				compiler,
+			)
+		});
+		compiler.namespace("balance", |compiler| {
+			let output = parameters.add(self.amount.value(), self.change_value.value(), compiler);
+			parameters.assert_eq(self.value.value(), &output, compiler)
+		});
 	}
 }
 
-pub struct ClaimPost<C>
+/// Returns the cost of the partial claim circuit with the given `parameters`.
+#[inline]
+pub fn partial_claim_cost<C>(parameters: &C::Parameters) -> Cost
 where
-	C: Configuration,
+	C: Configuration<Compiler = CostCompiler>,
 {
-	pub root: C::Field,
-	pub void_number: C::Field,
+	let mut compiler = CostCompiler::new();
+	PartialClaimVar::<C>::assert_valid(
+		&compiler.allocate_unknown(),
+		&parameters.as_constant(&mut compiler),
+		&mut compiler,
+	);
+	compiler.cost()
+}
+
+pub struct PartialClaimPost<C>
+where
+	C: ProofSystemConfiguration,
+{
+	pub input: PartialClaimInput<C>,
 	pub proof: C::Proof,
 }
 
 #[inline]
-pub fn claim_keys<C, R>(
-	parameters: &C::Parameters,
-	rng: &mut R,
-) -> Result<(C::ProvingKey, C::VerifyingKey), C::Error>
+pub fn partial_claim_compiler<C>(parameters: &C::Parameters) -> C::Compiler
 where
-	C: Configuration,
-	R: CryptoRng + RngCore + ?Sized,
+	C: ProofSystemConfiguration,
 {
 	let mut compiler = C::ProofSystem::for_compile();
-	ClaimVar::<C>::assert_valid(
+	PartialClaimVar::<C>::assert_valid(
 		&compiler.allocate_unknown(),
 		&parameters.as_constant(&mut compiler),
 		&mut compiler,
 	);
-	C::ProofSystem::compile(compiler, rng)
+	compiler
 }
 
+/// Returns the partial claim circuit in prove mode with the witness given by `data`.
 #[inline]
-pub fn claim<C, R>(
+pub fn partial_claim_witness_compiler<C>(
+	parameters: &C::Parameters,
+	data: &PartialClaim<C>,
+) -> C::Compiler
+where
+	C: ProofSystemConfiguration,
+{
+	let mut compiler = C::ProofSystem::for_prove();
+	PartialClaimVar::<C>::assert_valid(
+		&data.as_known(&mut compiler),
+		&parameters.as_constant(&mut compiler),
+		&mut compiler,
+	);
+	compiler
+}
+
+#[inline]
+pub fn partial_claim_keys<C, R>(
+	parameters: &C::Parameters,
+	rng: &mut R,
+) -> Result<(C::ProvingKey, C::VerifyingKey), C::Error>
+where
+	C: ProofSystemConfiguration,
+	R: CryptoRng + RngCore + ?Sized,
+{
+	C::ProofSystem::compile(partial_claim_compiler::<C>(parameters), rng)
+}
+
+#[allow(clippy::too_many_arguments)]
+#[inline]
+pub fn partial_claim<C, R>(
 	proving_key: &C::ProvingKey,
 	parameters: &C::Parameters,
 	accumulator: &C::Accumulator,
 	key: C::Field,
 	value: C::Field,
+	amount: C::Field,
+	change_key: C::Field,
+	change_value: C::Field,
 	rng: &mut R,
-) -> Result<ClaimPost<C>, C::Error>
+) -> Result<PartialClaimPost<C>, C::Error>
 where
-	C: Configuration,
+	C: ProofSystemConfiguration,
 	R: CryptoRng + RngCore + ?Sized,
 {
-	let data = Claim::new(parameters, accumulator, key, value).expect("FIXME");
+	let data =
+		PartialClaim::new(parameters, accumulator, key, value, amount, change_key, change_value)
+			.expect("Spent UTXOs are not allowed to be missing from the accumulator.");
+	let compiler = partial_claim_witness_compiler(parameters, &data);
+	Ok(PartialClaimPost {
+		input: PartialClaimInput::from_known(&data),
+		proof: C::ProofSystem::prove(proving_key, compiler, rng)?,
+	})
+}
+
+#[inline]
+pub fn verify_partial_claim<C>(
+	verifying_key: &C::VerifyingKey,
+	input: &PartialClaimInput<C>,
+	proof: &C::Proof,
+) -> Result<bool, C::Error>
+where
+	C: ProofSystemConfiguration,
+{
+	C::ProofSystem::verify(verifying_key, &input.to_field_elements(), proof)
+}
+
+#[inline]
+pub fn check_partial_claim<C>(
+	parameters: &C::Parameters,
+	data: &PartialClaim<C>,
+) -> Result<(), CheckError<C::Error>>
+where
+	C: ProofSystemConfiguration,
+{
+	let utxo = parameters.utxo(&data.key, &data.value, &mut ());
+	if !parameters.verify_membership(&utxo, &data.root, &data.membership_proof) {
+		return Err(CheckError::Membership)
+	}
+	if parameters.void_number(&data.key, &utxo, &mut ()) != data.void_number {
+		return Err(CheckError::VoidNumber)
+	}
+	if parameters.utxo(&data.change_key, &data.change_value, &mut ()) != data.change_utxo {
+		return Err(CheckError::Utxo)
+	}
+	if parameters.add(&data.amount, &data.change_value, &mut ()) != data.value {
+		return Err(CheckError::Balance)
+	}
+	C::ProofSystem::check(&partial_claim_witness_compiler(parameters, data))
+		.map_err(CheckError::Circuit)
+}
+
+/// Private Transfer
+///
+/// Spends two UTXOs from the accumulator with the given `root` and creates two new UTXOs with the
+/// same total value, so that notes can be split and merged without leaving the pool.
+pub struct Transfer<C>
+where
+	C: Configuration,
+{
+	pub root: C::Field,
+	pub sender_key_0: C::Field,
+	pub sender_value_0: C::Field,
+	pub sender_membership_proof_0: C::MembershipProof,
+	pub void_number_0: C::Field,
+	pub sender_key_1: C::Field,
+	pub sender_value_1: C::Field,
+	pub sender_membership_proof_1: C::MembershipProof,
+	pub void_number_1: C::Field,
+	pub receiver_key_0: C::Field,
+	pub receiver_value_0: C::Field,
+	pub utxo_0: C::Field,
+	pub receiver_key_1: C::Field,
+	pub receiver_value_1: C::Field,
+	pub utxo_1: C::Field,
+}
+
+impl<C> Transfer<C>
+where
+	C: Configuration,
+{
+	/// Builds a new [`Transfer`] which spends the UTXOs of the `senders` keys and values from the
+	/// `accumulator` and creates the UTXOs of the `receivers` keys and values, returning `None` if
+	/// either sender UTXO is not in the `accumulator`.
+	///
+	/// The values are not checked to be balanced, see [`check_transfer`].
+	#[inline]
+	pub fn new(
+		parameters: &C::Parameters,
+		accumulator: &C::Accumulator,
+		senders: [(C::Field, C::Field); 2],
+		receivers: [(C::Field, C::Field); 2],
+	) -> Option<Self> {
+		let [(sender_key_0, sender_value_0), (sender_key_1, sender_value_1)] = senders;
+		let [(receiver_key_0, receiver_value_0), (receiver_key_1, receiver_value_1)] = receivers;
+		let sender_utxo_0 = parameters.utxo(&sender_key_0, &sender_value_0, &mut ());
+		let sender_utxo_1 = parameters.utxo(&sender_key_1, &sender_value_1, &mut ());
+		let (sender_membership_proof_0, root) = accumulator.prove(&sender_utxo_0)?.into_parts();
+		let (sender_membership_proof_1, _) = accumulator.prove(&sender_utxo_1)?.into_parts();
+		Some(Self {
+			root,
+			void_number_0: parameters.void_number(&sender_key_0, &sender_utxo_0, &mut ()),
+			void_number_1: parameters.void_number(&sender_key_1, &sender_utxo_1, &mut ()),
+			utxo_0: parameters.utxo(&receiver_key_0, &receiver_value_0, &mut ()),
+			utxo_1: parameters.utxo(&receiver_key_1, &receiver_value_1, &mut ()),
+			sender_key_0,
+			sender_value_0,
+			sender_membership_proof_0,
+			sender_key_1,
+			sender_value_1,
+			sender_membership_proof_1,
+			receiver_key_0,
+			receiver_value_0,
+			receiver_key_1,
+			receiver_value_1,
+		})
+	}
+}
+
+#[derive(Variable)]
+#[eclair(compiler = "C::Compiler", type = "Transfer<C>", public_input = "TransferInput")]
+pub struct TransferVar<C>
+where
+	C: Configuration,
+{
+	#[eclair(public)]
+	pub root: C::FieldVar,
+
+	#[eclair(secret)]
+	pub sender_key_0: C::FieldVar,
+
+	#[eclair(secret)]
+	pub sender_value_0: UInt64<C::FieldVar>,
+
+	#[eclair(secret)]
+	pub sender_membership_proof_0: C::MembershipProofVar,
+
+	#[eclair(public)]
+	pub void_number_0: C::FieldVar,
+
+	#[eclair(secret)]
+	pub sender_key_1: C::FieldVar,
+
+	#[eclair(secret)]
+	pub sender_value_1: UInt64<C::FieldVar>,
+
+	#[eclair(secret)]
+	pub sender_membership_proof_1: C::MembershipProofVar,
+
+	#[eclair(public)]
+	pub void_number_1: C::FieldVar,
+
+	#[eclair(secret)]
+	pub receiver_key_0: C::FieldVar,
+
+	#[eclair(secret)]
+	pub receiver_value_0: UInt64<C::FieldVar>,
+
+	#[eclair(public)]
+	pub utxo_0: C::FieldVar,
+
+	#[eclair(secret)]
+	pub receiver_key_1: C::FieldVar,
+
+	#[eclair(secret)]
+	pub receiver_value_1: UInt64<C::FieldVar>,
+
+	#[eclair(public)]
+	pub utxo_1: C::FieldVar,
+}
+
+impl<C> TransferVar<C>
+where
+	C: Configuration,
+{
+	/// Asserts that both senders are spent from the accumulator, that both receiver UTXOs are
+	/// well-formed, and that the spent and created values are equal.
+	///
+	/// Every value is range checked to 64 bits when it is allocated, so neither sum can wrap
+	/// around the modulus of the field.
+	#[inline]
+	pub fn assert_valid(&self, parameters: &C::ParametersVar, compiler: &mut C::Compiler) {
+		compiler.namespace("sender 0", |compiler| {
+			assert_spend::<C>(
+				parameters,
+				&self.sender_key_0,
+				self.sender_value_0.value(),
+				&self.root,
+				&self.sender_membership_proof_0,
+				&self.void_number_0,
+				compiler,
+			)
+		});
+		compiler.namespace("sender 1", |compiler| {
+			assert_spend::<C>(
+				parameters,
+				&self.sender_key_1,
+				self.sender_value_1.value(),
+				&self.root,
+				&self.sender_membership_proof_1,
+				&self.void_number_1,
+				compiler,
+			)
+		});
+		compiler.namespace("receiver 0", |compiler| {
+			assert_utxo::<C>(
+				parameters,
+				&self.receiver_key_0,
+				self.receiver_value_0.value(),
+				&self.utxo_0,
+				compiler,
+			)
+		});
+		compiler.namespace("receiver 1", |compiler| {
+			assert_utxo::<C>(
+				parameters,
+				&self.receiver_key_1,
+				self.receiver_value_1.value(),
+				&self.utxo_1,
+				compiler,
+			)
+		});
+		compiler.namespace("balance", |compiler| {
+			let input =
+				parameters.add(self.sender_value_0.value(), self.sender_value_1.value(), compiler);
+			let output = parameters.add(
+				self.receiver_value_0.value(),
+				self.receiver_value_1.value(),
+				compiler,
+			);
+			parameters.assert_eq(&input, &output, compiler)
+		});
+	}
+}
+
+/// Returns the cost of the transfer circuit with the given `parameters`.
+#[inline]
+pub fn transfer_cost<C>(parameters: &C::Parameters) -> Cost
+where
+	C: Configuration<Compiler = CostCompiler>,
+{
+	let mut compiler = CostCompiler::new();
+	TransferVar::<C>::assert_valid(
+		&compiler.allocate_unknown(),
+		&parameters.as_constant(&mut compiler),
+		&mut compiler,
+	);
+	compiler.cost()
+}
+
+pub struct TransferPost<C>
+where
+	C: ProofSystemConfiguration,
+{
+	pub input: TransferInput<C>,
+	pub proof: C::Proof,
+}
+
+#[inline]
+pub fn transfer_compiler<C>(parameters: &C::Parameters) -> C::Compiler
+where
+	C: ProofSystemConfiguration,
+{
+	let mut compiler = C::ProofSystem::for_compile();
+	TransferVar::<C>::assert_valid(
+		&compiler.allocate_unknown(),
+		&parameters.as_constant(&mut compiler),
+		&mut compiler,
+	);
+	compiler
+}
+
+/// Returns the transfer circuit in prove mode with the witness given by `data`.
+#[inline]
+pub fn transfer_witness_compiler<C>(parameters: &C::Parameters, data: &Transfer<C>) -> C::Compiler
+where
+	C: ProofSystemConfiguration,
+{
 	let mut compiler = C::ProofSystem::for_prove();
-	ClaimVar::<C>::assert_valid(
+	TransferVar::<C>::assert_valid(
 		&data.as_known(&mut compiler),
 		&parameters.as_constant(&mut compiler),
 		&mut compiler,
 	);
-	Ok(ClaimPost {
-		root: data.root,
-		void_number: data.void_number,
+	compiler
+}
+
+#[inline]
+pub fn transfer_keys<C, R>(
+	parameters: &C::Parameters,
+	rng: &mut R,
+) -> Result<(C::ProvingKey, C::VerifyingKey), C::Error>
+where
+	C: ProofSystemConfiguration,
+	R: CryptoRng + RngCore + ?Sized,
+{
+	C::ProofSystem::compile(transfer_compiler::<C>(parameters), rng)
+}
+
+#[inline]
+pub fn transfer<C, R>(
+	proving_key: &C::ProvingKey,
+	parameters: &C::Parameters,
+	accumulator: &C::Accumulator,
+	senders: [(C::Field, C::Field); 2],
+	receivers: [(C::Field, C::Field); 2],
+	rng: &mut R,
+) -> Result<TransferPost<C>, C::Error>
+where
+	C: ProofSystemConfiguration,
+	R: CryptoRng + RngCore + ?Sized,
+{
+	let data = Transfer::new(parameters, accumulator, senders, receivers)
+		.expect("Sender UTXOs are not allowed to be missing from the accumulator.");
+	let compiler = transfer_witness_compiler(parameters, &data);
+	Ok(TransferPost {
+		input: TransferInput::from_known(&data),
 		proof: C::ProofSystem::prove(proving_key, compiler, rng)?,
 	})
 }
+
+#[inline]
+pub fn verify_transfer<C>(
+	verifying_key: &C::VerifyingKey,
+	input: &TransferInput<C>,
+	proof: &C::Proof,
+) -> Result<bool, C::Error>
+where
+	C: ProofSystemConfiguration,
+{
+	C::ProofSystem::verify(verifying_key, &input.to_field_elements(), proof)
+}
+
+#[inline]
+pub fn check_transfer<C>(
+	parameters: &C::Parameters,
+	data: &Transfer<C>,
+) -> Result<(), CheckError<C::Error>>
+where
+	C: ProofSystemConfiguration,
+{
+	for (key, value, membership_proof, void_number) in [
+		(
+			&data.sender_key_0,
+			&data.sender_value_0,
+			&data.sender_membership_proof_0,
+			&data.void_number_0,
+		),
+		(
+			&data.sender_key_1,
+			&data.sender_value_1,
+			&data.sender_membership_proof_1,
+			&data.void_number_1,
+		),
+	] {
+		let utxo = parameters.utxo(key, value, &mut ());
+		if !parameters.verify_membership(&utxo, &data.root, membership_proof) {
+			return Err(CheckError::Membership)
+		}
+		if parameters.void_number(key, &utxo, &mut ()) != *void_number {
+			return Err(CheckError::VoidNumber)
+		}
+	}
+	for (key, value, utxo) in [
+		(&data.receiver_key_0, &data.receiver_value_0, &data.utxo_0),
+		(&data.receiver_key_1, &data.receiver_value_1, &data.utxo_1),
+	] {
+		if parameters.utxo(key, value, &mut ()) != *utxo {
+			return Err(CheckError::Utxo)
+		}
+	}
+	if parameters.add(&data.sender_value_0, &data.sender_value_1, &mut ())
+		!= parameters.add(&data.receiver_value_0, &data.receiver_value_1, &mut ())
+	{
+		return Err(CheckError::Balance)
+	}
+	C::ProofSystem::check(&transfer_witness_compiler(parameters, data)).map_err(CheckError::Circuit)
+}
+
+/// Testing Framework
+pub mod test {
+	use super::*;
+	use crate::{
+		config::{Config, ConstraintField, DefaultHeight, Scalar},
+		crypto::{
+			arkworks::Fp,
+			merkle_tree::MerkleTree,
+			rand::{Rand, RngCore},
+		},
+		parameters::parameters,
+	};
+
+	/// Converts `value` into a field element.
+	#[inline]
+	fn value(value: u64) -> Scalar {
+		Fp(ConstraintField::from(value))
+	}
+
+	/// Builds a partial claim which spends a random key with the given `value`, withdrawing
+	/// `amount` and sending `change_value` to a random change key, and checks it with
+	/// [`check_partial_claim`].
+	#[inline]
+	pub fn check_partial_claim_values<R>(
+		rng: &mut R,
+		value: u64,
+		amount: u64,
+		change_value: u64,
+	) -> Result<(), CheckError<<Config as ProofSystemConfiguration>::Error>>
+	where
+		R: RngCore + ?Sized,
+	{
+		let parameters = parameters::<DefaultHeight>();
+		let key = rng.gen::<_, Scalar>();
+		let value = self::value(value);
+		let mut accumulator = MerkleTree::new(parameters.merkle_tree_parameters.clone());
+		accumulator.insert(&parameters.utxo(&key, &value, &mut ()));
+		let data = PartialClaim::<Config>::new(
+			&parameters,
+			&accumulator,
+			key,
+			value,
+			self::value(amount),
+			rng.gen(),
+			self::value(change_value),
+		)
+		.expect("The claimed UTXO was inserted into the accumulator.");
+		check_partial_claim(&parameters, &data)
+	}
+
+	/// Asserts that a partial claim whose amount and change add up to the claimed value passes
+	/// [`check_partial_claim`], and that one which withdraws more than the claimed value is
+	/// rejected with [`CheckError::Balance`].
+	#[inline]
+	pub fn assert_partial_claim_balance<R>(rng: &mut R)
+	where
+		R: RngCore + ?Sized,
+	{
+		assert!(
+			check_partial_claim_values(rng, 100, 60, 40).is_ok(),
+			"Balanced partial claims must pass the check."
+		);
+		assert!(
+			matches!(check_partial_claim_values(rng, 100, 70, 40), Err(CheckError::Balance)),
+			"Unbalanced partial claims must be rejected with a balance error."
+		);
+	}
+
+	/// Asserts that the amount and change of a partial claim are added without wrapping, so that
+	/// a partial claim of [`u64::MAX`] passes when it balances, and that one which would only
+	/// balance if `amount + change_value` wrapped around [`u64::MAX`] is rejected with
+	/// [`CheckError::Balance`].
+	#[inline]
+	pub fn assert_partial_claim_overflow<R>(rng: &mut R)
+	where
+		R: RngCore + ?Sized,
+	{
+		assert!(
+			check_partial_claim_values(rng, u64::MAX, u64::MAX - 1, 1).is_ok(),
+			"Balanced partial claims of `u64::MAX` must pass the check."
+		);
+		assert!(
+			matches!(check_partial_claim_values(rng, 5, u64::MAX, 6), Err(CheckError::Balance)),
+			"Partial claims are not allowed to balance by wrapping around `u64::MAX`."
+		);
+	}
+
+	/// Builds a transfer from two random senders with the `senders` values to two random receivers
+	/// with the `receivers` values, and checks it with [`check_transfer`].
+	#[inline]
+	pub fn check_transfer_values<R>(
+		rng: &mut R,
+		senders: [u64; 2],
+		receivers: [u64; 2],
+	) -> Result<(), CheckError<<Config as ProofSystemConfiguration>::Error>>
+	where
+		R: RngCore + ?Sized,
+	{
+		let parameters = parameters::<DefaultHeight>();
+		let senders = senders.map(|sender| (rng.gen::<_, Scalar>(), value(sender)));
+		let receivers = receivers.map(|receiver| (rng.gen::<_, Scalar>(), value(receiver)));
+		let mut accumulator = MerkleTree::new(parameters.merkle_tree_parameters.clone());
+		for (key, value) in &senders {
+			accumulator.insert(&parameters.utxo(key, value, &mut ()));
+		}
+		let data = Transfer::<Config>::new(&parameters, &accumulator, senders, receivers)
+			.expect("Both sender UTXOs were inserted into the accumulator.");
+		check_transfer(&parameters, &data)
+	}
+
+	/// Asserts that a transfer whose spent and created values are equal passes
+	/// [`check_transfer`].
+	#[inline]
+	pub fn assert_balanced_transfer<R>(rng: &mut R)
+	where
+		R: RngCore + ?Sized,
+	{
+		assert!(
+			check_transfer_values(rng, [60, 40], [80, 20]).is_ok(),
+			"Balanced transfers must pass the check."
+		);
+	}
+
+	/// Asserts that a transfer which creates more value than it spends is rejected with
+	/// [`CheckError::Balance`].
+	#[inline]
+	pub fn assert_unbalanced_transfer<R>(rng: &mut R)
+	where
+		R: RngCore + ?Sized,
+	{
+		assert!(
+			matches!(check_transfer_values(rng, [60, 40], [80, 40]), Err(CheckError::Balance)),
+			"Unbalanced transfers must be rejected with a balance error."
+		);
+	}
+
+	/// Asserts that transfer values near [`u64::MAX`] are added without wrapping, so that a
+	/// transfer whose sums exceed [`u64::MAX`] passes when they are equal, and that a transfer
+	/// which would only balance if the created values wrapped around [`u64::MAX`] is rejected
+	/// with [`CheckError::Balance`].
+	#[inline]
+	pub fn assert_transfer_overflow<R>(rng: &mut R)
+	where
+		R: RngCore + ?Sized,
+	{
+		assert!(
+			check_transfer_values(rng, [u64::MAX, 1], [u64::MAX - 1, 2]).is_ok(),
+			"Balanced transfers must pass the check even if their sums exceed `u64::MAX`."
+		);
+		assert!(
+			matches!(check_transfer_values(rng, [5, 0], [u64::MAX, 6]), Err(CheckError::Balance)),
+			"Transfers are not allowed to balance by wrapping around `u64::MAX`."
+		);
+	}
+}
@@ -1,27 +1,78 @@
 //! Subtornado Configuration
 
 use crate::{
-	circuit::{self, Configuration},
+	circuit::{self, Configuration, ProofSystemConfiguration},
 	crypto::{
-		accumulator,
-		arkworks::{Fp, FpVar, R1CS},
-		eclair::{
-			alloc::{Allocate, Constant},
-			bool::{Assert, AssertEq},
-		},
+		accumulator::{AssertValidVerification, Model},
+		arkworks::{Boolean, Fp, FpVar, R1CS},
+		cost::{self, CostCompiler},
+		eclair::{self, alloc::Constant, bool::AssertEq},
+		hash::ArityHash,
 		merkle_tree::{
 			self,
 			path::{constraint::PathVar, Path},
+			sparse::{BTreeNodeMap, SparseMerkleTree},
 			test::HashParameterSampling,
 		},
 		poseidon,
 		proofsystem::{self, arkworks::Groth16},
 		rand::{Rand, RngCore, Sample},
+		rescue,
 	},
 };
+use alloc::vec::Vec;
+use ark_ff::{BigInteger, FpParameters, PrimeField};
+use ark_r1cs_std::bits::ToBitsGadget;
+use core::marker::PhantomData;
 
+/// Default Merkle Tree Height
 pub const MERKLE_TREE_HEIGHT: usize = 20;
 
+/// Merkle Tree Height
+///
+/// The height of the UTXO merkle tree is chosen at the type level, so that the same code can
+/// deploy pools of different sizes and every pool gets its own claim circuit.
+pub trait MerkleTreeHeight {
+	/// Height of the Merkle Tree
+	///
+	/// # Contract
+	///
+	/// Trees must always have height at least `2`.
+	const HEIGHT: usize;
+}
+
+/// Merkle Tree Height Marker
+///
+/// Implements [`MerkleTreeHeight`] for the height `HEIGHT`.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Height<const HEIGHT: usize>;
+
+impl<const HEIGHT: usize> MerkleTreeHeight for Height<HEIGHT> {
+	const HEIGHT: usize = HEIGHT;
+}
+
+/// Default Merkle Tree Height Marker
+pub type DefaultHeight = Height<MERKLE_TREE_HEIGHT>;
+
+/// Depth of the Void Number Tree
+///
+/// Void numbers are placed in the sparse merkle tree by all of their bits, so that distinct void
+/// numbers never share a leaf.
+pub const VOID_NUMBER_TREE_DEPTH: usize =
+	<<ConstraintField as PrimeField>::Params as FpParameters>::MODULUS_BITS as usize;
+
+/// Poseidon Domain for Joining Two Merkle Tree Leaves
+pub const LEAF_DOMAIN: u64 = 1;
+
+/// Poseidon Domain for Joining Two Merkle Tree Inner Nodes
+pub const INNER_DOMAIN: u64 = 2;
+
+/// Poseidon Domain for UTXO Commitments
+pub const UTXO_DOMAIN: u64 = 3;
+
+/// Poseidon Domain for Void Number Derivation
+pub const VOID_NUMBER_DOMAIN: u64 = 4;
+
 pub type Pairing = ark_bls12_381::Bls12_381;
 pub type ConstraintField = ark_bls12_381::Fr;
 pub type Scalar = Fp<ConstraintField>;
@@ -37,52 +88,106 @@ impl poseidon::arkworks::Specification for PoseidonSpec<2> {
 	const SBOX_EXPONENT: u64 = 5;
 }
 
-pub type Poseidon2 = poseidon::Hasher<PoseidonSpec<2>, 2>;
-pub type Poseidon2Var = poseidon::Hasher<PoseidonSpec<2>, 2, Compiler>;
+impl poseidon::arkworks::Specification for PoseidonSpec<3> {
+	type Field = ConstraintField;
+	const FULL_ROUNDS: usize = 8;
+	const PARTIAL_ROUNDS: usize = 56;
+	const SBOX_EXPONENT: u64 = 5;
+}
 
-pub struct MerkleTreeInnerHash;
+impl poseidon::arkworks::Specification for PoseidonSpec<4> {
+	type Field = ConstraintField;
+	const FULL_ROUNDS: usize = 8;
+	const PARTIAL_ROUNDS: usize = 60;
+	const SBOX_EXPONENT: u64 = 5;
+}
 
-impl merkle_tree::InnerHash for MerkleTreeInnerHash {
-	type LeafDigest = Scalar;
-	type Parameters = Poseidon2;
-	type Output = Scalar;
+impl poseidon::arkworks::Specification for PoseidonSpec<5> {
+	type Field = ConstraintField;
+	const FULL_ROUNDS: usize = 8;
+	const PARTIAL_ROUNDS: usize = 60;
+	const SBOX_EXPONENT: u64 = 5;
+}
 
-	#[inline]
-	fn join(
-		parameters: &Self::Parameters,
-		lhs: &Self::Output,
-		rhs: &Self::Output,
-		compiler: &mut (),
-	) -> Self::Output {
-		parameters.hash([lhs, rhs], compiler)
-	}
+pub type Poseidon2<COM = ()> = poseidon::Hasher<PoseidonSpec<2>, 2, COM>;
+pub type Poseidon2Var = Poseidon2<Compiler>;
+pub type Poseidon3<COM = ()> = poseidon::Hasher<PoseidonSpec<3>, 3, COM>;
+pub type Poseidon3Var = Poseidon3<Compiler>;
+pub type Poseidon4<COM = ()> = poseidon::Hasher<PoseidonSpec<4>, 4, COM>;
+pub type Poseidon4Var = Poseidon4<Compiler>;
+pub type Poseidon5<COM = ()> = poseidon::Hasher<PoseidonSpec<5>, 5, COM>;
+pub type Poseidon5Var = Poseidon5<Compiler>;
+pub type OptimizedPoseidon2<COM = ()> = poseidon::OptimizedHasher<PoseidonSpec<2>, 2, COM>;
+pub type OptimizedPoseidon2Var = OptimizedPoseidon2<Compiler>;
 
-	#[inline]
-	fn join_leaves(
-		parameters: &Self::Parameters,
-		lhs: &Self::LeafDigest,
-		rhs: &Self::LeafDigest,
-		compiler: &mut (),
-	) -> Self::Output {
-		parameters.hash([lhs, rhs], compiler)
-	}
+pub struct RescueSpec<const ARITY: usize>;
+
+impl rescue::arkworks::Specification for RescueSpec<2> {
+	type Field = ConstraintField;
+	const ROUNDS: usize = 8;
+	const SBOX_EXPONENT: u64 = 5;
 }
 
-pub struct MerkleTreeInnerHashVar;
+pub type Rescue2<COM = ()> = rescue::Hasher<RescueSpec<2>, 2, COM>;
+pub type Rescue2Var = Rescue2<Compiler>;
+
+/// Field Element Type
+///
+/// This is [`Scalar`] in the native compiler and [`ScalarVar`] inside of the [`Compiler`].
+pub type Field<COM = ()> = <PoseidonSpec<2> as poseidon::Specification<COM>>::Field;
+
+/// Hash Selection
+///
+/// Chooses the two-input hash function used for UTXO commitments and void numbers in the
+/// [`Parameters`], see [`PoseidonHash`] and [`RescueHash`].
+pub trait HashSelection<COM = ()>
+where
+	PoseidonSpec<2>: poseidon::Specification<COM>,
+{
+	/// Hash Function Type
+	type Hash: ArityHash<2, COM, Input = Field<COM>, Output = Field<COM>>;
+}
+
+/// Poseidon Hash Selection
+pub struct PoseidonHash;
+
+impl<COM> HashSelection<COM> for PoseidonHash
+where
+	PoseidonSpec<2>: poseidon::Specification<COM>,
+{
+	type Hash = Poseidon2<COM>;
+}
+
+/// Rescue-Prime Hash Selection
+pub struct RescueHash;
+
+impl<COM> HashSelection<COM> for RescueHash
+where
+	PoseidonSpec<2>: poseidon::Specification<COM>,
+	RescueSpec<2>: rescue::Specification<COM, Field = Field<COM>>,
+	Field<COM>: Clone,
+{
+	type Hash = Rescue2<COM>;
+}
 
-impl merkle_tree::InnerHash<Compiler> for MerkleTreeInnerHashVar {
-	type LeafDigest = ScalarVar;
-	type Parameters = Poseidon2Var;
-	type Output = ScalarVar;
+pub struct MerkleTreeInnerHash;
+
+impl<COM> merkle_tree::InnerHash<COM> for MerkleTreeInnerHash
+where
+	PoseidonSpec<2>: poseidon::Specification<COM>,
+{
+	type LeafDigest = Field<COM>;
+	type Parameters = Poseidon2<COM>;
+	type Output = Field<COM>;
 
 	#[inline]
 	fn join(
 		parameters: &Self::Parameters,
 		lhs: &Self::Output,
 		rhs: &Self::Output,
-		compiler: &mut Compiler,
+		compiler: &mut COM,
 	) -> Self::Output {
-		parameters.hash([lhs, rhs], compiler)
+		parameters.hash_in_domain(INNER_DOMAIN, [lhs, rhs], compiler)
 	}
 
 	#[inline]
@@ -90,43 +195,77 @@ impl merkle_tree::InnerHash<Compiler> for MerkleTreeInnerHashVar {
 		parameters: &Self::Parameters,
 		lhs: &Self::LeafDigest,
 		rhs: &Self::LeafDigest,
-		compiler: &mut Compiler,
+		compiler: &mut COM,
 	) -> Self::Output {
-		parameters.hash([lhs, rhs], compiler)
+		parameters.hash_in_domain(LEAF_DOMAIN, [lhs, rhs], compiler)
 	}
 }
 
-pub struct MerkleTreeConfiguration;
+pub struct MerkleTreeConfiguration<H = DefaultHeight>(PhantomData<H>);
 
-impl merkle_tree::HashConfiguration for MerkleTreeConfiguration {
-	type LeafHash = merkle_tree::IdentityLeafHash<Scalar>;
+impl<H, COM> merkle_tree::HashConfiguration<COM> for MerkleTreeConfiguration<H>
+where
+	PoseidonSpec<2>: poseidon::Specification<COM>,
+	Field<COM>: Clone,
+{
+	type LeafHash = merkle_tree::IdentityLeafHash<Field<COM>, COM>;
 	type InnerHash = MerkleTreeInnerHash;
 }
 
-impl merkle_tree::HashConfiguration<Compiler> for MerkleTreeConfiguration {
-	type LeafHash = merkle_tree::IdentityLeafHash<ScalarVar, Compiler>;
-	type InnerHash = MerkleTreeInnerHashVar;
+impl<H, COM> merkle_tree::Configuration<COM> for MerkleTreeConfiguration<H>
+where
+	H: MerkleTreeHeight,
+	PoseidonSpec<2>: poseidon::Specification<COM>,
+	Field<COM>: Clone,
+{
+	const HEIGHT: usize = H::HEIGHT;
 }
 
-impl merkle_tree::Configuration for MerkleTreeConfiguration {
-	const HEIGHT: usize = MERKLE_TREE_HEIGHT;
+impl<H> merkle_tree::sparse::Configuration for MerkleTreeConfiguration<H> {
+	type Bit = bool;
+
+	const DEPTH: usize = VOID_NUMBER_TREE_DEPTH;
+
+	#[inline]
+	fn key_bits(leaf: &Scalar, compiler: &mut ()) -> Vec<bool> {
+		let _ = compiler;
+		let mut bits = leaf.0.into_repr().to_bits_le();
+		bits.truncate(VOID_NUMBER_TREE_DEPTH);
+		bits
+	}
 }
 
-impl merkle_tree::Configuration<Compiler> for MerkleTreeConfiguration {
-	const HEIGHT: usize = MERKLE_TREE_HEIGHT;
+impl<H> merkle_tree::sparse::Configuration<Compiler> for MerkleTreeConfiguration<H> {
+	type Bit = Boolean<ConstraintField>;
+
+	const DEPTH: usize = VOID_NUMBER_TREE_DEPTH;
+
+	#[inline]
+	fn key_bits(leaf: &ScalarVar, compiler: &mut Compiler) -> Vec<Self::Bit> {
+		let _ = compiler;
+		let mut bits = leaf.to_bits_le().expect("Bit decomposition is not allowed to fail.");
+		bits.truncate(VOID_NUMBER_TREE_DEPTH);
+		bits
+	}
 }
 
-impl Constant<Compiler> for MerkleTreeConfiguration {
-	type Type = MerkleTreeConfiguration;
+/// Void Number Tree
+///
+/// The sparse merkle tree of every void number which was spent, stored in the node map `M`.
+pub type VoidNumberTree<M = BTreeNodeMap<MerkleTreeConfiguration>> =
+	SparseMerkleTree<MerkleTreeConfiguration, M>;
+
+impl<H, COM> Constant<COM> for MerkleTreeConfiguration<H> {
+	type Type = Self;
 
 	#[inline]
-	fn new_constant(this: &Self::Type, compiler: &mut Compiler) -> Self {
+	fn new_constant(this: &Self::Type, compiler: &mut COM) -> Self {
 		let _ = (this, compiler);
-		Self
+		Self(PhantomData)
 	}
 }
 
-impl HashParameterSampling for MerkleTreeConfiguration {
+impl<H> HashParameterSampling for MerkleTreeConfiguration<H> {
 	type LeafHashParameterDistribution = ();
 	type InnerHashParameterDistribution = ();
 
@@ -153,13 +292,47 @@ impl HashParameterSampling for MerkleTreeConfiguration {
 	}
 }
 
-pub struct Parameters {
-	pub utxo_hash: Poseidon2,
-	pub void_number_hash: Poseidon2,
-	pub merkle_tree_parameters: merkle_tree::Parameters<MerkleTreeConfiguration>,
+/// Circuit Parameters
+///
+/// The same parameters are used natively with `COM = ()` and as constants inside of the
+/// [`Compiler`], see [`ParametersVar`]. The UTXO and void number hashes are chosen by `A`, while
+/// the merkle tree always hashes with [`Poseidon2`].
+#[derive(Constant)]
+#[eclair(
+	compiler = "COM",
+	type = "Parameters<H, A>",
+	bound = "
+		A: HashSelection,
+		<A as HashSelection<COM>>::Hash: Constant<COM, Type = <A as HashSelection>::Hash>,
+		merkle_tree::Parameters<MerkleTreeConfiguration<H>, COM>:
+			Constant<COM, Type = merkle_tree::Parameters<MerkleTreeConfiguration<H>>>,
+	"
+)]
+pub struct Parameters<H = DefaultHeight, A = PoseidonHash, COM = ()>
+where
+	A: HashSelection<COM>,
+	PoseidonSpec<2>: poseidon::Specification<COM>,
+	MerkleTreeConfiguration<H>: merkle_tree::HashConfiguration<COM>,
+{
+	pub utxo_hash: <A as HashSelection<COM>>::Hash,
+	pub void_number_hash: <A as HashSelection<COM>>::Hash,
+	pub merkle_tree_parameters: merkle_tree::Parameters<MerkleTreeConfiguration<H>, COM>,
 }
 
-impl Sample for Parameters {
+/// Circuit Parameters Variable
+pub type ParametersVar<H = DefaultHeight, A = PoseidonHash> = Parameters<H, A, Compiler>;
+
+/// Membership Proof Type
+///
+/// This is a [`Path`] in the native compiler and a [`PathVar`] inside of the [`Compiler`].
+pub type MembershipProof<H = DefaultHeight, COM = ()> =
+	<merkle_tree::Parameters<MerkleTreeConfiguration<H>, COM> as Model<COM>>::Witness;
+
+impl<H, A> Sample for Parameters<H, A>
+where
+	A: HashSelection,
+	A::Hash: Sample,
+{
 	fn sample<R>(_: (), rng: &mut R) -> Self
 	where
 		R: RngCore + ?Sized,
@@ -172,55 +345,33 @@ impl Sample for Parameters {
 	}
 }
 
-impl circuit::Parameters for Parameters {
-	type Field = Scalar;
-	type MembershipProof = Path<MerkleTreeConfiguration>;
-
-	#[inline]
-	fn assert_eq(&self, lhs: &Self::Field, rhs: &Self::Field, _: &mut ()) {
-		assert_eq!(lhs, rhs)
-	}
-
-	#[inline]
-	fn utxo(&self, key: &Self::Field, value: &Self::Field, compiler: &mut ()) -> Self::Field {
-		self.utxo_hash.hash([key, value], compiler)
-	}
-
-	#[inline]
-	fn void_number(&self, key: &Self::Field, utxo: &Self::Field, compiler: &mut ()) -> Self::Field {
-		self.void_number_hash.hash([key, utxo], compiler)
-	}
+impl<H, A, COM> circuit::Parameters<COM> for Parameters<H, A, COM>
+where
+	H: MerkleTreeHeight,
+	A: HashSelection<COM>,
+	COM: AssertEq,
+	PoseidonSpec<2>: poseidon::Specification<COM>,
+	MerkleTreeConfiguration<H>: merkle_tree::HashConfiguration<COM>,
+	Field<COM>: eclair::cmp::PartialEq<Field<COM>, COM>,
+	merkle_tree::Parameters<MerkleTreeConfiguration<H>, COM>:
+		AssertValidVerification<COM, Item = Field<COM>, Output = Field<COM>>,
+{
+	type Field = Field<COM>;
+	type MembershipProof = MembershipProof<H, COM>;
 
 	#[inline]
-	fn assert_membership(
-		&self,
-		utxo: &Self::Field,
-		root: &Self::Field,
-		membership_proof: &Self::MembershipProof,
-		_: &mut (),
-	) {
-		assert!(self.merkle_tree_parameters.verify_path(membership_proof, root, utxo))
+	fn assert_eq(&self, lhs: &Self::Field, rhs: &Self::Field, compiler: &mut COM) {
+		compiler.assert_eq(lhs, rhs)
 	}
-}
-
-pub struct ParametersVar {
-	pub utxo_hash: Poseidon2Var,
-	pub void_number_hash: Poseidon2Var,
-	pub merkle_tree_parameters: merkle_tree::Parameters<MerkleTreeConfiguration, Compiler>,
-}
-
-impl circuit::Parameters<Compiler> for ParametersVar {
-	type Field = ScalarVar;
-	type MembershipProof = PathVar<MerkleTreeConfiguration, Compiler>;
 
 	#[inline]
-	fn assert_eq(&self, lhs: &Self::Field, rhs: &Self::Field, compiler: &mut Compiler) {
-		compiler.assert_eq(lhs, rhs)
+	fn add(&self, lhs: &Self::Field, rhs: &Self::Field, compiler: &mut COM) -> Self::Field {
+		<PoseidonSpec<2> as poseidon::Specification<COM>>::add(lhs, rhs, compiler)
 	}
 
 	#[inline]
-	fn utxo(&self, key: &Self::Field, value: &Self::Field, compiler: &mut Compiler) -> Self::Field {
-		self.utxo_hash.hash([key, value], compiler)
+	fn utxo(&self, key: &Self::Field, value: &Self::Field, compiler: &mut COM) -> Self::Field {
+		self.utxo_hash.hash_in_domain(UTXO_DOMAIN, [key, value], compiler)
 	}
 
 	#[inline]
@@ -228,9 +379,9 @@ impl circuit::Parameters<Compiler> for ParametersVar {
 		&self,
 		key: &Self::Field,
 		utxo: &Self::Field,
-		compiler: &mut Compiler,
+		compiler: &mut COM,
 	) -> Self::Field {
-		self.void_number_hash.hash([key, utxo], compiler)
+		self.void_number_hash.hash_in_domain(VOID_NUMBER_DOMAIN, [key, utxo], compiler)
 	}
 
 	#[inline]
@@ -239,61 +390,94 @@ impl circuit::Parameters<Compiler> for ParametersVar {
 		utxo: &Self::Field,
 		root: &Self::Field,
 		membership_proof: &Self::MembershipProof,
-		compiler: &mut Compiler,
+		compiler: &mut COM,
 	) {
-		let is_valid =
-			self.merkle_tree_parameters
-				.verify_path_with(membership_proof, root, utxo, compiler);
-		compiler.assert(&is_valid);
+		self.merkle_tree_parameters.assert_valid(utxo, membership_proof, root, compiler)
 	}
 }
 
-impl Constant<Compiler> for ParametersVar {
-	type Type = Parameters;
-
+impl<H, A> circuit::NativeParameters for Parameters<H, A>
+where
+	H: MerkleTreeHeight,
+	A: HashSelection,
+{
 	#[inline]
-	fn new_constant(this: &Self::Type, compiler: &mut Compiler) -> Self {
-		Self {
-			utxo_hash: this.utxo_hash.as_constant(compiler),
-			void_number_hash: this.void_number_hash.as_constant(compiler),
-			merkle_tree_parameters: this.merkle_tree_parameters.as_constant(compiler),
-		}
+	fn verify_membership(
+		&self,
+		utxo: &Self::Field,
+		root: &Self::Field,
+		membership_proof: &Self::MembershipProof,
+	) -> bool {
+		self.merkle_tree_parameters.verify(utxo, membership_proof, root, &mut ())
 	}
 }
 
-pub struct Accumulator(merkle_tree::full::FullMerkleTree<MerkleTreeConfiguration>);
-
-impl accumulator::Accumulator<Scalar> for Accumulator {
-	type Root = Scalar;
-	type MembershipProof = Path<MerkleTreeConfiguration>;
-
-	#[inline]
-	fn insert(&mut self, item: Scalar) {
-		self.0.push(&item);
-	}
-
-	#[inline]
-	fn membership_proof(&self, item: &Scalar) -> Option<(Self::Root, Self::MembershipProof)> {
-		Some((*self.0.root(), self.0.path(self.0.position(&self.0.parameters.digest(item))?).ok()?))
-	}
+/// Circuit Configuration
+///
+/// Proves the circuits with [`Groth16`] over the [`Compiler`], hashing UTXOs and void numbers
+/// with the hash chosen by `A`.
+pub struct Config<H = DefaultHeight, A = PoseidonHash>(PhantomData<(H, A)>);
+
+impl<H, A> Configuration for Config<H, A>
+where
+	H: MerkleTreeHeight,
+	A: HashSelection + HashSelection<Compiler>,
+	<A as HashSelection<Compiler>>::Hash: Constant<Compiler, Type = <A as HashSelection>::Hash>,
+{
+	type Compiler = Compiler;
+	type Field = Scalar;
+	type MembershipProof = Path<MerkleTreeConfiguration<H>>;
+	type Parameters = Parameters<H, A>;
+	type AccumulatorModel = merkle_tree::Parameters<MerkleTreeConfiguration<H>>;
+	type Accumulator = merkle_tree::full::FullMerkleTree<
+		MerkleTreeConfiguration<H>,
+		merkle_tree::inner_tree::BTreeMap<MerkleTreeConfiguration<H>>,
+		merkle_tree::full::BTreeIndex<MerkleTreeConfiguration<H>>,
+	>;
+	type FieldVar = ScalarVar;
+	type MembershipProofVar = PathVar<MerkleTreeConfiguration<H>, Compiler>;
+	type ParametersVar = ParametersVar<H, A>;
 }
 
-pub struct Config;
-
-impl Configuration for Config {
-	type Compiler = Compiler;
+impl<H, A> ProofSystemConfiguration for Config<H, A>
+where
+	H: MerkleTreeHeight,
+	A: HashSelection + HashSelection<Compiler>,
+	<A as HashSelection<Compiler>>::Hash: Constant<Compiler, Type = <A as HashSelection>::Hash>,
+{
 	type ProvingKey = ProvingKey;
 	type VerifyingKey = VerifyingKey;
 	type Proof = Proof;
 	type Error = <Self::ProofSystem as proofsystem::ProofSystem>::Error;
 	type ProofSystem = ProofSystem;
+}
+
+/// Cost Estimation Configuration
+///
+/// Uses the same native types as [`Config`] but compiles the circuits with the [`CostCompiler`],
+/// see [`mint_cost`](circuit::mint_cost) and [`claim_cost`](circuit::claim_cost).
+pub struct CostConfig<H = DefaultHeight, A = PoseidonHash>(PhantomData<(H, A)>);
+
+impl<H, A> Configuration for CostConfig<H, A>
+where
+	H: MerkleTreeHeight,
+	A: HashSelection + HashSelection<CostCompiler>,
+	<A as HashSelection<CostCompiler>>::Hash:
+		Constant<CostCompiler, Type = <A as HashSelection>::Hash>,
+{
+	type Compiler = CostCompiler;
 	type Field = Scalar;
-	type MembershipProof = Path<MerkleTreeConfiguration>;
-	type Parameters = Parameters;
-	type Accumulator = Accumulator;
-	type FieldVar = ScalarVar;
-	type MembershipProofVar = PathVar<MerkleTreeConfiguration, Compiler>;
-	type ParametersVar = ParametersVar;
+	type MembershipProof = Path<MerkleTreeConfiguration<H>>;
+	type Parameters = Parameters<H, A>;
+	type AccumulatorModel = merkle_tree::Parameters<MerkleTreeConfiguration<H>>;
+	type Accumulator = merkle_tree::full::FullMerkleTree<
+		MerkleTreeConfiguration<H>,
+		merkle_tree::inner_tree::BTreeMap<MerkleTreeConfiguration<H>>,
+		merkle_tree::full::BTreeIndex<MerkleTreeConfiguration<H>>,
+	>;
+	type FieldVar = cost::FieldVar<Scalar>;
+	type MembershipProofVar = PathVar<MerkleTreeConfiguration<H>, CostCompiler>;
+	type ParametersVar = Parameters<H, A, CostCompiler>;
 }
 
 pub type ProofSystem = Groth16<Pairing>;
@@ -311,5 +495,69 @@ pub mod types {
 	pub type MerkleRoot = [u8; 32];
 	pub type HashDigest = [u8; 32];
 
-	pub const MERKLE_TREE_DEPTH: usize = super::MERKLE_TREE_HEIGHT;
+	pub const VOID_NUMBER_TREE_DEPTH: usize = super::VOID_NUMBER_TREE_DEPTH;
+}
+
+/// Testing Framework
+pub mod test {
+	use super::*;
+	use crate::{
+		crypto::{
+			eclair::{
+				alloc::{
+					mode::{Public, Secret},
+					Allocate,
+				},
+				bool::Assert,
+			},
+			merkle_tree::{
+				full::FullMerkleTree, path::constraint::MultiPathVar,
+				test::multi_path_sibling_count,
+			},
+		},
+		parameters::parameters,
+	};
+
+	/// Asserts that the multi-path for the leaves at `indices` of a tree built from `leaves` stores
+	/// exactly the siblings which are shared between their paths, that the multi-path circuit
+	/// accepts it, and that the circuit rejects it for a different set of leaves.
+	///
+	/// # Panics
+	///
+	/// This function panics if any of the `indices` is out of bounds for `leaves`.
+	#[inline]
+	pub fn assert_valid_multi_path_circuit(leaves: &[Scalar], indices: &[usize]) {
+		let parameters = parameters::<DefaultHeight>().merkle_tree_parameters;
+		let tree = FullMerkleTree::<MerkleTreeConfiguration>::from_slice(parameters, leaves)
+			.expect("Only valid leaf lists are accepted.");
+		let multi_path = tree.multi_path(indices).expect("Only valid queries are accepted.");
+		let expected = multi_path_sibling_count::<MerkleTreeConfiguration>(indices);
+		let members = multi_path.leaf_indices.iter().map(|node| leaves[node.0]).collect::<Vec<_>>();
+		let mut forged = members.clone();
+		forged[0] = Fp(forged[0].0 + ConstraintField::from(1u64));
+		for (members, satisfied) in [(members, true), (forged, false)] {
+			let mut compiler = Compiler::for_prove();
+			let parameters_var: merkle_tree::Parameters<MerkleTreeConfiguration, Compiler> =
+				tree.parameters().as_constant(&mut compiler);
+			let multi_path_var = MultiPathVar::new_known(&multi_path, &mut compiler);
+			assert_eq!(
+				multi_path_var.leaf_siblings.len() + multi_path_var.inner_siblings.len(),
+				expected,
+				"Multi-path variables must allocate exactly the shared siblings."
+			);
+			let root = tree.root().as_known::<Public, ScalarVar>(&mut compiler);
+			let members = members
+				.iter()
+				.map(|leaf| leaf.as_known::<Secret, ScalarVar>(&mut compiler))
+				.collect::<Vec<_>>();
+			let is_valid = parameters_var.verify_multi_path_with(
+				&multi_path_var,
+				&root,
+				&members,
+				&mut compiler,
+			);
+			compiler.assert(&is_valid);
+			assert_eq!(compiler.check().is_ok(), satisfied);
+		}
+	}
 }
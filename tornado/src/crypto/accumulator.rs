@@ -1,10 +1,283 @@
-//! Accumulator Trait
+//! Dynamic Cryptographic Accumulators
 
-pub trait Accumulator<T> {
-	type Root;
-	type MembershipProof;
+use crate::crypto::eclair::bool::AssertEq;
 
-	fn insert(&mut self, item: T);
+/// Accumulator Membership Model
+///
+/// A model verifies that an item was accumulated into some output using a witness, either
+/// natively with `COM = ()` or inside of a compiler.
+pub trait Model<COM = ()> {
+	/// Item Type
+	type Item: ?Sized;
 
-	fn membership_proof(&self, item: &T) -> Option<(Self::Root, Self::MembershipProof)>;
+	/// Secret Witness Type
+	type Witness;
+
+	/// Output Type
+	type Output;
+
+	/// Verification Type
+	///
+	/// Typically this is either [`bool`], a [`Result`] type, or a circuit boolean variable.
+	type Verification;
+
+	/// Verifies that `item` is stored in a known accumulator with accumulated `output` and
+	/// membership `witness`.
+	fn verify(
+		&self,
+		item: &Self::Item,
+		witness: &Self::Witness,
+		output: &Self::Output,
+		compiler: &mut COM,
+	) -> Self::Verification;
+}
+
+/// Accumulator Membership Model Validity Assertion
+///
+/// For situations where we just want to assert validity of the membership proof, we can use this
+/// trait as an optimization path for [`Model::verify`].
+pub trait AssertValidVerification<COM = ()>: Model<COM>
+where
+	COM: AssertEq,
+{
+	/// Asserts that the verification of the storage of `item` in the known accumulator is valid.
+	fn assert_valid(
+		&self,
+		item: &Self::Item,
+		witness: &Self::Witness,
+		output: &Self::Output,
+		compiler: &mut COM,
+	);
+}
+
+/// Accumulator
+pub trait Accumulator {
+	/// Item Type
+	type Item: ?Sized;
+
+	/// Model Type
+	type Model: Model<Item = Self::Item> + ?Sized;
+
+	/// Returns the model associated with `self`.
+	fn model(&self) -> &Self::Model;
+
+	/// Inserts `item` into `self` with the guarantee that `self` can later return a valid
+	/// membership proof for `item` with a call to [`prove`](Self::prove). This method returns
+	/// `false` if the maximum capacity of the accumulator would be exceeded by inserting `item`.
+	fn insert(&mut self, item: &Self::Item) -> bool;
+
+	/// Returns a membership proof for `item` if it is contained in `self`.
+	fn prove(&self, item: &Self::Item) -> Option<MembershipProof<Self::Model>>;
+
+	/// Returns `true` if `item` is stored in `self`.
+	///
+	/// # Implementation Note
+	///
+	/// This method must at least return `true` for `item` whenever a valid proof of membership
+	/// exists. It may return `true` in other cases when `self` knows that it has `item` stored but
+	/// cannot return a proof for it, like in the case of
+	/// [`insert_nonprovable`](OptimizedAccumulator::insert_nonprovable).
+	#[inline]
+	fn contains(&self, item: &Self::Item) -> bool {
+		self.prove(item).is_some()
+	}
+}
+
+impl<A> Accumulator for &mut A
+where
+	A: Accumulator + ?Sized,
+{
+	type Item = A::Item;
+	type Model = A::Model;
+
+	#[inline]
+	fn model(&self) -> &Self::Model {
+		(**self).model()
+	}
+
+	#[inline]
+	fn insert(&mut self, item: &Self::Item) -> bool {
+		(**self).insert(item)
+	}
+
+	#[inline]
+	fn prove(&self, item: &Self::Item) -> Option<MembershipProof<Self::Model>> {
+		(**self).prove(item)
+	}
+
+	#[inline]
+	fn contains(&self, item: &Self::Item) -> bool {
+		(**self).contains(item)
+	}
+}
+
+/// Constant Capacity Accumulator
+pub trait ConstantCapacityAccumulator: Accumulator {
+	/// Returns the total number of items that can be stored in `Self`.
+	fn capacity() -> usize;
+}
+
+/// Exact Size Accumulator
+pub trait ExactSizeAccumulator: Accumulator {
+	/// Returns the number of items stored in `self`.
+	fn len(&self) -> usize;
+
+	/// Returns `true` if the length of `self` is zero.
+	#[inline]
+	fn is_empty(&self) -> bool {
+		self.len() == 0
+	}
+}
+
+/// Optimized Accumulator
+///
+/// Accumulators which can insert items that will never need a membership proof, or drop the
+/// proofs of items that are no longer needed, can implement these hooks to save memory. The
+/// default implementations fall back to the behavior of [`Accumulator`].
+pub trait OptimizedAccumulator: Accumulator {
+	/// Inserts `item` into `self` without the guarantee that `self` will be able to return a
+	/// proof of membership of `item`. This method returns `false` if the maximum capacity of the
+	/// accumulator would be exceeded by inserting `item`.
+	///
+	/// # Implementation Note
+	///
+	/// By default, this method uses [`insert`](Accumulator::insert) to store `item` in `self`.
+	/// Since the insertion may not return a proof, it is not valid to call this method and then
+	/// expect a membership proof from [`prove`](Accumulator::prove).
+	#[inline]
+	fn insert_nonprovable(&mut self, item: &Self::Item) -> bool {
+		self.insert(item)
+	}
+
+	/// Removes the witnesses to the membership of `item` in `self`. The resulting state of the
+	/// accumulator after removing a proof should be the same as if the item had been inserted
+	/// into the accumulator with [`insert_nonprovable`](Self::insert_nonprovable). This method
+	/// returns `true` if the item was successfully demoted to non-provable.
+	///
+	/// # Implementation Note
+	///
+	/// By default, this method does nothing and returns `false`. Implementations of this method
+	/// may fail arbitrarily, and should only successfully remove a proof if the implementation is
+	/// efficient enough. Space and time tradeoffs should be studied to determine the usefulness
+	/// of this method.
+	#[inline]
+	fn remove_proof(&mut self, item: &Self::Item) -> bool {
+		let _ = item;
+		false
+	}
+}
+
+impl<A> OptimizedAccumulator for &mut A
+where
+	A: OptimizedAccumulator + ?Sized,
+{
+	#[inline]
+	fn insert_nonprovable(&mut self, item: &Self::Item) -> bool {
+		(**self).insert_nonprovable(item)
+	}
+
+	#[inline]
+	fn remove_proof(&mut self, item: &Self::Item) -> bool {
+		(**self).remove_proof(item)
+	}
+}
+
+/// Accumulator Membership Proof
+pub struct MembershipProof<M>
+where
+	M: Model + ?Sized,
+{
+	/// Secret Membership Witness
+	witness: M::Witness,
+
+	/// Accumulator Output
+	output: M::Output,
+}
+
+impl<M> MembershipProof<M>
+where
+	M: Model + ?Sized,
+{
+	/// Builds a new [`MembershipProof`] from `witness` and `output`.
+	#[inline]
+	pub fn new(witness: M::Witness, output: M::Output) -> Self {
+		Self { witness, output }
+	}
+
+	/// Returns the accumulated output part of `self`, dropping the [`M::Witness`](Model::Witness).
+	#[inline]
+	pub fn into_output(self) -> M::Output {
+		self.output
+	}
+
+	/// Returns the accumulated output part of `self`.
+	#[inline]
+	pub fn output(&self) -> &M::Output {
+		&self.output
+	}
+
+	/// Verifies that `item` is stored in a known accumulator using `model`.
+	#[inline]
+	pub fn verify(&self, model: &M, item: &M::Item) -> M::Verification {
+		model.verify(item, &self.witness, &self.output, &mut ())
+	}
+
+	/// Splits `self` into its [`M::Witness`](Model::Witness) and [`M::Output`](Model::Output).
+	#[inline]
+	pub fn into_parts(self) -> (M::Witness, M::Output) {
+		(self.witness, self.output)
+	}
+}
+
+/// Testing Framework
+pub mod test {
+	use super::*;
+	use alloc::vec::Vec;
+	use core::fmt::Debug;
+
+	/// Asserts that `accumulator` can prove the membership of `item` after it is inserted.
+	#[inline]
+	pub fn assert_provable_membership<A>(accumulator: &mut A, item: &A::Item)
+	where
+		A: Accumulator,
+		A::Model: Model<Verification = bool>,
+	{
+		assert!(accumulator.insert(item), "Item could not be inserted into the accumulator.");
+		assert!(
+			accumulator.contains(item),
+			"Item was supposed to be contained in the accumulator."
+		);
+		let proof = accumulator.prove(item).expect("Item was supposed to have a membership proof.");
+		assert!(proof.verify(accumulator.model(), item), "Invalid proof returned by accumulator.");
+	}
+
+	/// Asserts that the `accumulator` yields unique accumulated values after every insertion of
+	/// the items in `iter`.
+	#[inline]
+	pub fn assert_unique_outputs<'i, A, I>(accumulator: &mut A, iter: I)
+	where
+		A: Accumulator,
+		A::Item: 'i,
+		<A::Model as Model>::Output: Debug + PartialEq,
+		I: IntoIterator<Item = &'i A::Item>,
+	{
+		let outputs = iter
+			.into_iter()
+			.map(|item| {
+				assert!(
+					accumulator.insert(item),
+					"Item could not be inserted into the accumulator."
+				);
+				accumulator
+					.prove(item)
+					.expect("Item was supposed to have a membership proof.")
+					.into_output()
+			})
+			.collect::<Vec<_>>();
+		for (i, x) in outputs.iter().enumerate() {
+			for (j, y) in outputs.iter().enumerate().skip(i + 1) {
+				assert_ne!(x, y, "Found matching outputs at indices {:?} and {:?}.", i, j);
+			}
+		}
+	}
 }
@@ -8,16 +8,23 @@ use crate::crypto::{
 			Constant, Variable,
 		},
 		bool::{Assert, AssertEq, ConditionalSwap},
-		Has,
+		num::AssertWithinBitRange,
+		Has, Namespace, NonNative,
 	},
 	rand::{RngCore, Sample},
 };
-use ark_ff::{PrimeField, UniformRand};
-use ark_r1cs_std::{alloc::AllocVar, eq::EqGadget, select::CondSelectGadget};
+use alloc::{
+	string::{String, ToString},
+	vec::Vec,
+};
+use ark_ff::{BigInteger, FpParameters, PrimeField, UniformRand};
+use ark_r1cs_std::{alloc::AllocVar, eq::EqGadget, select::CondSelectGadget, R1CSVar};
 use ark_relations::{
 	ns, r1cs as ark_r1cs,
 	r1cs::{ConstraintSynthesizer, ConstraintSystemRef},
 };
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, Read, SerializationError, Write};
+use core::{fmt, ops::Range};
 
 pub use ark_r1cs::SynthesisError;
 pub use ark_r1cs_std::{bits::boolean::Boolean, fields::fp::FpVar};
@@ -62,6 +69,149 @@ where
 	}
 }
 
+impl<F> CanonicalSerialize for Fp<F>
+where
+	F: PrimeField,
+{
+	#[inline]
+	fn serialize<W>(&self, writer: W) -> Result<(), SerializationError>
+	where
+		W: Write,
+	{
+		self.0.serialize(writer)
+	}
+
+	#[inline]
+	fn serialized_size(&self) -> usize {
+		self.0.serialized_size()
+	}
+}
+
+impl<F> CanonicalDeserialize for Fp<F>
+where
+	F: PrimeField,
+{
+	#[inline]
+	fn deserialize<R>(reader: R) -> Result<Self, SerializationError>
+	where
+		R: Read,
+	{
+		F::deserialize(reader).map(Self)
+	}
+}
+
+impl<F, const BITS: usize> AssertWithinBitRange<Fp<F>, BITS> for ()
+where
+	F: PrimeField,
+{
+	#[inline]
+	fn assert_within_range(&mut self, value: &Fp<F>) {
+		assert!(
+			value.0.into_repr().num_bits() as usize <= BITS,
+			"Values are not allowed to exceed their bit range."
+		);
+	}
+}
+
+/// Namespace Statistics
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct NamespaceStatistics {
+	/// Namespace Path
+	///
+	/// Nested namespaces are joined with `/`, starting from the outermost namespace.
+	pub path: String,
+
+	/// Number of Constraints
+	pub constraints: usize,
+
+	/// Number of Public Input Variables
+	pub public_inputs: usize,
+
+	/// Number of Secret Witness Variables
+	pub witnesses: usize,
+}
+
+/// Constraint System Statistics
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct Statistics {
+	/// Number of Constraints
+	pub constraints: usize,
+
+	/// Number of Public Input Variables
+	///
+	/// This does not count the constant `one` variable which every constraint system allocates.
+	pub public_inputs: usize,
+
+	/// Number of Secret Witness Variables
+	pub witnesses: usize,
+
+	/// Per-Namespace Breakdown
+	///
+	/// Counts are inclusive, so the counts of a namespace include the counts of all of the
+	/// namespaces nested inside of it. Namespaces are listed in the order they were first entered.
+	pub namespaces: Vec<NamespaceStatistics>,
+}
+
+impl fmt::Display for Statistics {
+	#[inline]
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		writeln!(f, "constraints:   {}", self.constraints)?;
+		writeln!(f, "public inputs: {}", self.public_inputs)?;
+		writeln!(f, "witnesses:     {}", self.witnesses)?;
+		for namespace in &self.namespaces {
+			writeln!(
+				f,
+				"  {}: {} constraints, {} public inputs, {} witnesses",
+				namespace.path, namespace.constraints, namespace.public_inputs, namespace.witnesses
+			)?;
+		}
+		Ok(())
+	}
+}
+
+/// Unsatisfied Constraint
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Unsatisfied {
+	/// Constraint Trace
+	///
+	/// This is the index of the first unsatisfied constraint unless constraint tracing was enabled
+	/// with [`trace_constraints`], in which case it is the trace of `ns!` labels which were active
+	/// when the constraint was generated.
+	pub trace: String,
+
+	/// Namespace Path
+	///
+	/// Path of the innermost [`Namespace`] which generated the unsatisfied constraint. This is only
+	/// known if `trace` is a constraint index.
+	pub namespace: Option<String>,
+}
+
+impl fmt::Display for Unsatisfied {
+	#[inline]
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match &self.namespace {
+			Some(namespace) => write!(f, "unsatisfied constraint {} in {}", self.trace, namespace),
+			_ => write!(f, "unsatisfied constraint:\n{}", self.trace),
+		}
+	}
+}
+
+/// Runs `f` with constraint tracing enabled so that every constraint generated inside of `f` records
+/// the `ns!` labels and [`Namespace`] spans it was generated under. The labels are reported by
+/// [`R1CS::check`].
+#[cfg(feature = "debug")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "debug")))]
+#[inline]
+pub fn trace_constraints<T, F>(f: F) -> T
+where
+	F: FnOnce() -> T,
+{
+	use tracing_subscriber::layer::SubscriberExt;
+	let subscriber =
+		tracing_subscriber::Registry::default().with(ark_r1cs::ConstraintLayer::default());
+	tracing::subscriber::with_default(subscriber, f)
+}
+
 /// Arkworks Rank-1 Constraint System
 pub struct R1CS<F>
 where
@@ -69,6 +219,18 @@ where
 {
 	/// Constraint System
 	pub(crate) cs: ark_r1cs::ConstraintSystemRef<F>,
+
+	/// Namespace Stack
+	namespace_stack: Vec<&'static str>,
+
+	/// Namespace Statistics
+	namespaces: Vec<NamespaceStatistics>,
+
+	/// Namespace Constraint Ranges
+	///
+	/// Each entry points into `namespaces` with the range of constraints generated by one visit of
+	/// that namespace. Inner namespaces are recorded before the namespaces that enclose them.
+	constraint_ranges: Vec<(usize, Range<usize>)>,
 }
 
 impl<F> R1CS<F>
@@ -82,7 +244,7 @@ where
 		let cs = ark_r1cs::ConstraintSystem::new_ref();
 		cs.set_optimization_goal(ark_r1cs::OptimizationGoal::Constraints);
 		cs.set_mode(ark_r1cs::SynthesisMode::Setup);
-		Self { cs }
+		Self::new(cs)
 	}
 
 	/// Constructs a new constraint system which is ready for known variables.
@@ -91,7 +253,116 @@ where
 		// FIXME: This might not be the right setup for all proof systems.
 		let cs = ark_r1cs::ConstraintSystem::new_ref();
 		cs.set_optimization_goal(ark_r1cs::OptimizationGoal::Constraints);
-		Self { cs }
+		Self::new(cs)
+	}
+
+	/// Builds a new [`R1CS`] compiler around `cs`.
+	#[inline]
+	fn new(cs: ConstraintSystemRef<F>) -> Self {
+		Self {
+			cs,
+			namespace_stack: Vec::new(),
+			namespaces: Vec::new(),
+			constraint_ranges: Vec::new(),
+		}
+	}
+
+	/// Returns the number of constraints in `self`.
+	#[inline]
+	pub fn num_constraints(&self) -> usize {
+		self.cs.num_constraints()
+	}
+
+	/// Returns the number of public input variables in `self`, not counting the constant `one`
+	/// variable.
+	#[inline]
+	pub fn num_public_inputs(&self) -> usize {
+		self.cs.num_instance_variables() - 1
+	}
+
+	/// Returns the number of secret witness variables in `self`.
+	#[inline]
+	pub fn num_witnesses(&self) -> usize {
+		self.cs.num_witness_variables()
+	}
+
+	/// Returns the constraint and variable counts of `self` with their per-namespace breakdown.
+	/// See [`Namespace`] for attributing constraints to a namespace.
+	#[inline]
+	pub fn statistics(&self) -> Statistics {
+		Statistics {
+			constraints: self.num_constraints(),
+			public_inputs: self.num_public_inputs(),
+			witnesses: self.num_witnesses(),
+			namespaces: self.namespaces.clone(),
+		}
+	}
+
+	/// Returns the path of the innermost namespace which generated the constraint at `index`.
+	#[inline]
+	fn namespace_of(&self, index: usize) -> Option<String> {
+		self.constraint_ranges
+			.iter()
+			.find(|(_, range)| range.contains(&index))
+			.map(|(namespace, _)| self.namespaces[*namespace].path.clone())
+	}
+
+	/// Checks that every constraint in `self` is satisfied by the known variable assignment,
+	/// returning the first unsatisfied constraint otherwise.
+	///
+	/// # Panics
+	///
+	/// This method panics if `self` was built with [`for_compile`](Self::for_compile), since
+	/// there are no known variables to check against.
+	#[inline]
+	pub fn check(&self) -> Result<(), Unsatisfied> {
+		if self.cs.is_satisfied().expect("Checking satisfiability is not allowed to fail.") {
+			return Ok(());
+		}
+		let trace = self
+			.cs
+			.which_is_unsatisfied()
+			.expect("Checking satisfiability is not allowed to fail.")
+			.unwrap_or_else(|| "unknown".to_string());
+		Err(Unsatisfied {
+			namespace: trace.parse().ok().and_then(|index| self.namespace_of(index)),
+			trace,
+		})
+	}
+}
+
+impl<F> Namespace for R1CS<F>
+where
+	F: PrimeField,
+{
+	#[inline]
+	fn namespace<T, N>(&mut self, label: &'static str, f: N) -> T
+	where
+		N: FnOnce(&mut Self) -> T,
+	{
+		self.namespace_stack.push(label);
+		let path = self.namespace_stack.join("/");
+		let index = match self.namespaces.iter().position(|n| n.path == path) {
+			Some(index) => index,
+			_ => {
+				self.namespaces.push(NamespaceStatistics { path, ..Default::default() });
+				self.namespaces.len() - 1
+			},
+		};
+		let constraints = self.num_constraints();
+		let public_inputs = self.num_public_inputs();
+		let witnesses = self.num_witnesses();
+		let span = ark_r1cs::info_span!(target: "r1cs", "namespace", label);
+		let result = span.in_scope(|| f(self));
+		let (next_constraints, next_public_inputs, next_witnesses) =
+			(self.num_constraints(), self.num_public_inputs(), self.num_witnesses());
+		let statistics = &mut self.namespaces[index];
+		statistics.constraints += next_constraints - constraints;
+		statistics.public_inputs += next_public_inputs - public_inputs;
+		statistics.witnesses += next_witnesses - witnesses;
+		self.constraint_ranges.push((index, constraints..next_constraints));
+		self.namespace_stack.pop();
+		result
 	}
 }
 
@@ -135,6 +406,8 @@ where
 
 impl<F> AssertEq for R1CS<F> where F: PrimeField {}
 
+impl<F> NonNative for R1CS<F> where F: PrimeField {}
+
 impl<F> Constant<R1CS<F>> for Boolean<F>
 where
 	F: PrimeField,
@@ -279,3 +552,36 @@ where
 		(conditionally_select(bit, rhs, lhs), conditionally_select(bit, lhs, rhs))
 	}
 }
+
+impl<F, const BITS: usize> AssertWithinBitRange<FpVar<F>, BITS> for R1CS<F>
+where
+	F: PrimeField,
+{
+	/// Asserts that `value` fits in `BITS` bits by decomposing it into `BITS` boolean witnesses,
+	/// costing `BITS + 1` constraints.
+	///
+	/// # Panics
+	///
+	/// This method panics if `BITS` is not smaller than the number of bits in the modulus, since
+	/// the recomposition would then wrap around.
+	#[inline]
+	fn assert_within_range(&mut self, value: &FpVar<F>) {
+		assert!(
+			BITS < F::Params::MODULUS_BITS as usize,
+			"Bit ranges are not allowed to reach the size of the modulus."
+		);
+		let value_bits = value.value().ok().map(|value| value.into_repr().to_bits_le());
+		let bits = (0..BITS)
+			.map(|i| {
+				Boolean::new_witness(ns!(self.cs, "range check bit"), || {
+					value_bits.as_ref().map(|bits| bits[i]).ok_or(SynthesisError::AssignmentMissing)
+				})
+				.expect("Variable allocation is not allowed to fail.")
+			})
+			.collect::<Vec<_>>();
+		Boolean::le_bits_to_fp_var(&bits)
+			.expect("Recomposing bits is not allowed to fail.")
+			.enforce_equal(value)
+			.expect("Enforcing equality is not allowed to fail.");
+	}
+}
@@ -0,0 +1,541 @@
+//! Circom Binary Formats
+//!
+//! Exports the constraint systems built by the [`R1CS`] compiler to the `.r1cs` and `.wtns`
+//! binary formats from iden3, so that circuits can be inspected with circom tooling like
+//! `snarkjs`. Both formats start with four magic bytes, a version, and a list of sections which
+//! each start with their type and their size in bytes. Integers are little-endian and field
+//! elements are written in their canonical little-endian representation, using the number of
+//! bytes given in the header.
+//!
+//! Wires are numbered in the same order as the arkworks variables: wire `0` is the constant one,
+//! followed by the public inputs and then by the secret witnesses. Arkworks does not distinguish
+//! secret inputs from intermediate witnesses, so every secret witness is exported as an
+//! intermediate wire.
+
+use crate::crypto::arkworks::R1CS;
+use alloc::vec::Vec;
+use ark_ff::{BigInteger, FpParameters, PrimeField};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, Read, SerializationError, Write};
+
+/// Constraint System Magic Bytes
+const R1CS_MAGIC: [u8; 4] = *b"r1cs";
+
+/// Constraint System Format Version
+const R1CS_VERSION: u32 = 1;
+
+/// Constraint System Header Section Type
+const R1CS_HEADER_SECTION: u32 = 1;
+
+/// Constraint System Constraints Section Type
+const R1CS_CONSTRAINTS_SECTION: u32 = 2;
+
+/// Constraint System Wire to Label Map Section Type
+const R1CS_WIRE_TO_LABEL_SECTION: u32 = 3;
+
+/// Witness Magic Bytes
+const WTNS_MAGIC: [u8; 4] = *b"wtns";
+
+/// Witness Format Version
+const WTNS_VERSION: u32 = 2;
+
+/// Witness Header Section Type
+const WTNS_HEADER_SECTION: u32 = 1;
+
+/// Witness Values Section Type
+const WTNS_VALUES_SECTION: u32 = 2;
+
+/// Linear Combination
+///
+/// Every term is a wire index together with its coefficient.
+pub type LinearCombination<F> = Vec<(u32, F)>;
+
+/// Rank-1 Constraint
+///
+/// The constraint is satisfied by a witness `w` whenever `<a, w> * <b, w> = <c, w>`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Constraint<F> {
+	/// Left Linear Combination
+	pub a: LinearCombination<F>,
+
+	/// Right Linear Combination
+	pub b: LinearCombination<F>,
+
+	/// Output Linear Combination
+	pub c: LinearCombination<F>,
+}
+
+impl<F> Constraint<F>
+where
+	F: PrimeField,
+{
+	/// Returns `true` if `self` is satisfied by the wire assignment `witness`.
+	#[inline]
+	pub fn is_satisfied(&self, witness: &[F]) -> bool {
+		match (evaluate(&self.a, witness), evaluate(&self.b, witness), evaluate(&self.c, witness)) {
+			(Some(a), Some(b), Some(c)) => a * b == c,
+			_ => false,
+		}
+	}
+}
+
+/// Evaluates `lc` over the wire assignment `witness`, returning `None` if `lc` refers to a wire
+/// which is not assigned.
+#[inline]
+fn evaluate<F>(lc: &[(u32, F)], witness: &[F]) -> Option<F>
+where
+	F: PrimeField,
+{
+	lc.iter().try_fold(F::zero(), |sum, (wire, coefficient)| {
+		Some(sum + *witness.get(*wire as usize)? * coefficient)
+	})
+}
+
+/// Circom Constraint System
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ConstraintSystem<F> {
+	/// Number of Public Input Wires
+	///
+	/// This does not count the constant one wire.
+	pub public_inputs: u32,
+
+	/// Number of Wires
+	pub wires: u32,
+
+	/// Constraints
+	pub constraints: Vec<Constraint<F>>,
+}
+
+impl<F> ConstraintSystem<F>
+where
+	F: PrimeField,
+{
+	/// Returns `true` if every constraint in `self` is satisfied by `witness`.
+	#[inline]
+	pub fn is_satisfied(&self, witness: &Witness<F>) -> bool {
+		witness.values.len() == self.wires as usize
+			&& witness.values.first() == Some(&F::one())
+			&& self
+				.constraints
+				.iter()
+				.all(|constraint| constraint.is_satisfied(&witness.values))
+	}
+
+	/// Writes `self` to `writer` in the `.r1cs` format.
+	#[inline]
+	pub fn write<W>(&self, mut writer: W) -> Result<(), SerializationError>
+	where
+		W: Write,
+	{
+		let mut header = Vec::new();
+		write_field_header::<F, _>(&mut header)?;
+		self.wires.serialize(&mut header)?;
+		0u32.serialize(&mut header)?;
+		self.public_inputs.serialize(&mut header)?;
+		0u32.serialize(&mut header)?;
+		u64::from(self.wires).serialize(&mut header)?;
+		length(self.constraints.len())?.serialize(&mut header)?;
+		let mut constraints = Vec::new();
+		for constraint in &self.constraints {
+			for lc in [&constraint.a, &constraint.b, &constraint.c] {
+				length(lc.len())?.serialize(&mut constraints)?;
+				for (wire, coefficient) in lc {
+					wire.serialize(&mut constraints)?;
+					coefficient.into_repr().serialize(&mut constraints)?;
+				}
+			}
+		}
+		let mut labels = Vec::new();
+		for wire in 0..u64::from(self.wires) {
+			wire.serialize(&mut labels)?;
+		}
+		writer.write_all(&R1CS_MAGIC)?;
+		R1CS_VERSION.serialize(&mut writer)?;
+		3u32.serialize(&mut writer)?;
+		write_section(&mut writer, R1CS_HEADER_SECTION, &header)?;
+		write_section(&mut writer, R1CS_CONSTRAINTS_SECTION, &constraints)?;
+		write_section(&mut writer, R1CS_WIRE_TO_LABEL_SECTION, &labels)
+	}
+
+	/// Reads a constraint system in the `.r1cs` format from `reader`.
+	///
+	/// Public outputs and secret inputs are read as public inputs and intermediate wires,
+	/// respectively, since they are assigned in the same order.
+	#[inline]
+	pub fn read<R>(reader: R) -> Result<Self, SerializationError>
+	where
+		R: Read,
+	{
+		let sections = read_sections(reader, R1CS_MAGIC, R1CS_VERSION)?;
+		let mut header = section(&sections, R1CS_HEADER_SECTION)?;
+		read_field_header::<F, _>(&mut header)?;
+		let wires = u32::deserialize(&mut header)?;
+		let public_outputs = u32::deserialize(&mut header)?;
+		let public_inputs = u32::deserialize(&mut header)?;
+		let _secret_inputs = u32::deserialize(&mut header)?;
+		let _labels = u64::deserialize(&mut header)?;
+		let count = u32::deserialize(&mut header)?;
+		let mut reader = section(&sections, R1CS_CONSTRAINTS_SECTION)?;
+		let mut constraints = Vec::new();
+		for _ in 0..count {
+			let mut lcs = [Vec::new(), Vec::new(), Vec::new()];
+			for lc in &mut lcs {
+				for _ in 0..u32::deserialize(&mut reader)? {
+					lc.push((u32::deserialize(&mut reader)?, read_field(&mut reader)?));
+				}
+			}
+			let [a, b, c] = lcs;
+			constraints.push(Constraint { a, b, c });
+		}
+		Ok(Self {
+			public_inputs: public_outputs
+				.checked_add(public_inputs)
+				.ok_or(SerializationError::InvalidData)?,
+			wires,
+			constraints,
+		})
+	}
+}
+
+/// Circom Witness
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Witness<F> {
+	/// Wire Values
+	///
+	/// The first value is the constant one.
+	pub values: Vec<F>,
+}
+
+impl<F> Witness<F>
+where
+	F: PrimeField,
+{
+	/// Writes `self` to `writer` in the `.wtns` format.
+	#[inline]
+	pub fn write<W>(&self, mut writer: W) -> Result<(), SerializationError>
+	where
+		W: Write,
+	{
+		let mut header = Vec::new();
+		write_field_header::<F, _>(&mut header)?;
+		length(self.values.len())?.serialize(&mut header)?;
+		let mut values = Vec::new();
+		for value in &self.values {
+			value.into_repr().serialize(&mut values)?;
+		}
+		writer.write_all(&WTNS_MAGIC)?;
+		WTNS_VERSION.serialize(&mut writer)?;
+		2u32.serialize(&mut writer)?;
+		write_section(&mut writer, WTNS_HEADER_SECTION, &header)?;
+		write_section(&mut writer, WTNS_VALUES_SECTION, &values)
+	}
+
+	/// Reads a witness in the `.wtns` format from `reader`.
+	#[inline]
+	pub fn read<R>(reader: R) -> Result<Self, SerializationError>
+	where
+		R: Read,
+	{
+		let sections = read_sections(reader, WTNS_MAGIC, WTNS_VERSION)?;
+		let mut header = section(&sections, WTNS_HEADER_SECTION)?;
+		read_field_header::<F, _>(&mut header)?;
+		let count = u32::deserialize(&mut header)?;
+		let mut reader = section(&sections, WTNS_VALUES_SECTION)?;
+		let mut values = Vec::new();
+		for _ in 0..count {
+			values.push(read_field(&mut reader)?);
+		}
+		Ok(Self { values })
+	}
+}
+
+impl<F> R1CS<F>
+where
+	F: PrimeField,
+{
+	/// Returns the constraints of `self` as a circom constraint system.
+	///
+	/// # Note
+	///
+	/// This finalizes the underlying arkworks constraint system, inlining all of its linear
+	/// combinations, so no more constraints should be added to `self` afterwards.
+	#[inline]
+	pub fn circom_constraints(&mut self) -> ConstraintSystem<F> {
+		self.cs.finalize();
+		let matrices = self
+			.cs
+			.to_matrices()
+			.expect("Constraint matrices are not allowed to be missing.");
+		let lc = |row: &Vec<(F, usize)>| {
+			row.iter()
+				.map(|(coefficient, wire)| (wire_index(*wire), *coefficient))
+				.collect()
+		};
+		ConstraintSystem {
+			public_inputs: wire_index(matrices.num_instance_variables - 1),
+			wires: wire_index(matrices.num_instance_variables + matrices.num_witness_variables),
+			constraints: matrices
+				.a
+				.iter()
+				.zip(&matrices.b)
+				.zip(&matrices.c)
+				.map(|((a, b), c)| Constraint { a: lc(a), b: lc(b), c: lc(c) })
+				.collect(),
+		}
+	}
+
+	/// Returns the variable assignment of `self` as a circom witness.
+	///
+	/// The witness is only complete if `self` was built with [`for_prove`](Self::for_prove).
+	#[inline]
+	pub fn circom_witness(&self) -> Witness<F> {
+		let cs = self.cs.borrow().expect("Constraint system is not allowed to be missing.");
+		Witness {
+			values: cs.instance_assignment.iter().chain(&cs.witness_assignment).copied().collect(),
+		}
+	}
+}
+
+/// Converts the arkworks variable index `index` into a circom wire index.
+#[inline]
+fn wire_index(index: usize) -> u32 {
+	u32::try_from(index).expect("Wire indices are not allowed to exceed `u32::MAX`.")
+}
+
+/// Converts `len` into the length prefix used by the circom formats.
+#[inline]
+fn length(len: usize) -> Result<u32, SerializationError> {
+	u32::try_from(len).map_err(|_| SerializationError::NotEnoughSpace)
+}
+
+/// Returns the number of bytes of a field element of `F`.
+#[inline]
+fn field_size<F>() -> u32
+where
+	F: PrimeField,
+{
+	(F::BigInt::NUM_LIMBS * 8) as u32
+}
+
+/// Writes the field element size and the modulus of `F` to `writer`.
+#[inline]
+fn write_field_header<F, W>(mut writer: W) -> Result<(), SerializationError>
+where
+	F: PrimeField,
+	W: Write,
+{
+	field_size::<F>().serialize(&mut writer)?;
+	F::Params::MODULUS.serialize(&mut writer)
+}
+
+/// Reads the field element size and the modulus from `reader`, checking that they match `F`.
+#[inline]
+fn read_field_header<F, R>(mut reader: R) -> Result<(), SerializationError>
+where
+	F: PrimeField,
+	R: Read,
+{
+	if u32::deserialize(&mut reader)? != field_size::<F>()
+		|| F::BigInt::deserialize(&mut reader)? != F::Params::MODULUS
+	{
+		return Err(SerializationError::InvalidData)
+	}
+	Ok(())
+}
+
+/// Reads a field element in its canonical representation from `reader`.
+#[inline]
+fn read_field<F, R>(reader: R) -> Result<F, SerializationError>
+where
+	F: PrimeField,
+	R: Read,
+{
+	F::from_repr(F::BigInt::deserialize(reader)?).ok_or(SerializationError::InvalidData)
+}
+
+/// Writes a section of type `kind` with the given `content` to `writer`.
+#[inline]
+fn write_section<W>(mut writer: W, kind: u32, content: &[u8]) -> Result<(), SerializationError>
+where
+	W: Write,
+{
+	kind.serialize(&mut writer)?;
+	(content.len() as u64).serialize(&mut writer)?;
+	writer.write_all(content)?;
+	Ok(())
+}
+
+/// Reads the magic bytes and the version from `reader`, checking that they match `magic` and
+/// `version`, and then reads all of the sections which follow.
+#[inline]
+fn read_sections<R>(
+	mut reader: R,
+	magic: [u8; 4],
+	version: u32,
+) -> Result<Vec<(u32, Vec<u8>)>, SerializationError>
+where
+	R: Read,
+{
+	let mut bytes = [0; 4];
+	reader.read_exact(&mut bytes)?;
+	if bytes != magic || u32::deserialize(&mut reader)? != version {
+		return Err(SerializationError::InvalidData)
+	}
+	let mut sections = Vec::new();
+	for _ in 0..u32::deserialize(&mut reader)? {
+		let kind = u32::deserialize(&mut reader)?;
+		let size = u64::deserialize(&mut reader)?;
+		let mut content =
+			alloc::vec![0; usize::try_from(size).map_err(|_| SerializationError::InvalidData)?];
+		reader.read_exact(&mut content)?;
+		sections.push((kind, content));
+	}
+	Ok(sections)
+}
+
+/// Returns the content of the first section of type `kind` in `sections`.
+#[inline]
+fn section(sections: &[(u32, Vec<u8>)], kind: u32) -> Result<&[u8], SerializationError> {
+	sections
+		.iter()
+		.find(|(k, _)| *k == kind)
+		.map(|(_, content)| content.as_slice())
+		.ok_or(SerializationError::InvalidData)
+}
+
+/// Testing Framework
+pub mod test {
+	use super::*;
+	use crate::{
+		circuit::{
+			claim_compiler, claim_witness_compiler, mint_compiler, mint_witness_compiler, Claim,
+			Mint,
+		},
+		config::{Config, ConstraintField, DefaultHeight, Scalar},
+		crypto::{
+			accumulator::Accumulator,
+			arkworks::{Fp, FpVar},
+			eclair::{
+				alloc::{
+					mode::{Public, Secret},
+					Allocate,
+				},
+				bool::AssertEq,
+			},
+			merkle_tree::MerkleTree,
+			rand::{Rand, RngCore},
+		},
+		parameters::parameters,
+	};
+
+	/// Asserts that `constraints` and `witness` round-trip through the `.r1cs` and `.wtns` formats
+	/// and that the re-imported constraint system is satisfied by the re-imported witness.
+	#[inline]
+	pub fn assert_round_trip<F>(constraints: &ConstraintSystem<F>, witness: &Witness<F>)
+	where
+		F: PrimeField,
+	{
+		let mut bytes = Vec::new();
+		constraints
+			.write(&mut bytes)
+			.expect("Writing to a vector is not allowed to fail.");
+		let decoded_constraints = ConstraintSystem::read(bytes.as_slice())
+			.expect("Constraints produced by `write` must decode.");
+		assert_eq!(
+			&decoded_constraints, constraints,
+			"Constraints did not round-trip through the `.r1cs` format."
+		);
+		let mut bytes = Vec::new();
+		witness.write(&mut bytes).expect("Writing to a vector is not allowed to fail.");
+		let decoded_witness =
+			Witness::read(bytes.as_slice()).expect("Witness produced by `write` must decode.");
+		assert_eq!(
+			&decoded_witness, witness,
+			"Witness did not round-trip through the `.wtns` format."
+		);
+		assert!(
+			decoded_constraints.is_satisfied(&decoded_witness),
+			"Re-imported constraints must be satisfied by the re-imported witness."
+		);
+	}
+
+	/// Exports a circuit proving knowledge of a square root of its public input and checks it with
+	/// [`assert_round_trip`], while a witness with a tampered public input is rejected.
+	#[inline]
+	pub fn assert_square_root_round_trip<F, R>(rng: &mut R)
+	where
+		F: PrimeField,
+		R: RngCore + ?Sized,
+	{
+		let mut compiler = R1CS::<F>::for_prove();
+		let root = rng.gen::<_, Fp<F>>();
+		let square: FpVar<F> = Fp(root.0 * root.0).as_known::<Public, _>(&mut compiler);
+		let root: FpVar<F> = root.as_known::<Secret, _>(&mut compiler);
+		let product = &root * &root;
+		compiler.assert_eq(&square, &product);
+		let constraints = compiler.circom_constraints();
+		let mut witness = compiler.circom_witness();
+		assert_round_trip(&constraints, &witness);
+		witness.values[1] += F::one();
+		assert!(
+			!constraints.is_satisfied(&witness),
+			"Tampered witness is not allowed to satisfy the constraints."
+		);
+	}
+	/// Exports the `circuit` built in prove mode with [`assert_round_trip`], checking that its
+	/// constraints match the constraints of the `compiled` circuit built without a witness and
+	/// that a witness with a tampered first public input is rejected.
+	#[inline]
+	pub fn assert_circuit_round_trip<F>(mut compiled: R1CS<F>, mut circuit: R1CS<F>)
+	where
+		F: PrimeField,
+	{
+		let constraints = circuit.circom_constraints();
+		assert_eq!(
+			compiled.circom_constraints(),
+			constraints,
+			"Constraints are not allowed to depend on the witness."
+		);
+		let mut witness = circuit.circom_witness();
+		assert_round_trip(&constraints, &witness);
+		witness.values[1] += F::one();
+		assert!(
+			!constraints.is_satisfied(&witness),
+			"Tampered witness is not allowed to satisfy the constraints."
+		);
+	}
+
+	/// Exports the mint circuit for a random key and value with [`assert_circuit_round_trip`].
+	#[inline]
+	pub fn assert_mint_round_trip<R>(rng: &mut R)
+	where
+		R: RngCore + ?Sized,
+	{
+		let parameters = parameters::<DefaultHeight>();
+		let value = Fp(ConstraintField::from(rng.next_u64()));
+		let data = Mint::<Config>::new(&parameters, rng.gen::<_, Scalar>(), value);
+		assert_circuit_round_trip(
+			mint_compiler::<Config>(&parameters),
+			mint_witness_compiler::<Config>(&parameters, &data),
+		);
+	}
+
+	/// Exports the claim circuit for a random key and value, whose UTXO is the only member of the
+	/// accumulator, with [`assert_circuit_round_trip`].
+	#[inline]
+	pub fn assert_claim_round_trip<R>(rng: &mut R)
+	where
+		R: RngCore + ?Sized,
+	{
+		let parameters = parameters::<DefaultHeight>();
+		let key = rng.gen::<_, Scalar>();
+		let value = Fp(ConstraintField::from(rng.next_u64()));
+		let mut accumulator = MerkleTree::new(parameters.merkle_tree_parameters.clone());
+		accumulator.insert(&Mint::<Config>::new(&parameters, key, value).utxo);
+		let data = Claim::<Config>::new(&parameters, &accumulator, key, value)
+			.expect("The claimed UTXO was inserted into the accumulator.");
+		assert_circuit_round_trip(
+			claim_compiler::<Config>(&parameters),
+			claim_witness_compiler::<Config>(&parameters, &data),
+		);
+	}
+}
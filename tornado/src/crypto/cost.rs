@@ -0,0 +1,486 @@
+//! Constraint Cost Estimation
+//!
+//! Synthesizing a full arkworks constraint system just to compare two designs of a circuit is slow,
+//! since every variable and linear combination has to be built. The [`CostCompiler`] in this module
+//! implements the same ECLAIR interfaces as [`R1CS`](crate::crypto::arkworks::R1CS), but it only
+//! keeps track of which variables are constants and counts the operations which the arkworks
+//! gadgets would have performed. The resulting [`Cost`] is an estimate of the size of the same
+//! circuit which is cheap enough to compute in unit tests.
+
+use crate::crypto::eclair::{
+	self,
+	alloc::{
+		mode::{Public, Secret},
+		Constant, Variable,
+	},
+	bool::{Assert, AssertEq, ConditionalSwap},
+	num::AssertWithinBitRange,
+	Has, Namespace, NonNative,
+};
+use core::{fmt, marker::PhantomData};
+
+/// Constraint Cost
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct Cost {
+	/// Number of Multiplications
+	///
+	/// Every constraint of a rank-one constraint system is a multiplication, so boolean checks and
+	/// equality assertions are counted as multiplications by one.
+	pub multiplications: usize,
+
+	/// Number of Linear Combinations
+	///
+	/// Additions and multiplications by constants only build linear combinations of existing
+	/// variables, which do not need any constraints on their own.
+	pub linear_combinations: usize,
+
+	/// Number of Allocations
+	///
+	/// This counts public inputs and secret witnesses, including the witnesses which hold the
+	/// results of multiplications.
+	pub allocations: usize,
+}
+
+impl fmt::Display for Cost {
+	#[inline]
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		writeln!(f, "multiplications:     {}", self.multiplications)?;
+		writeln!(f, "linear combinations: {}", self.linear_combinations)?;
+		writeln!(f, "allocations:         {}", self.allocations)
+	}
+}
+
+/// Variable Kind
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+enum Kind {
+	/// Constant
+	Constant,
+
+	/// Allocated Variable or Linear Combination of Variables
+	Variable,
+}
+
+impl Kind {
+	/// Returns the kind of a value computed from values of kind `lhs` and `rhs`.
+	#[inline]
+	fn join(lhs: Self, rhs: Self) -> Self {
+		match (lhs, rhs) {
+			(Self::Constant, Self::Constant) => Self::Constant,
+			_ => Self::Variable,
+		}
+	}
+}
+
+/// Constraint Cost Estimating Compiler
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct CostCompiler {
+	/// Accumulated Cost
+	cost: Cost,
+}
+
+impl CostCompiler {
+	/// Builds a new [`CostCompiler`] with no accumulated cost.
+	#[inline]
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Returns the cost accumulated by `self`.
+	#[inline]
+	pub fn cost(&self) -> Cost {
+		self.cost
+	}
+
+	/// Records an allocation, returning the kind of the allocated value.
+	#[inline]
+	fn allocate(&mut self) -> Kind {
+		self.cost.allocations += 1;
+		Kind::Variable
+	}
+
+	/// Records a linear combination of values of kind `lhs` and `rhs`, returning the kind of the
+	/// result.
+	#[inline]
+	fn linear_combination(&mut self, lhs: Kind, rhs: Kind) -> Kind {
+		let kind = Kind::join(lhs, rhs);
+		if kind == Kind::Variable {
+			self.cost.linear_combinations += 1;
+		}
+		kind
+	}
+
+	/// Records a multiplication of values of kind `lhs` and `rhs`, returning the kind of the
+	/// result. Multiplications by constants are linear combinations.
+	#[inline]
+	fn multiply(&mut self, lhs: Kind, rhs: Kind) -> Kind {
+		match (lhs, rhs) {
+			(Kind::Variable, Kind::Variable) => {
+				self.cost.multiplications += 1;
+				self.allocate()
+			},
+			_ => self.linear_combination(lhs, rhs),
+		}
+	}
+
+	/// Records a constraint on a value of kind `kind`. Constraints on constants are checked when
+	/// they are built and cost nothing.
+	#[inline]
+	fn constrain(&mut self, kind: Kind) {
+		if kind == Kind::Variable {
+			self.cost.multiplications += 1;
+		}
+	}
+}
+
+impl Namespace for CostCompiler {
+	#[inline]
+	fn namespace<T, F>(&mut self, label: &'static str, f: F) -> T
+	where
+		F: FnOnce(&mut Self) -> T,
+	{
+		let _ = label;
+		f(self)
+	}
+}
+
+impl NonNative for CostCompiler {}
+
+/// Boolean Variable
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Boolean {
+	/// Variable Kind
+	kind: Kind,
+}
+
+impl Boolean {
+	/// Builds a new constant [`Boolean`].
+	#[inline]
+	pub fn constant() -> Self {
+		Self { kind: Kind::Constant }
+	}
+
+	/// Returns `true` if `self` is a constant.
+	#[inline]
+	pub fn is_constant(&self) -> bool {
+		self.kind == Kind::Constant
+	}
+
+	/// Allocates a new [`Boolean`] into `compiler` together with the constraint that it is
+	/// either zero or one.
+	#[inline]
+	fn allocate(compiler: &mut CostCompiler) -> Self {
+		let kind = compiler.allocate();
+		compiler.constrain(kind);
+		Self { kind }
+	}
+}
+
+impl Has<bool> for CostCompiler {
+	type Type = Boolean;
+}
+
+impl Assert for CostCompiler {
+	#[inline]
+	fn assert(&mut self, bit: &Boolean) {
+		self.constrain(bit.kind)
+	}
+}
+
+impl AssertEq for CostCompiler {}
+
+impl Constant<CostCompiler> for Boolean {
+	type Type = bool;
+
+	#[inline]
+	fn new_constant(this: &Self::Type, compiler: &mut CostCompiler) -> Self {
+		let _ = (this, compiler);
+		Self::constant()
+	}
+}
+
+impl Variable<Public, CostCompiler> for Boolean {
+	type Type = bool;
+
+	#[inline]
+	fn new_known(this: &Self::Type, compiler: &mut CostCompiler) -> Self {
+		let _ = this;
+		Self::allocate(compiler)
+	}
+
+	#[inline]
+	fn new_unknown(compiler: &mut CostCompiler) -> Self {
+		Self::allocate(compiler)
+	}
+}
+
+impl Variable<Secret, CostCompiler> for Boolean {
+	type Type = bool;
+
+	#[inline]
+	fn new_known(this: &Self::Type, compiler: &mut CostCompiler) -> Self {
+		let _ = this;
+		Self::allocate(compiler)
+	}
+
+	#[inline]
+	fn new_unknown(compiler: &mut CostCompiler) -> Self {
+		Self::allocate(compiler)
+	}
+}
+
+impl eclair::cmp::PartialEq<Self, CostCompiler> for Boolean {
+	/// Compares `self` and `rhs` with an exclusive or, which allocates its result and costs one
+	/// constraint unless one of the sides is a constant.
+	#[inline]
+	fn eq(&self, rhs: &Self, compiler: &mut CostCompiler) -> Boolean {
+		match (self.kind, rhs.kind) {
+			(Kind::Variable, Kind::Variable) => {
+				Self { kind: compiler.multiply(self.kind, rhs.kind) }
+			},
+			_ => Self { kind: Kind::join(self.kind, rhs.kind) },
+		}
+	}
+}
+
+/// Field Element Variable
+///
+/// The type `T` is the underlying type of the field element, which is what the variable is
+/// allocated from.
+#[derive(derivative::Derivative)]
+#[derivative(
+	Clone(bound = ""),
+	Copy(bound = ""),
+	Debug(bound = ""),
+	Eq(bound = ""),
+	Hash(bound = ""),
+	PartialEq(bound = "")
+)]
+pub struct FieldVar<T> {
+	/// Variable Kind
+	kind: Kind,
+
+	/// Type Parameter Marker
+	__: PhantomData<T>,
+}
+
+impl<T> FieldVar<T> {
+	/// Builds a new [`FieldVar`] of the given `kind`.
+	#[inline]
+	fn new(kind: Kind) -> Self {
+		Self { kind, __: PhantomData }
+	}
+
+	/// Builds a new constant [`FieldVar`].
+	#[inline]
+	pub fn constant() -> Self {
+		Self::new(Kind::Constant)
+	}
+
+	/// Returns `true` if `self` is a constant.
+	#[inline]
+	pub fn is_constant(&self) -> bool {
+		self.kind == Kind::Constant
+	}
+
+	/// Adds `self` and `rhs` inside of `compiler`.
+	#[inline]
+	pub fn add(&self, rhs: &Self, compiler: &mut CostCompiler) -> Self {
+		Self::new(compiler.linear_combination(self.kind, rhs.kind))
+	}
+
+	/// Multiplies `self` and `rhs` inside of `compiler`.
+	#[inline]
+	pub fn mul(&self, rhs: &Self, compiler: &mut CostCompiler) -> Self {
+		Self::new(compiler.multiply(self.kind, rhs.kind))
+	}
+
+	/// Raises `self` to the power `exponent` inside of `compiler`, using the same square and
+	/// multiply algorithm as the arkworks gadget.
+	#[inline]
+	pub fn pow(&self, exponent: u64, compiler: &mut CostCompiler) -> Self {
+		let mut result = Self::constant();
+		for i in (0..(u64::BITS - exponent.leading_zeros())).rev() {
+			result = result.mul(&result, compiler);
+			if (exponent >> i) & 1 == 1 {
+				result = result.mul(self, compiler);
+			}
+		}
+		result
+	}
+
+	/// Computes the `exponent`-th root of `self` inside of `compiler` by allocating the root as a
+	/// witness and constraining its `exponent`-th power to be equal to `self`.
+	#[inline]
+	pub fn root(&self, exponent: u64, compiler: &mut CostCompiler) -> Self {
+		if self.is_constant() {
+			return Self::constant()
+		}
+		let root = Self::new(compiler.allocate());
+		let power = root.pow(exponent, compiler);
+		compiler.constrain(power.kind);
+		root
+	}
+}
+
+impl<T> Constant<CostCompiler> for FieldVar<T> {
+	type Type = T;
+
+	#[inline]
+	fn new_constant(this: &Self::Type, compiler: &mut CostCompiler) -> Self {
+		let _ = (this, compiler);
+		Self::constant()
+	}
+}
+
+impl<T> Variable<Public, CostCompiler> for FieldVar<T> {
+	type Type = T;
+
+	#[inline]
+	fn new_known(this: &Self::Type, compiler: &mut CostCompiler) -> Self {
+		let _ = this;
+		Self::new(compiler.allocate())
+	}
+
+	#[inline]
+	fn new_unknown(compiler: &mut CostCompiler) -> Self {
+		Self::new(compiler.allocate())
+	}
+}
+
+impl<T> Variable<Secret, CostCompiler> for FieldVar<T> {
+	type Type = T;
+
+	#[inline]
+	fn new_known(this: &Self::Type, compiler: &mut CostCompiler) -> Self {
+		let _ = this;
+		Self::new(compiler.allocate())
+	}
+
+	#[inline]
+	fn new_unknown(compiler: &mut CostCompiler) -> Self {
+		Self::new(compiler.allocate())
+	}
+}
+
+impl<T> eclair::cmp::PartialEq<Self, CostCompiler> for FieldVar<T> {
+	/// Compares `self` and `rhs`, which allocates a boolean and a multiplier and costs three
+	/// constraints unless both sides are constants.
+	#[inline]
+	fn eq(&self, rhs: &Self, compiler: &mut CostCompiler) -> Boolean {
+		match Kind::join(self.kind, rhs.kind) {
+			Kind::Constant => Boolean::constant(),
+			_ => {
+				let is_not_equal = Boolean::allocate(compiler);
+				compiler.allocate();
+				compiler.constrain(Kind::Variable);
+				compiler.constrain(Kind::Variable);
+				is_not_equal
+			},
+		}
+	}
+}
+
+impl<T> ConditionalSwap<CostCompiler> for FieldVar<T> {
+	/// Swaps `lhs` and `rhs` with two conditional selections, each of which allocates its result
+	/// and costs one constraint unless `bit` or both of the values are constants.
+	#[inline]
+	fn swap(bit: &Boolean, lhs: &Self, rhs: &Self, compiler: &mut CostCompiler) -> (Self, Self) {
+		let kind = Kind::join(lhs.kind, rhs.kind);
+		let kind = match (bit.kind, kind) {
+			(Kind::Constant, _) => kind,
+			(_, Kind::Constant) => {
+				compiler.linear_combination(bit.kind, kind);
+				compiler.linear_combination(bit.kind, kind)
+			},
+			_ => {
+				compiler.multiply(bit.kind, kind);
+				compiler.multiply(bit.kind, kind)
+			},
+		};
+		(Self::new(kind), Self::new(kind))
+	}
+}
+
+impl<T, const BITS: usize> AssertWithinBitRange<FieldVar<T>, BITS> for CostCompiler {
+	/// Asserts that `value` fits in `BITS` bits by decomposing it into `BITS` boolean witnesses
+	/// and recomposing them, costing `BITS + 1` constraints.
+	#[inline]
+	fn assert_within_range(&mut self, value: &FieldVar<T>) {
+		let bits = (0..BITS).fold(Kind::Constant, |acc, _| {
+			let bit = Boolean::allocate(self);
+			Kind::join(acc, bit.kind)
+		});
+		let recomposed = self.linear_combination(bits, Kind::Constant);
+		self.constrain(Kind::join(recomposed, value.kind));
+	}
+}
+
+/// Testing Framework
+pub mod test {
+	use super::*;
+	use crate::{
+		circuit::{
+			claim_compiler, claim_cost, mint_compiler, mint_cost, partial_claim_compiler,
+			partial_claim_cost, transfer_compiler, transfer_cost,
+		},
+		config::{Config, CostConfig, DefaultHeight},
+		parameters::parameters,
+	};
+
+	/// Asserts that the cost of raising a variable to the power `exponent` matches the cost of
+	/// the square and multiply algorithm in the arkworks gadget.
+	#[inline]
+	pub fn assert_pow_cost(exponent: u64) {
+		let mut compiler = CostCompiler::new();
+		let base = <FieldVar<()> as Variable<Secret, _>>::new_unknown(&mut compiler);
+		let before = compiler.cost();
+		base.pow(exponent, &mut compiler);
+		let bits = (u64::BITS - exponent.leading_zeros()) as usize;
+		let ones = exponent.count_ones() as usize;
+		assert_eq!(
+			compiler.cost().multiplications - before.multiplications,
+			bits.saturating_sub(1) + ones.saturating_sub(1),
+			"Exponentiation did not cost one multiplication per squaring and per extra set bit."
+		);
+	}
+
+	/// Asserts that operations on constants are free.
+	#[inline]
+	pub fn assert_constants_are_free() {
+		let mut compiler = CostCompiler::new();
+		let lhs = FieldVar::<()>::constant();
+		let rhs = FieldVar::<()>::constant();
+		let sum = lhs.add(&rhs, &mut compiler);
+		let product = sum.mul(&rhs, &mut compiler).pow(5, &mut compiler);
+		assert!(product.is_constant(), "Operations on constants must return constants.");
+		compiler.assert_eq(&product, &lhs);
+		assert_eq!(compiler.cost(), Cost::default(), "Operations on constants must be free.");
+	}
+
+	/// Asserts that the estimated number of multiplications of every circuit matches the number
+	/// of constraints in its compiled constraint system.
+	#[inline]
+	pub fn assert_circuit_costs_match_constraints() {
+		let parameters = parameters::<DefaultHeight>();
+		assert_eq!(
+			mint_cost::<CostConfig>(&parameters).multiplications,
+			mint_compiler::<Config>(&parameters).num_constraints(),
+			"Mint cost estimate does not match the mint circuit."
+		);
+		assert_eq!(
+			claim_cost::<CostConfig>(&parameters).multiplications,
+			claim_compiler::<Config>(&parameters).num_constraints(),
+			"Claim cost estimate does not match the claim circuit."
+		);
+		assert_eq!(
+			partial_claim_cost::<CostConfig>(&parameters).multiplications,
+			partial_claim_compiler::<Config>(&parameters).num_constraints(),
+			"Partial claim cost estimate does not match the partial claim circuit."
+		);
+		assert_eq!(
+			transfer_cost::<CostConfig>(&parameters).multiplications,
+			transfer_compiler::<Config>(&parameters).num_constraints(),
+			"Transfer cost estimate does not match the transfer circuit."
+		);
+	}
+}
@@ -26,9 +26,17 @@
 //! Allocation does not necessarily refer to allocation of memory nor the simulation of memory-based
 //! abstractions inside of compilers, like heap allocation. Allocation only refers to lifting
 //! constants and variables from one compiler to another.
+//!
+//! # Derive Macros
+//!
+//! Structs whose fields are allocated one by one can implement [`Constant`] and [`Variable`]
+//! with the derive macros of the same name, exported from this module. See the `tornado-derive`
+//! crate for the `#[eclair(..)]` attributes they accept.
 
 use core::marker::PhantomData;
 
+pub use tornado_derive::{Constant, Variable};
+
 /// Constant Type Alias
 pub type Const<C, COM> = <C as Constant<COM>>::Type;
 
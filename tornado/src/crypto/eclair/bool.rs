@@ -25,6 +25,10 @@ use crate::crypto::eclair::{cmp::PartialEq, Has};
 /// Boolean Type Inside of the Compiler
 pub type Bool<COM = ()> = <COM as Has<bool>>::Type;
 
+impl Has<bool> for () {
+	type Type = bool;
+}
+
 /// Assertion
 pub trait Assert: Has<bool> {
 	/// Asserts that `bit` reduces to `true`.
@@ -42,7 +46,6 @@ pub trait Assert: Has<bool> {
 	}
 }
 
-/* FIXME: We cannot implement this yet.
 impl Assert for () {
 	#[inline]
 	fn assert(&mut self, bit: &Bool<Self>) {
@@ -50,7 +53,6 @@ impl Assert for () {
 		assert!(bit);
 	}
 }
-*/
 
 /// Equality Assertion
 pub trait AssertEq: Assert {
@@ -91,6 +93,8 @@ pub trait AssertEq: Assert {
 	}
 }
 
+impl AssertEq for () {}
+
 /// Conditional Selection
 pub trait ConditionalSelect<COM>: Sized
 where
@@ -115,8 +119,7 @@ where
 	}
 }
 
-/* FIXME: We cannot implement this yet.
-impl<V> ConditionalSelect for V {
+impl<V> ConditionalSelect<()> for V {
 	#[inline]
 	fn select_from<T, F>(bit: &bool, true_value: T, false_value: F, _: &mut ()) -> Self
 	where
@@ -130,7 +133,6 @@ impl<V> ConditionalSelect for V {
 		}
 	}
 }
-*/
 
 /// Conditional Swap
 pub trait ConditionalSwap<COM>: Sized
@@ -142,15 +144,16 @@ where
 	fn swap(bit: &Bool<COM>, lhs: &Self, rhs: &Self, compiler: &mut COM) -> (Self, Self);
 }
 
-/* FIXME: We cannot implement this yet.
-impl<V> ConditionalSwap for V {
+impl<V> ConditionalSwap<()> for V
+where
+	V: Clone,
+{
 	#[inline]
-	fn swap(bit: &bool, lhs: Self, rhs: Self, _: &mut ()) -> (Self, Self) {
+	fn swap(bit: &bool, lhs: &Self, rhs: &Self, _: &mut ()) -> (Self, Self) {
 		if *bit {
-			(rhs, lhs)
+			(rhs.clone(), lhs.clone())
 		} else {
-			(lhs, rhs)
+			(lhs.clone(), rhs.clone())
 		}
 	}
 }
-*/
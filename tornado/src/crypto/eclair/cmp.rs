@@ -17,6 +17,7 @@
 //! Comparison
 
 use crate::crypto::eclair::{bool::Bool, Has};
+use core::cmp;
 
 /// Partial Equivalence Relations
 pub trait PartialEq<Rhs, COM>
@@ -28,22 +29,16 @@ where
 	fn eq(&self, rhs: &Rhs, compiler: &mut COM) -> Bool<COM>;
 }
 
-/* FIXME: We cannot implement this yet.
-impl<T, Rhs> PartialEq<Rhs> for T
+impl<T, Rhs> PartialEq<Rhs, ()> for T
 where
-	T: cmp::PartialEq<Rhs>,
+	T: cmp::PartialEq<Rhs> + ?Sized,
+	Rhs: ?Sized,
 {
 	#[inline]
 	fn eq(&self, rhs: &Rhs, _: &mut ()) -> bool {
-		self.eq(rhs)
-	}
-
-	#[inline]
-	fn ne(&self, rhs: &Rhs, _: &mut ()) -> bool {
-		self.ne(rhs)
+		cmp::PartialEq::eq(self, rhs)
 	}
 }
-*/
 
 /// Equality
 pub trait Eq<COM>: PartialEq<Self, COM>
@@ -52,6 +47,4 @@ where
 {
 }
 
-/* FIXME: We cannot implement this yet.
-impl<T> Eq for T where T: cmp::Eq {}
-*/
+impl<T> Eq<()> for T where T: cmp::Eq {}
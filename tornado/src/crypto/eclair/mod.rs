@@ -3,6 +3,7 @@
 pub mod alloc;
 pub mod bool;
 pub mod cmp;
+pub mod num;
 
 /// Compiler Type Introspection
 pub trait Has<T> {
@@ -15,3 +16,31 @@ pub trait Has<T> {
 	/// See the [`bool`] module for an example of how to use introspection.
 	type Type;
 }
+
+/// Non-Native Compiler Marker
+///
+/// This trait is explicitly not implemented for `()`, the native compiler. Since `()` now
+/// implements the same ECLAIR traits as any other compiler, this marker lets us write separate
+/// implementations for native and non-native compilers whenever their types differ, for example
+/// when a native type and its variable are distinct `struct`s.
+pub trait NonNative {}
+
+/// Compiler Namespaces
+pub trait Namespace {
+	/// Runs `f` inside of a namespace labelled `label`, letting the compiler attribute any
+	/// constraints and variables allocated by `f` to that namespace.
+	fn namespace<T, F>(&mut self, label: &'static str, f: F) -> T
+	where
+		F: FnOnce(&mut Self) -> T;
+}
+
+impl Namespace for () {
+	#[inline]
+	fn namespace<T, F>(&mut self, label: &'static str, f: F) -> T
+	where
+		F: FnOnce(&mut Self) -> T,
+	{
+		let _ = label;
+		f(self)
+	}
+}
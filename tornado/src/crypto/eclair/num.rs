@@ -0,0 +1,78 @@
+//! Numeric Types
+//!
+//! Field elements inside of a compiler wrap around the modulus, so any value which stands in for a
+//! machine integer has to be constrained to its bit range before it can be added or subtracted
+//! safely. In this module we define the [`UnsignedInteger`] gadget which carries that constraint.
+
+use crate::crypto::eclair::alloc::Variable;
+
+/// Bit Range Assertion
+pub trait AssertWithinBitRange<T, const BITS: usize> {
+	/// Asserts that `value` is smaller than `2^BITS`.
+	fn assert_within_range(&mut self, value: &T);
+}
+
+/// Unsigned Integer
+///
+/// This type wraps a value of type `T` which was asserted to be smaller than `2^BITS` when it was
+/// built with [`new`](Self::new) or allocated as a [`Variable`].
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct UnsignedInteger<T, const BITS: usize>(T);
+
+impl<T, const BITS: usize> UnsignedInteger<T, BITS> {
+	/// Builds a new [`UnsignedInteger`] from `value`, asserting that it fits in `BITS` bits.
+	#[inline]
+	pub fn new<COM>(value: T, compiler: &mut COM) -> Self
+	where
+		COM: AssertWithinBitRange<T, BITS> + ?Sized,
+	{
+		compiler.assert_within_range(&value);
+		Self(value)
+	}
+
+	/// Builds a new [`UnsignedInteger`] from `value` without checking that it fits in `BITS` bits.
+	#[inline]
+	pub fn new_unchecked(value: T) -> Self {
+		Self(value)
+	}
+
+	/// Returns the underlying value of `self`.
+	#[inline]
+	pub fn value(&self) -> &T {
+		&self.0
+	}
+
+	/// Returns the underlying value of `self`, dropping the range guarantee.
+	#[inline]
+	pub fn into_inner(self) -> T {
+		self.0
+	}
+}
+
+impl<T, const BITS: usize> AsRef<T> for UnsignedInteger<T, BITS> {
+	#[inline]
+	fn as_ref(&self) -> &T {
+		&self.0
+	}
+}
+
+impl<T, M, COM, const BITS: usize> Variable<M, COM> for UnsignedInteger<T, BITS>
+where
+	T: Variable<M, COM>,
+	COM: AssertWithinBitRange<T, BITS>,
+{
+	type Type = T::Type;
+
+	#[inline]
+	fn new_unknown(compiler: &mut COM) -> Self {
+		Self::new(T::new_unknown(compiler), compiler)
+	}
+
+	#[inline]
+	fn new_known(this: &Self::Type, compiler: &mut COM) -> Self {
+		Self::new(T::new_known(this, compiler), compiler)
+	}
+}
+
+/// 64-bit Unsigned Integer
+pub type UInt64<T> = UnsignedInteger<T, 64>;
@@ -0,0 +1,29 @@
+//! Hash Functions
+
+/// Fixed-Arity Hash Function
+///
+/// Hashes a fixed number of inputs, either natively with `COM = ()` or inside of a compiler. The
+/// same parameters can be used in every compiler, so that choosing a different implementation
+/// only changes the type of the hash and not the code which uses it.
+pub trait ArityHash<const ARITY: usize, COM = ()> {
+	/// Input Type
+	type Input;
+
+	/// Output Type
+	type Output;
+
+	/// Computes the hash over `input` in the given `domain` and `compiler`. Hashes in different
+	/// domains are independent even when they share the same parameters.
+	fn hash_in_domain(
+		&self,
+		domain: u64,
+		input: [&Self::Input; ARITY],
+		compiler: &mut COM,
+	) -> Self::Output;
+
+	/// Computes the hash over `input` in the given `compiler`, using the default domain `0`.
+	#[inline]
+	fn hash(&self, input: [&Self::Input; ARITY], compiler: &mut COM) -> Self::Output {
+		self.hash_in_domain(0, input, compiler)
+	}
+}
@@ -0,0 +1,353 @@
+//! File-Backed Merkle Tree Storage
+//!
+//! [`PersistentMerkleTree`] keeps a [`FullMerkleTree`] in memory and mirrors it to an append-only
+//! log file. Every leaf insertion is written as one record holding the new leaf digest and every
+//! inner digest that the insertion changed, so a crash can only lose the insertion that was being
+//! written and never leaves a partially updated tree behind. Records are checksummed and an
+//! incomplete record at the end of the log is truncated when the log is reopened, while a complete
+//! record with a bad checksum is reported as corrupted instead of being discarded.
+
+use super::{
+	full::{Full, FullMerkleTree},
+	inner_tree::{self, InnerMap, InnerTree},
+	Configuration, InnerDigest, Leaf, LeafDigest, MerkleTree, Parameters, Root,
+};
+use crate::util::MaybeSendSync;
+use alloc::vec::Vec;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, SerializationError};
+use core::mem;
+use std::{
+	fs::{File, OpenOptions},
+	io::{self, Read, Write},
+	path::Path,
+};
+
+/// Record Header Size
+///
+/// Each record starts with the length of its payload as a little-endian `u32` followed by the
+/// checksum of its payload as a little-endian `u64`.
+const HEADER_SIZE: usize = 12;
+
+/// Returns the FNV-1a checksum of `bytes`.
+#[inline]
+fn checksum(bytes: &[u8]) -> u64 {
+	bytes.iter().fold(0xcbf29ce484222325, |hash, byte| {
+		(hash ^ u64::from(*byte)).wrapping_mul(0x100000001b3)
+	})
+}
+
+/// Converts a serialization `error` into an I/O error.
+#[inline]
+fn invalid_data(error: SerializationError) -> io::Error {
+	io::Error::new(io::ErrorKind::InvalidData, error)
+}
+
+/// Returns the payload of the record starting at `offset` in `log` and the offset of the next
+/// record, or `None` if the log ends before a complete record at `offset`. A complete record whose
+/// checksum does not match its payload is an [`InvalidData`](io::ErrorKind::InvalidData) error.
+#[inline]
+fn next_record(log: &[u8], offset: usize) -> io::Result<Option<(&[u8], usize)>> {
+	let (header, start) = match log.get(offset..offset + HEADER_SIZE) {
+		Some(header) => (header, offset + HEADER_SIZE),
+		_ => return Ok(None),
+	};
+	let mut length = [0; 4];
+	let mut expected = [0; 8];
+	length.copy_from_slice(&header[..4]);
+	expected.copy_from_slice(&header[4..]);
+	let end = start + u32::from_le_bytes(length) as usize;
+	let payload = match log.get(start..end) {
+		Some(payload) => payload,
+		_ => return Ok(None),
+	};
+	if checksum(payload) != u64::from_le_bytes(expected) {
+		return Err(io::Error::new(
+			io::ErrorKind::InvalidData,
+			"Log record checksum does not match its payload.",
+		))
+	}
+	Ok(Some((payload, end)))
+}
+
+/// Reads a little-endian `u64` from `reader`.
+#[inline]
+fn read_u64(reader: &mut &[u8]) -> io::Result<u64> {
+	let mut bytes = [0; 8];
+	reader.read_exact(&mut bytes)?;
+	Ok(u64::from_le_bytes(bytes))
+}
+
+/// Logged Inner Digest Map
+///
+/// An [`InnerMap`] which stores its digests in memory and remembers which indices were written
+/// since the last time [`PersistentMerkleTree`] appended them to its log.
+#[derive(derivative::Derivative)]
+#[derivative(Default(bound = ""))]
+pub struct LogMap<C>
+where
+	C: Configuration + ?Sized,
+{
+	/// Inner Digests
+	map: inner_tree::BTreeMap<C>,
+
+	/// Indices Written since the Last Record
+	writes: Vec<usize>,
+}
+
+impl<C> InnerMap<C> for LogMap<C>
+where
+	C: Configuration + ?Sized,
+{
+	#[inline]
+	fn get(&self, index: usize) -> Option<&InnerDigest<C>> {
+		self.map.get(&index)
+	}
+
+	#[inline]
+	fn set(&mut self, index: usize, inner_digest: InnerDigest<C>) {
+		self.map.insert(index, inner_digest);
+		self.writes.push(index);
+	}
+
+	#[inline]
+	fn remove(&mut self, index: usize) {
+		self.map.remove(&index);
+		self.writes.retain(|i| *i != index);
+	}
+}
+
+/// Persistent Merkle Tree
+pub struct PersistentMerkleTree<C>
+where
+	C: Configuration + ?Sized,
+	Parameters<C>: MaybeSendSync,
+	LeafDigest<C>: Clone + Default + MaybeSendSync,
+	InnerDigest<C>: Clone + Default + PartialEq + MaybeSendSync,
+{
+	/// Merkle Tree
+	tree: FullMerkleTree<C, LogMap<C>>,
+
+	/// Log File
+	file: File,
+}
+
+impl<C> PersistentMerkleTree<C>
+where
+	C: Configuration + ?Sized,
+	Parameters<C>: MaybeSendSync,
+	LeafDigest<C>: Clone + Default + CanonicalSerialize + CanonicalDeserialize + MaybeSendSync,
+	InnerDigest<C>:
+		Clone + Default + PartialEq + CanonicalSerialize + CanonicalDeserialize + MaybeSendSync,
+{
+	/// Opens the log at `path`, creating it if it does not exist, and replays it into a tree over
+	/// `parameters`. An incomplete record at the end of the log is truncated, and a complete record
+	/// with a bad checksum is an [`InvalidData`](io::ErrorKind::InvalidData) error which leaves the
+	/// log untouched.
+	#[inline]
+	pub fn open<P>(path: P, parameters: Parameters<C>) -> io::Result<Self>
+	where
+		P: AsRef<Path>,
+	{
+		let mut file = OpenOptions::new().read(true).append(true).create(true).open(path)?;
+		let mut log = Vec::new();
+		file.read_to_end(&mut log)?;
+		let mut leaf_digests = Vec::new();
+		let mut map = LogMap::<C>::default();
+		let mut offset = 0;
+		while let Some((mut payload, next)) = next_record(&log, offset)? {
+			leaf_digests.push(LeafDigest::<C>::deserialize(&mut payload).map_err(invalid_data)?);
+			for _ in 0..read_u64(&mut payload)? {
+				let index = read_u64(&mut payload)? as usize;
+				map.map.insert(
+					index,
+					InnerDigest::<C>::deserialize(&mut payload).map_err(invalid_data)?,
+				);
+			}
+			offset = next;
+		}
+		if offset < log.len() {
+			file.set_len(offset as u64)?;
+		}
+		Ok(Self {
+			tree: MerkleTree::from_tree(
+				Full::new_unchecked(leaf_digests, InnerTree::with_map(map)),
+				parameters,
+			),
+			file,
+		})
+	}
+
+	/// Returns a shared reference to the in-memory tree.
+	#[inline]
+	pub fn tree(&self) -> &FullMerkleTree<C, LogMap<C>> {
+		&self.tree
+	}
+
+	/// Returns the number of leaves in the tree.
+	#[inline]
+	pub fn len(&self) -> usize {
+		self.tree.len()
+	}
+
+	/// Returns `true` if the tree is empty.
+	#[inline]
+	pub fn is_empty(&self) -> bool {
+		self.tree.is_empty()
+	}
+
+	/// Returns the current root of the tree.
+	#[inline]
+	pub fn root(&self) -> &Root<C> {
+		self.tree.root()
+	}
+
+	/// Builds the log record for the last leaf which was pushed to the tree.
+	#[inline]
+	fn record(&mut self) -> io::Result<Vec<u8>> {
+		let mut payload = Vec::new();
+		self.tree
+			.current_leaf()
+			.expect("The tree is not allowed to be empty after a push.")
+			.serialize(&mut payload)
+			.map_err(invalid_data)?;
+		let map = self.tree.tree.inner_digests_mut().map_mut();
+		let writes = mem::take(&mut map.writes);
+		payload.extend((writes.len() as u64).to_le_bytes());
+		for index in writes {
+			payload.extend((index as u64).to_le_bytes());
+			map.map[&index].serialize(&mut payload).map_err(invalid_data)?;
+		}
+		let mut record = Vec::with_capacity(HEADER_SIZE + payload.len());
+		record.extend((payload.len() as u32).to_le_bytes());
+		record.extend(checksum(&payload).to_le_bytes());
+		record.extend(payload);
+		Ok(record)
+	}
+
+	/// Appends `leaf` to the tree and to the log, returning `false` if the tree is full.
+	///
+	/// The record is appended but not synced to disk, see [`sync`](Self::sync). If this method
+	/// returns an error, the in-memory tree is ahead of the log and `self` should be reopened.
+	#[inline]
+	pub fn push(&mut self, leaf: &Leaf<C>) -> io::Result<bool> {
+		if !self.tree.push(leaf) {
+			return Ok(false)
+		}
+		let record = self.record()?;
+		self.file.write_all(&record)?;
+		Ok(true)
+	}
+
+	/// Appends every leaf in `leaves` to the tree and to the log, returning `false` if the tree
+	/// became full before all of the leaves were inserted.
+	#[inline]
+	pub fn extend<'l, L>(&mut self, leaves: L) -> io::Result<bool>
+	where
+		Leaf<C>: 'l,
+		L: IntoIterator<Item = &'l Leaf<C>>,
+	{
+		for leaf in leaves {
+			if !self.push(leaf)? {
+				return Ok(false)
+			}
+		}
+		Ok(true)
+	}
+
+	/// Flushes every appended record to disk.
+	#[inline]
+	pub fn sync(&self) -> io::Result<()> {
+		self.file.sync_data()
+	}
+}
+
+/// Testing Framework
+pub mod test {
+	use super::*;
+
+	/// Tests that reopening the log at `path` after inserting `leaves` gives back the same tree,
+	/// even if a torn record was left at the end of the log.
+	///
+	/// # Panics
+	///
+	/// This function panics if the log at `path` is not empty to begin with.
+	#[inline]
+	pub fn assert_reopen_preserves_tree<C>(
+		parameters: &Parameters<C>,
+		leaves: &[Leaf<C>],
+		path: &Path,
+	) where
+		C: Configuration + ?Sized,
+		Leaf<C>: Sized,
+		Parameters<C>: Clone + MaybeSendSync,
+		LeafDigest<C>: Clone + Default + CanonicalSerialize + CanonicalDeserialize + MaybeSendSync,
+		InnerDigest<C>: Clone
+			+ Default
+			+ PartialEq
+			+ CanonicalSerialize
+			+ CanonicalDeserialize
+			+ MaybeSendSync
+			+ core::fmt::Debug,
+	{
+		let mut tree =
+			PersistentMerkleTree::open(path, parameters.clone()).expect("Unable to open log.");
+		assert!(tree.is_empty(), "The log must be empty.");
+		tree.extend(leaves).expect("Unable to append to log.");
+		tree.sync().expect("Unable to sync log.");
+		let root = tree.root().clone();
+		let expected = FullMerkleTree::<C>::from_slice(parameters.clone(), leaves)
+			.expect("Unable to build in-memory tree.");
+		assert_eq!(&root, expected.root(), "Persistent and in-memory roots must agree.");
+		drop(tree);
+		OpenOptions::new()
+			.append(true)
+			.open(path)
+			.and_then(|mut file| file.write_all(&[0xFF; HEADER_SIZE + 1]))
+			.expect("Unable to tear log.");
+		let tree =
+			PersistentMerkleTree::open(path, parameters.clone()).expect("Unable to reopen log.");
+		assert_eq!(tree.len(), leaves.len(), "Reopened tree must keep every leaf.");
+		assert_eq!(tree.root(), &root, "Reopened tree must keep its root.");
+	}
+
+	/// Tests that reopening the log at `path` after corrupting the payload of its first record
+	/// fails with [`InvalidData`](io::ErrorKind::InvalidData) and keeps every record in the log.
+	///
+	/// # Panics
+	///
+	/// This function panics if the log at `path` is not empty to begin with or if `leaves` is
+	/// empty.
+	#[inline]
+	pub fn assert_corrupted_record_is_rejected<C>(
+		parameters: &Parameters<C>,
+		leaves: &[Leaf<C>],
+		path: &Path,
+	) where
+		C: Configuration + ?Sized,
+		Leaf<C>: Sized,
+		Parameters<C>: Clone + MaybeSendSync,
+		LeafDigest<C>: Clone + Default + CanonicalSerialize + CanonicalDeserialize + MaybeSendSync,
+		InnerDigest<C>:
+			Clone + Default + PartialEq + CanonicalSerialize + CanonicalDeserialize + MaybeSendSync,
+	{
+		assert!(!leaves.is_empty(), "At least one leaf is required to corrupt a record.");
+		let mut tree =
+			PersistentMerkleTree::open(path, parameters.clone()).expect("Unable to open log.");
+		assert!(tree.is_empty(), "The log must be empty.");
+		tree.extend(leaves).expect("Unable to append to log.");
+		tree.sync().expect("Unable to sync log.");
+		drop(tree);
+		let mut log = std::fs::read(path).expect("Unable to read log.");
+		log[HEADER_SIZE] ^= 0xFF;
+		std::fs::write(path, &log).expect("Unable to corrupt log.");
+		let error = PersistentMerkleTree::open(path, parameters.clone())
+			.err()
+			.expect("Corrupted records are not allowed to be replayed.");
+		assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+		assert_eq!(
+			std::fs::read(path).expect("Unable to read log."),
+			log,
+			"Corrupted logs are not allowed to be truncated."
+		);
+	}
+}
@@ -20,16 +20,147 @@ use super::{
 	capacity,
 	inner_tree::{BTreeMap, InnerMap, InnerTree},
 	Configuration, CurrentPath, InnerDigest, LeafDigest, MerkleTree, Node, Parameters, Path,
-	PathError, Root, Tree, WithProofs,
+	PathError, Root, Tree, WithCheckpoints, WithProofs,
 };
-use alloc::vec::Vec;
+use crate::util::MaybeSendSync;
+use alloc::{collections::btree_map, vec::Vec};
 use core::{fmt::Debug, hash::Hash};
 
 #[cfg(feature = "serde")]
 use manta_util::serde::{Deserialize, Serialize};
 
+#[cfg(feature = "std")]
+use std::{collections::hash_map, hash::BuildHasher};
+
 /// Full Merkle Tree Type
-pub type FullMerkleTree<C, M = BTreeMap<C>> = MerkleTree<C, Full<C, M>>;
+pub type FullMerkleTree<C, M = BTreeMap<C>, I = NoIndex> = MerkleTree<C, Full<C, M, I>>;
+
+/// [`Full`] Leaf Digest Index
+///
+/// A leaf index maps leaf digests to their positions in the tree so that
+/// [`position`](WithProofs::position) does not have to scan every leaf. Whenever the same leaf
+/// digest is stored more than once, the index must return its first position.
+pub trait LeafIndex<C>
+where
+	C: Configuration + ?Sized,
+{
+	/// Returns the position of `leaf_digest` in `leaf_digests`, which are the leaves indexed by
+	/// `self`.
+	fn position(
+		&self,
+		leaf_digests: &[LeafDigest<C>],
+		leaf_digest: &LeafDigest<C>,
+	) -> Option<usize>
+	where
+		LeafDigest<C>: PartialEq;
+
+	/// Records that `leaf_digest` is stored at `index`, keeping any earlier position of
+	/// `leaf_digest`.
+	fn insert(&mut self, leaf_digest: &LeafDigest<C>, index: usize);
+
+	/// Removes `leaf_digest` from `self` if it was recorded at `index`.
+	fn remove(&mut self, leaf_digest: &LeafDigest<C>, index: usize);
+}
+
+/// Empty [`Full`] Leaf Index
+///
+/// This index does not store anything, so [`position`](LeafIndex::position) scans the leaves
+/// linearly.
+#[cfg_attr(
+	feature = "serde",
+	derive(Deserialize, Serialize),
+	serde(crate = "manta_util::serde", deny_unknown_fields)
+)]
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct NoIndex;
+
+impl<C> LeafIndex<C> for NoIndex
+where
+	C: Configuration + ?Sized,
+{
+	#[inline]
+	fn position(&self, leaf_digests: &[LeafDigest<C>], leaf_digest: &LeafDigest<C>) -> Option<usize>
+	where
+		LeafDigest<C>: PartialEq,
+	{
+		leaf_digests.iter().position(move |d| d == leaf_digest)
+	}
+
+	#[inline]
+	fn insert(&mut self, leaf_digest: &LeafDigest<C>, index: usize) {
+		let _ = (leaf_digest, index);
+	}
+
+	#[inline]
+	fn remove(&mut self, leaf_digest: &LeafDigest<C>, index: usize) {
+		let _ = (leaf_digest, index);
+	}
+}
+
+/// B-Tree Map [`Full`] Leaf Index
+pub type BTreeIndex<C> = btree_map::BTreeMap<LeafDigest<C>, usize>;
+
+impl<C> LeafIndex<C> for BTreeIndex<C>
+where
+	C: Configuration + ?Sized,
+	LeafDigest<C>: Clone + Ord,
+{
+	#[inline]
+	fn position(&self, leaf_digests: &[LeafDigest<C>], leaf_digest: &LeafDigest<C>) -> Option<usize>
+	where
+		LeafDigest<C>: PartialEq,
+	{
+		let _ = leaf_digests;
+		self.get(leaf_digest).copied()
+	}
+
+	#[inline]
+	fn insert(&mut self, leaf_digest: &LeafDigest<C>, index: usize) {
+		self.entry(leaf_digest.clone()).or_insert(index);
+	}
+
+	#[inline]
+	fn remove(&mut self, leaf_digest: &LeafDigest<C>, index: usize) {
+		if self.get(leaf_digest) == Some(&index) {
+			self.remove(leaf_digest);
+		}
+	}
+}
+
+/// Hash Map [`Full`] Leaf Index
+#[cfg(feature = "std")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "std")))]
+pub type HashIndex<C, S = hash_map::RandomState> = hash_map::HashMap<LeafDigest<C>, usize, S>;
+
+#[cfg(feature = "std")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "std")))]
+impl<C, S> LeafIndex<C> for HashIndex<C, S>
+where
+	C: Configuration + ?Sized,
+	LeafDigest<C>: Clone + Eq + Hash,
+	S: BuildHasher,
+{
+	#[inline]
+	fn position(&self, leaf_digests: &[LeafDigest<C>], leaf_digest: &LeafDigest<C>) -> Option<usize>
+	where
+		LeafDigest<C>: PartialEq,
+	{
+		let _ = leaf_digests;
+		self.get(leaf_digest).copied()
+	}
+
+	#[inline]
+	fn insert(&mut self, leaf_digest: &LeafDigest<C>, index: usize) {
+		self.entry(leaf_digest.clone()).or_insert(index);
+	}
+
+	#[inline]
+	fn remove(&mut self, leaf_digest: &LeafDigest<C>, index: usize) {
+		if self.get(leaf_digest) == Some(&index) {
+			self.remove(leaf_digest);
+		}
+	}
+}
 
 /// Full Merkle Tree Backing Structure
 #[cfg_attr(
@@ -40,9 +171,15 @@ pub type FullMerkleTree<C, M = BTreeMap<C>> = MerkleTree<C, Full<C, M>>;
 			deserialize = r"
                 LeafDigest<C>: Deserialize<'de>,
                 InnerDigest<C>: Deserialize<'de>,
-                M: Deserialize<'de>
+                M: Deserialize<'de>,
+                I: Deserialize<'de>
             ",
-			serialize = "LeafDigest<C>: Serialize, InnerDigest<C>: Serialize, M: Serialize"
+			serialize = r"
+                LeafDigest<C>: Serialize,
+                InnerDigest<C>: Serialize,
+                M: Serialize,
+                I: Serialize
+            "
 		),
 		crate = "manta_util::serde",
 		deny_unknown_fields
@@ -50,35 +187,55 @@ pub type FullMerkleTree<C, M = BTreeMap<C>> = MerkleTree<C, Full<C, M>>;
 )]
 #[derive(derivative::Derivative)]
 #[derivative(
-	Clone(bound = "LeafDigest<C>: Clone, InnerDigest<C>: Clone, M: Clone"),
-	Debug(bound = "LeafDigest<C>: Debug, InnerDigest<C>: Debug, M: Debug"),
-	Default(bound = "LeafDigest<C>: Default, InnerDigest<C>: Default, M: Default"),
-	Eq(bound = "LeafDigest<C>: Eq, InnerDigest<C>: Eq, M: Eq"),
-	Hash(bound = "LeafDigest<C>: Hash, InnerDigest<C>: Hash, M: Hash"),
-	PartialEq(bound = "LeafDigest<C>: PartialEq, InnerDigest<C>: PartialEq, M: PartialEq")
+	Clone(bound = "LeafDigest<C>: Clone, InnerDigest<C>: Clone, M: Clone, I: Clone"),
+	Debug(bound = "LeafDigest<C>: Debug, InnerDigest<C>: Debug, M: Debug, I: Debug"),
+	Default(bound = "LeafDigest<C>: Default, InnerDigest<C>: Default, M: Default, I: Default"),
+	Eq(bound = "LeafDigest<C>: Eq, InnerDigest<C>: Eq, M: Eq, I: Eq"),
+	Hash(bound = "LeafDigest<C>: Hash, InnerDigest<C>: Hash, M: Hash, I: Hash"),
+	PartialEq(
+		bound = "LeafDigest<C>: PartialEq, InnerDigest<C>: PartialEq, M: PartialEq, I: PartialEq"
+	)
 )]
-pub struct Full<C, M = BTreeMap<C>>
+pub struct Full<C, M = BTreeMap<C>, I = NoIndex>
 where
 	C: Configuration + ?Sized,
 	M: InnerMap<C>,
+	I: LeafIndex<C>,
 {
 	/// Leaf Digests
 	leaf_digests: Vec<LeafDigest<C>>,
 
 	/// Inner Digests
 	inner_digests: InnerTree<C, M>,
+
+	/// Leaf Digest Index
+	leaf_index: I,
 }
 
-impl<C, M> Full<C, M>
+impl<C, M, I> Full<C, M, I>
 where
 	C: Configuration + ?Sized,
 	M: InnerMap<C>,
+	I: LeafIndex<C>,
 {
 	/// Builds a new [`Full`] without checking that `leaf_digests` and `inner_digests` form a
-	/// consistent merkle tree.
+	/// consistent merkle tree. The leaf index is rebuilt from `leaf_digests`.
+	#[inline]
+	pub fn new_unchecked(leaf_digests: Vec<LeafDigest<C>>, inner_digests: InnerTree<C, M>) -> Self
+	where
+		I: Default,
+	{
+		let mut leaf_index = I::default();
+		for (index, leaf_digest) in leaf_digests.iter().enumerate() {
+			leaf_index.insert(leaf_digest, index);
+		}
+		Self { leaf_digests, inner_digests, leaf_index }
+	}
+
+	/// Returns the leaf digest index of `self`.
 	#[inline]
-	pub fn new_unchecked(leaf_digests: Vec<LeafDigest<C>>, inner_digests: InnerTree<C, M>) -> Self {
-		Self { leaf_digests, inner_digests }
+	pub fn leaf_index(&self) -> &I {
+		&self.leaf_index
 	}
 
 	/// Returns the leaf digests currently stored in the merkle tree.
@@ -105,6 +262,13 @@ where
 		self.len() == 0
 	}
 
+	/// Returns a mutable reference to the inner digests of `self`.
+	#[cfg(feature = "std")]
+	#[inline]
+	pub(super) fn inner_digests_mut(&mut self) -> &mut InnerTree<C, M> {
+		&mut self.inner_digests
+	}
+
 	/// Returns a reference to the root inner digest.
 	#[inline]
 	pub fn root(&self) -> &InnerDigest<C> {
@@ -145,16 +309,19 @@ where
 				self.get_leaf_sibling(leaf_index).unwrap_or(&Default::default()),
 			),
 		);
+		self.leaf_index.insert(&leaf_digest, leaf_index.0);
 		self.leaf_digests.push(leaf_digest);
 	}
 }
 
-impl<C, M> Tree<C> for Full<C, M>
+impl<C, M, I> Tree<C> for Full<C, M, I>
 where
 	C: Configuration + ?Sized,
 	M: InnerMap<C> + Default,
-	LeafDigest<C>: Clone + Default,
-	InnerDigest<C>: Clone + Default + PartialEq,
+	I: LeafIndex<C> + Default,
+	Parameters<C>: MaybeSendSync,
+	LeafDigest<C>: Clone + Default + MaybeSendSync,
+	InnerDigest<C>: Clone + Default + PartialEq + MaybeSendSync,
 {
 	#[inline]
 	fn new(parameters: &Parameters<C>) -> Self {
@@ -203,14 +370,47 @@ where
 		self.push_leaf_digest(parameters, Node(len), leaf_digest()?);
 		Some(true)
 	}
+
+	#[inline]
+	fn extend_digests<L>(
+		&mut self,
+		parameters: &Parameters<C>,
+		leaf_digests: L,
+	) -> Result<(), L::IntoIter>
+	where
+		L: IntoIterator<Item = LeafDigest<C>>,
+	{
+		let leaf_digests = leaf_digests.into_iter();
+		if !matches!(leaf_digests.size_hint().1, Some(max) if max <= capacity::<C, _>() - self.len())
+		{
+			return Err(leaf_digests)
+		}
+		let start = self.len();
+		self.leaf_digests.extend(leaf_digests);
+		assert!(
+			self.len() <= capacity::<C, _>(),
+			"Leaf digest iterators are not allowed to yield more items than their size hint."
+		);
+		for (index, leaf_digest) in self.leaf_digests.iter().enumerate().skip(start) {
+			self.leaf_index.insert(leaf_digest, index);
+		}
+		if self.len() > start {
+			let leaf_index = Node(start).as_left();
+			let bases = parameters.join_leaf_pairs(&self.leaf_digests[leaf_index.0..]);
+			self.inner_digests.batch_insert(parameters, leaf_index, bases);
+		}
+		Ok(())
+	}
 }
 
-impl<C, M> WithProofs<C> for Full<C, M>
+impl<C, M, I> WithProofs<C> for Full<C, M, I>
 where
 	C: Configuration + ?Sized,
 	M: Default + InnerMap<C>,
-	LeafDigest<C>: Clone + Default + PartialEq,
-	InnerDigest<C>: Clone + Default + PartialEq,
+	I: Default + LeafIndex<C>,
+	Parameters<C>: MaybeSendSync,
+	LeafDigest<C>: Clone + Default + PartialEq + MaybeSendSync,
+	InnerDigest<C>: Clone + Default + PartialEq + MaybeSendSync,
 {
 	#[inline]
 	fn leaf_digest(&self, index: usize) -> Option<&LeafDigest<C>> {
@@ -219,7 +419,7 @@ where
 
 	#[inline]
 	fn position(&self, leaf_digest: &LeafDigest<C>) -> Option<usize> {
-		self.leaf_digests.iter().position(move |d| d == leaf_digest)
+		self.leaf_index.position(&self.leaf_digests, leaf_digest)
 	}
 
 	#[inline]
@@ -251,8 +451,42 @@ where
 	#[inline]
 	fn remove_path(&mut self, index: usize) -> bool {
 		// NOTE: This method cannot be implemented, since this violates the semantics of this tree,
-		//       which is supposed to keep all of its nodes forever.
+		//       which is supposed to keep all of its nodes forever. Since no leaf is removed, the
+		//       leaf index stays consistent.
 		let _ = index;
 		false
 	}
 }
+
+impl<C, M, I> WithCheckpoints<C> for Full<C, M, I>
+where
+	C: Configuration + ?Sized,
+	M: InnerMap<C>,
+	I: LeafIndex<C>,
+	LeafDigest<C>: Default,
+{
+	type Checkpoint = usize;
+
+	#[inline]
+	fn checkpoint(&self) -> Self::Checkpoint {
+		self.len()
+	}
+
+	#[inline]
+	fn rollback_to(&mut self, parameters: &Parameters<C>, checkpoint: &Self::Checkpoint) -> bool {
+		let length = *checkpoint;
+		if length > self.len() {
+			return false
+		}
+		for index in length..self.len() {
+			self.inner_digests.remove_path_for_leaf(Node(index));
+			self.leaf_index.remove(&self.leaf_digests[index], index);
+		}
+		self.leaf_digests.truncate(length);
+		match self.leaf_digests.pop() {
+			Some(leaf_digest) => self.push_leaf_digest(parameters, Node(length - 1), leaf_digest),
+			_ => self.inner_digests.remove_root(),
+		}
+		true
+	}
+}
@@ -20,7 +20,8 @@ use super::{
 	path::{CurrentInnerPath, InnerPath},
 	path_length, Configuration, InnerDigest, Node, Parameters, Parity,
 };
-use alloc::collections::btree_map;
+use crate::util::MaybeSendSync;
+use alloc::{collections::btree_map, vec::Vec};
 use core::{fmt::Debug, hash::Hash, iter::FusedIterator, marker::PhantomData, ops::Index};
 
 #[cfg(feature = "serde")]
@@ -206,6 +207,9 @@ where
 	/// Sets the inner digest at `index` to `inner_digest`.
 	fn set(&mut self, index: usize, inner_digest: InnerDigest<C>);
 
+	/// Removes the inner digest at `index`, if it is stored in `self`.
+	fn remove(&mut self, index: usize);
+
 	/// Sets the inner digest at `index` to `inner_digest` and returns a reference to the
 	/// newly stored value.
 	#[inline]
@@ -246,6 +250,11 @@ where
 	fn set(&mut self, index: usize, inner_digest: InnerDigest<C>) {
 		(**self).set(index, inner_digest);
 	}
+
+	#[inline]
+	fn remove(&mut self, index: usize) {
+		(**self).remove(index);
+	}
 }
 
 /// B-Tree Map [`InnerTree`] Backend
@@ -264,6 +273,11 @@ where
 	fn set(&mut self, index: usize, inner_digest: InnerDigest<C>) {
 		self.insert(index, inner_digest);
 	}
+
+	#[inline]
+	fn remove(&mut self, index: usize) {
+		self.remove(&index);
+	}
 }
 
 /// Hash Map [`InnerTree`] Backend
@@ -287,6 +301,11 @@ where
 	fn set(&mut self, index: usize, inner_digest: InnerDigest<C>) {
 		self.insert(index, inner_digest);
 	}
+
+	#[inline]
+	fn remove(&mut self, index: usize) {
+		self.remove(&index);
+	}
 }
 
 /// [`InnerTree`] Sentinel Source Tree Backend
@@ -445,6 +464,14 @@ where
 		Self { map, sentinel_source, __: PhantomData }
 	}
 
+	/// Returns a mutable reference to the inner digest map of `self`. Writing to the map directly
+	/// can leave `self` inconsistent, so this is only used to flush the internal state of the map.
+	#[cfg(feature = "std")]
+	#[inline]
+	pub(super) fn map_mut(&mut self) -> &mut M {
+		&mut self.map
+	}
+
 	/// Tries to get the inner digest at `node`, returning `None` if the inner digest is missing.
 	#[inline]
 	pub fn get(&self, node: InnerNode) -> Option<&InnerDigest<C>> {
@@ -532,6 +559,71 @@ where
 		self.set_root(root);
 	}
 
+	/// Inserts the `bases` inner digests corresponding to the consecutive leaf pairs starting at
+	/// the pair which contains `leaf_index`, computing every inner digest that they change exactly
+	/// once.
+	///
+	/// # Panics
+	///
+	/// This method panics if `bases` is empty.
+	#[inline]
+	pub fn batch_insert(
+		&mut self,
+		parameters: &Parameters<C>,
+		leaf_index: Node,
+		bases: Vec<InnerDigest<C>>,
+	) where
+		Parameters<C>: MaybeSendSync,
+		InnerDigest<C>: Clone + Default + MaybeSendSync,
+	{
+		assert!(!bases.is_empty(), "Batch insertions are not allowed to be empty.");
+		let mut digests = bases;
+		let mut node = match InnerNode::from_leaf::<C>(leaf_index) {
+			Some(node) => node,
+			_ => {
+				self.set_root(digests.swap_remove(0));
+				return
+			},
+		};
+		loop {
+			for (i, digest) in digests.iter().enumerate() {
+				self.map.set(node.map_index() + i, digest.clone());
+			}
+			if node.is_right() {
+				node = node.sibling();
+				digests.insert(0, self.get_or_sentinel(node).clone());
+			}
+			digests = parameters.join_pairs(&digests);
+			match node.parent() {
+				Some(parent) => node = parent,
+				_ => {
+					self.set_root(digests.swap_remove(0));
+					return
+				},
+			}
+		}
+	}
+
+	/// Removes the inner digest at `node`, resetting it to its sentinel value.
+	#[inline]
+	pub fn remove(&mut self, node: InnerNode) {
+		self.map.remove(node.map_index());
+	}
+
+	/// Removes the inner digests on the path of the leaf at `leaf_index`, excluding the root.
+	#[inline]
+	pub fn remove_path_for_leaf(&mut self, leaf_index: Node) {
+		for node in InnerNodeIter::from_leaf::<C>(leaf_index) {
+			self.remove(node);
+		}
+	}
+
+	/// Removes the current root, resetting it to its sentinel value.
+	#[inline]
+	pub fn remove_root(&mut self) {
+		self.map.remove(0);
+	}
+
 	/// Computes the inner path starting from `node`.
 	#[inline]
 	pub fn path_iter(&self, node: InnerNode) -> InnerTreePathIter<C, M, S> {
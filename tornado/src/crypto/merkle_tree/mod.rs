@@ -5,10 +5,16 @@ mod tree;
 
 pub mod full;
 pub mod inner_tree;
+pub mod partial;
 pub mod path;
 pub mod single_path;
+pub mod sparse;
 pub mod test;
 
+#[cfg(feature = "std")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "std")))]
+pub mod fs;
+
 pub use node::*;
 pub use path::prelude::*;
 pub use tree::*;
@@ -0,0 +1,302 @@
+//! Partial Merkle Tree Storage
+//!
+//! [`Partial`] ingests every leaf of the tree so that it always knows the current root, but it only
+//! keeps the digests needed to build membership proofs for the leaves which were marked with
+//! [`mark`](Partial::mark). Everything else is pruned as soon as the tree moves past it, so a tree
+//! with `n` marked leaves stores `O(n * HEIGHT)` digests.
+
+use super::{
+	capacity,
+	inner_tree::{BTreeMap, InnerMap, InnerNodeIter, InnerTree},
+	Configuration, CurrentPath, InnerDigest, LeafDigest, MerkleTree, Node, Parameters, Path,
+	PathError, Root, Tree, WithProofs,
+};
+use alloc::collections::{btree_map, BTreeSet};
+use core::{fmt::Debug, hash::Hash};
+
+/// Partial Merkle Tree Type
+pub type PartialMerkleTree<C, M = BTreeMap<C>> = MerkleTree<C, Partial<C, M>>;
+
+/// Partial Merkle Tree Backing Structure
+#[derive(derivative::Derivative)]
+#[derivative(
+	Clone(bound = "LeafDigest<C>: Clone, InnerDigest<C>: Clone, M: Clone"),
+	Debug(bound = "LeafDigest<C>: Debug, InnerDigest<C>: Debug, M: Debug"),
+	Default(bound = "LeafDigest<C>: Default, InnerDigest<C>: Default, M: Default"),
+	Eq(bound = "LeafDigest<C>: Eq, InnerDigest<C>: Eq, M: Eq"),
+	Hash(bound = "LeafDigest<C>: Hash, InnerDigest<C>: Hash, M: Hash"),
+	PartialEq(bound = "LeafDigest<C>: PartialEq, InnerDigest<C>: PartialEq, M: PartialEq")
+)]
+pub struct Partial<C, M = BTreeMap<C>>
+where
+	C: Configuration + ?Sized,
+	M: InnerMap<C>,
+{
+	/// Number of Leaves
+	len: usize,
+
+	/// Stored Leaf Digests
+	leaf_digests: btree_map::BTreeMap<usize, LeafDigest<C>>,
+
+	/// Marked Leaf Indices
+	marked: BTreeSet<usize>,
+
+	/// Inner Digests
+	inner_digests: InnerTree<C, M>,
+}
+
+impl<C, M> Partial<C, M>
+where
+	C: Configuration + ?Sized,
+	M: InnerMap<C>,
+{
+	/// Returns the number of leaves in this tree.
+	#[inline]
+	pub fn len(&self) -> usize {
+		self.len
+	}
+
+	/// Returns `true` if this tree is empty.
+	#[inline]
+	pub fn is_empty(&self) -> bool {
+		self.len == 0
+	}
+
+	/// Returns a reference to the root inner digest.
+	#[inline]
+	pub fn root(&self) -> &InnerDigest<C> {
+		self.inner_digests.root()
+	}
+
+	/// Returns the indices of the marked leaves in increasing order.
+	#[inline]
+	pub fn marked(&self) -> impl '_ + Iterator<Item = usize> {
+		self.marked.iter().copied()
+	}
+
+	/// Returns `true` if the leaf at `index` is marked.
+	#[inline]
+	pub fn is_marked(&self, index: usize) -> bool {
+		self.marked.contains(&index)
+	}
+
+	/// Returns the number of leaf digests stored in this tree.
+	#[inline]
+	pub fn stored_leaf_count(&self) -> usize {
+		self.leaf_digests.len()
+	}
+
+	/// Returns `true` if the digests under the subtree of height `shift` containing the leaf at
+	/// `index` are still needed, either by a marked leaf or by the current leaf.
+	#[inline]
+	fn is_needed(&self, index: usize, shift: usize) -> bool {
+		let start = (index >> shift) << shift;
+		let end = start + (1 << shift);
+		matches!(self.len.checked_sub(1), Some(current) if (start..end).contains(&current))
+			|| self.marked.range(start..end).next().is_some()
+	}
+
+	/// Returns `true` if the path of the leaf at `index` can be computed from the stored digests.
+	#[inline]
+	fn is_provable(&self, index: usize) -> bool {
+		index < self.len && self.is_needed(index, 1)
+	}
+
+	/// Removes every digest on the path of the leaf at `index` which is no longer needed by any
+	/// marked leaf or by the current leaf.
+	#[inline]
+	fn prune(&mut self, index: usize) {
+		if self.is_needed(index, 1) {
+			return
+		}
+		let leaf_index = Node(index);
+		self.leaf_digests.remove(&leaf_index.0);
+		self.leaf_digests.remove(&leaf_index.sibling().0);
+		for (level, node) in InnerNodeIter::from_leaf::<C>(leaf_index).enumerate() {
+			if self.is_needed(index, level + 2) {
+				return
+			}
+			self.inner_digests.remove(node);
+			self.inner_digests.remove(node.sibling());
+		}
+	}
+
+	/// Marks the leaf at `index` so that its path is kept as the tree grows, returning `false` if
+	/// the leaf was already pruned.
+	///
+	/// # Implementation Note
+	///
+	/// A leaf can only be marked while its digest is still stored, that is, while it is the
+	/// current leaf or the sibling of the current leaf or of another marked leaf. To keep a leaf
+	/// provable, mark it when it is inserted, for example with
+	/// [`push_provable`](WithProofs::push_provable).
+	#[inline]
+	pub fn mark(&mut self, index: usize) -> bool {
+		if !self.is_provable(index) {
+			return false
+		}
+		self.marked.insert(index);
+		true
+	}
+
+	/// Unmarks the leaf at `index`, pruning every digest which was only kept for its path and
+	/// returning `false` if the leaf was not marked.
+	#[inline]
+	pub fn unmark(&mut self, index: usize) -> bool {
+		if !self.marked.remove(&index) {
+			return false
+		}
+		self.prune(index);
+		true
+	}
+
+	/// Returns an owned sibling leaf node to `index`.
+	#[inline]
+	fn get_owned_leaf_sibling(&self, index: Node) -> LeafDigest<C>
+	where
+		LeafDigest<C>: Clone + Default,
+	{
+		self.leaf_digests.get(&index.sibling().0).cloned().unwrap_or_default()
+	}
+
+	/// Appends a `leaf_digest` with index given by `leaf_index` into the tree and prunes the path
+	/// of the previous leaf.
+	#[inline]
+	fn push_leaf_digest(
+		&mut self,
+		parameters: &Parameters<C>,
+		leaf_index: Node,
+		leaf_digest: LeafDigest<C>,
+	) where
+		LeafDigest<C>: Default,
+	{
+		self.inner_digests.insert(
+			parameters,
+			leaf_index,
+			leaf_index.join_leaves(
+				parameters,
+				&leaf_digest,
+				self.leaf_digests.get(&leaf_index.sibling().0).unwrap_or(&Default::default()),
+			),
+		);
+		self.leaf_digests.insert(leaf_index.0, leaf_digest);
+		self.len += 1;
+		if let Some(previous) = leaf_index.0.checked_sub(1) {
+			self.prune(previous);
+		}
+	}
+}
+
+impl<C, M> Tree<C> for Partial<C, M>
+where
+	C: Configuration + ?Sized,
+	M: InnerMap<C> + Default,
+	LeafDigest<C>: Clone + Default,
+	InnerDigest<C>: Clone + Default + PartialEq,
+{
+	#[inline]
+	fn new(parameters: &Parameters<C>) -> Self {
+		let _ = parameters;
+		Default::default()
+	}
+
+	#[inline]
+	fn len(&self) -> usize {
+		self.len()
+	}
+
+	#[inline]
+	fn current_leaf(&self) -> Option<&LeafDigest<C>> {
+		self.leaf_digests.get(&self.len.checked_sub(1)?)
+	}
+
+	#[inline]
+	fn root(&self) -> &Root<C> {
+		self.root()
+	}
+
+	#[inline]
+	fn current_path(&self, parameters: &Parameters<C>) -> CurrentPath<C> {
+		let _ = parameters;
+		if self.len == 0 {
+			return Default::default()
+		}
+		let leaf_index = Node(self.len - 1);
+		CurrentPath::from_inner(
+			self.get_owned_leaf_sibling(leaf_index),
+			self.inner_digests.current_path_unchecked(leaf_index),
+		)
+	}
+
+	#[inline]
+	fn maybe_push_digest<F>(&mut self, parameters: &Parameters<C>, leaf_digest: F) -> Option<bool>
+	where
+		F: FnOnce() -> Option<LeafDigest<C>>,
+	{
+		let len = self.len();
+		if len >= capacity::<C, _>() {
+			return Some(false)
+		}
+		self.push_leaf_digest(parameters, Node(len), leaf_digest()?);
+		Some(true)
+	}
+}
+
+impl<C, M> WithProofs<C> for Partial<C, M>
+where
+	C: Configuration + ?Sized,
+	M: Default + InnerMap<C>,
+	LeafDigest<C>: Clone + Default + PartialEq,
+	InnerDigest<C>: Clone + Default + PartialEq,
+{
+	#[inline]
+	fn leaf_digest(&self, index: usize) -> Option<&LeafDigest<C>> {
+		self.leaf_digests.get(&index)
+	}
+
+	#[inline]
+	fn position(&self, leaf_digest: &LeafDigest<C>) -> Option<usize> {
+		self.marked
+			.iter()
+			.find(move |i| self.leaf_digests.get(*i) == Some(leaf_digest))
+			.copied()
+	}
+
+	#[inline]
+	fn maybe_push_provable_digest<F>(
+		&mut self,
+		parameters: &Parameters<C>,
+		leaf_digest: F,
+	) -> Option<bool>
+	where
+		F: FnOnce() -> Option<LeafDigest<C>>,
+	{
+		let pushed = self.maybe_push_digest(parameters, leaf_digest)?;
+		if pushed {
+			self.marked.insert(self.len - 1);
+		}
+		Some(pushed)
+	}
+
+	#[inline]
+	fn path(&self, parameters: &Parameters<C>, index: usize) -> Result<Path<C>, PathError> {
+		let _ = parameters;
+		let length = self.len();
+		if index >= length {
+			return Err(PathError::IndexTooLarge { length })
+		}
+		if !self.is_provable(index) {
+			return Err(PathError::MissingPath)
+		}
+		let leaf_index = Node(index);
+		Ok(Path::from_inner(
+			self.get_owned_leaf_sibling(leaf_index),
+			self.inner_digests.path(leaf_index),
+		))
+	}
+
+	#[inline]
+	fn remove_path(&mut self, index: usize) -> bool {
+		self.unmark(index)
+	}
+}
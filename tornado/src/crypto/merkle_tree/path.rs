@@ -39,7 +39,7 @@ use manta_util::serde::{Deserialize, Serialize};
 
 pub(super) mod prelude {
 	#[doc(inline)]
-	pub use super::{CurrentPath, Path};
+	pub use super::{CurrentPath, MultiPath, Path};
 }
 
 /// Merkle Tree Inner Path
@@ -901,6 +901,235 @@ where
 	}
 }
 
+/// Calls `missing` with the position of every node in `nodes` whose sibling is not in `nodes`,
+/// and returns the parents of `nodes` with the position of their left-most child in `nodes`.
+///
+/// The `nodes` must be given in strictly increasing order.
+#[inline]
+fn parents<F>(nodes: &[Node], mut missing: F) -> Vec<(Node, usize)>
+where
+	F: FnMut(usize),
+{
+	let mut parents = Vec::with_capacity(nodes.len());
+	let mut position = 0;
+	while let Some(node) = nodes.get(position) {
+		parents.push((node.parent(), position));
+		if node.is_left() && nodes.get(position + 1) == Some(&node.sibling()) {
+			position += 2;
+		} else {
+			missing(position);
+			position += 1;
+		}
+	}
+	parents
+}
+
+/// Joins every node in `level` with its sibling, taking the sibling from `siblings` whenever it
+/// is not also in `level`, and returns the parents of `level` or `None` if `siblings` ran out.
+///
+/// The nodes in `level` must be given in strictly increasing order.
+#[inline]
+fn join_level<T, U, I, F>(
+	level: Vec<(Node, T)>,
+	siblings: &mut I,
+	mut join: F,
+) -> Option<Vec<(Node, U)>>
+where
+	I: Iterator<Item = T>,
+	F: FnMut(&T, &T) -> U,
+{
+	let mut parents = Vec::with_capacity(level.len());
+	let mut level = level.into_iter().peekable();
+	while let Some((node, digest)) = level.next() {
+		let (lhs, rhs) = match level.next_if(|(next, _)| node.is_left() && *next == node.sibling())
+		{
+			Some((_, sibling)) => (digest, sibling),
+			_ => node.parity().order(digest, siblings.next()?),
+		};
+		parents.push((node.parent(), join(&lhs, &rhs)));
+	}
+	Some(parents)
+}
+
+/// Returns `true` if `leaf_indices` is non-empty and strictly increasing.
+#[inline]
+fn is_strictly_increasing(leaf_indices: &[Node]) -> bool {
+	!leaf_indices.is_empty() && leaf_indices.windows(2).all(|w| w[0].0 < w[1].0)
+}
+
+/// Merkle Tree Multi-Path
+///
+/// A [`MultiPath`] proves the membership of a set of leaves at once. Siblings which can be
+/// computed from the other leaves in the set are not stored, so leaves which are close to each
+/// other in the tree share most of their path.
+#[cfg_attr(
+	feature = "serde",
+	derive(Deserialize, Serialize),
+	serde(
+		bound(
+			deserialize = "LeafDigest<C>: Deserialize<'de>, InnerDigest<C>: Deserialize<'de>",
+			serialize = "LeafDigest<C>: Serialize, InnerDigest<C>: Serialize"
+		),
+		crate = "manta_util::serde",
+		deny_unknown_fields
+	)
+)]
+#[derive(derivative::Derivative)]
+#[derivative(
+	Clone(bound = "LeafDigest<C>: Clone, InnerDigest<C>: Clone"),
+	Debug(bound = "LeafDigest<C>: Debug, InnerDigest<C>: Debug"),
+	Default(bound = ""),
+	Eq(bound = "LeafDigest<C>: Eq, InnerDigest<C>: Eq"),
+	Hash(bound = "LeafDigest<C>: Hash, InnerDigest<C>: Hash"),
+	PartialEq(bound = "LeafDigest<C>: PartialEq, InnerDigest<C>: PartialEq")
+)]
+pub struct MultiPath<C>
+where
+	C: Configuration + ?Sized,
+{
+	/// Leaf Indices
+	///
+	/// Leaf indices are stored in strictly increasing order.
+	pub leaf_indices: Vec<Node>,
+
+	/// Leaf Sibling Digests
+	///
+	/// Sibling digests are only stored for the leaves whose sibling is not in the set, in the
+	/// order of their leaf indices.
+	pub leaf_siblings: Vec<LeafDigest<C>>,
+
+	/// Inner Sibling Digests
+	///
+	/// Sibling digests are only stored for the inner nodes whose sibling cannot be computed from
+	/// the set of leaves. They are stored from leaf to root, and from left to right within each
+	/// level of the tree.
+	pub inner_siblings: Vec<InnerDigest<C>>,
+}
+
+impl<C> MultiPath<C>
+where
+	C: Configuration + ?Sized,
+{
+	/// Builds a new [`MultiPath`] from the `paths` of a set of leaves, only keeping the siblings
+	/// which cannot be computed from the other leaves in the set. Duplicate paths for the same
+	/// leaf index are ignored.
+	#[inline]
+	pub fn from_paths<I>(paths: I) -> Self
+	where
+		I: IntoIterator<Item = Path<C>>,
+		LeafDigest<C>: Clone,
+		InnerDigest<C>: Clone,
+	{
+		let mut paths = paths.into_iter().collect::<Vec<_>>();
+		paths.sort_by_key(|path| path.leaf_index().0);
+		paths.dedup_by_key(|path| path.leaf_index().0);
+		let leaf_indices = paths.iter().map(Path::leaf_index).collect::<Vec<_>>();
+		let mut leaf_siblings = Vec::new();
+		let mut level = parents(&leaf_indices, |position| {
+			leaf_siblings.push(paths[position].sibling_digest.clone())
+		});
+		let mut inner_siblings = Vec::new();
+		for depth in 0..path_length::<C, _>() {
+			let nodes = level.iter().map(|(node, _)| *node).collect::<Vec<_>>();
+			let representatives =
+				level.into_iter().map(|(_, position)| position).collect::<Vec<_>>();
+			level = parents(&nodes, |position| {
+				inner_siblings.push(paths[representatives[position]].inner_path.path[depth].clone())
+			})
+			.into_iter()
+			.map(|(node, position)| (node, representatives[position]))
+			.collect();
+		}
+		Self { leaf_indices, leaf_siblings, inner_siblings }
+	}
+
+	/// Returns the number of leaf siblings and inner siblings stored in a [`MultiPath`] for the
+	/// leaves at `leaf_indices`, which must be given in strictly increasing order.
+	#[inline]
+	pub fn sibling_counts(leaf_indices: &[Node]) -> (usize, usize) {
+		let mut leaf_count = 0;
+		let mut nodes = parents(leaf_indices, |_| leaf_count += 1)
+			.into_iter()
+			.map(|(node, _)| node)
+			.collect::<Vec<_>>();
+		let mut inner_count = 0;
+		for _ in 0..path_length::<C, _>() {
+			nodes = parents(&nodes, |_| inner_count += 1)
+				.into_iter()
+				.map(|(node, _)| node)
+				.collect();
+		}
+		(leaf_count, inner_count)
+	}
+
+	/// Computes the root of the merkle tree relative to `leaf_digests` using `parameters`, where
+	/// `leaf_digests` are given in the same order as [`leaf_indices`](Self::leaf_indices). This
+	/// method returns `None` if `self` is malformed or does not match `leaf_digests`.
+	#[inline]
+	pub fn root(
+		&self,
+		parameters: &Parameters<C>,
+		leaf_digests: &[LeafDigest<C>],
+	) -> Option<Root<C>>
+	where
+		InnerDigest<C>: Clone,
+	{
+		if leaf_digests.len() != self.leaf_indices.len()
+			|| !is_strictly_increasing(&self.leaf_indices)
+		{
+			return None
+		}
+		let mut leaf_siblings = self.leaf_siblings.iter();
+		let level = join_level(
+			self.leaf_indices.iter().copied().zip(leaf_digests).collect(),
+			&mut leaf_siblings,
+			|lhs, rhs| parameters.join_leaves(lhs, rhs),
+		)?;
+		let mut inner_siblings = self.inner_siblings.iter().cloned();
+		let mut level = (0..path_length::<C, _>()).try_fold(level, |level, _| {
+			join_level(level, &mut inner_siblings, |lhs, rhs| parameters.join(lhs, rhs))
+		})?;
+		match (level.pop(), level.is_empty()) {
+			(Some((Node(0), root)), true)
+				if leaf_siblings.next().is_none() && inner_siblings.next().is_none() =>
+			{
+				Some(root)
+			},
+			_ => None,
+		}
+	}
+
+	/// Returns `true` if `self` is a witness to the fact that every digest in `leaf_digests` is
+	/// stored in a merkle tree with the given `root`.
+	#[inline]
+	pub fn verify_digests(
+		&self,
+		parameters: &Parameters<C>,
+		root: &Root<C>,
+		leaf_digests: &[LeafDigest<C>],
+	) -> bool
+	where
+		InnerDigest<C>: Clone + PartialEq,
+	{
+		matches!(self.root(parameters, leaf_digests), Some(computed) if &computed == root)
+	}
+
+	/// Returns `true` if `self` is a witness to the fact that every leaf in `leaves` is stored in
+	/// a merkle tree with the given `root`.
+	#[inline]
+	pub fn verify(&self, parameters: &Parameters<C>, root: &Root<C>, leaves: &[Leaf<C>]) -> bool
+	where
+		Leaf<C>: Sized,
+		InnerDigest<C>: Clone + PartialEq,
+	{
+		self.verify_digests(
+			parameters,
+			root,
+			&leaves.iter().map(|leaf| parameters.digest(leaf)).collect::<Vec<_>>(),
+		)
+	}
+}
+
 /// Constraint System Gadgets
 pub mod constraint {
 	use super::*;
@@ -1040,6 +1269,19 @@ pub mod constraint {
 	}
 
 	/// Path Variable
+	#[derive(Variable)]
+	#[eclair(
+		compiler = "COM",
+		type = "Path<C::Type>",
+		mode = "secret",
+		bound = "
+			Bool<COM>: Variable<Secret, COM, Type = bool>,
+			C: Constant<COM>,
+			C::Type: Configuration,
+			InnerDigest<C, COM>: Variable<Secret, COM, Type = InnerDigest<C::Type>>,
+			LeafDigest<C, COM>: Variable<Secret, COM, Type = LeafDigest<C::Type>>,
+		"
+	)]
 	pub struct PathVar<C, COM>
 	where
 		C: Configuration<COM> + ?Sized,
@@ -1104,30 +1346,147 @@ pub mod constraint {
 		}
 	}
 
-	impl<C, COM> Variable<Secret, COM> for PathVar<C, COM>
+	/// Multi-Path Variable
+	///
+	/// The leaf indices of a [`MultiPathVar`] decide which nodes are joined at every level of the
+	/// tree, so they are part of the shape of the circuit and are not allocated. Only the sibling
+	/// digests are secret.
+	pub struct MultiPathVar<C, COM>
+	where
+		C: Configuration<COM> + ?Sized,
+	{
+		/// Leaf Indices
+		pub leaf_indices: Vec<Node>,
+
+		/// Leaf Sibling Digests
+		pub leaf_siblings: Vec<LeafDigest<C, COM>>,
+
+		/// Inner Sibling Digests
+		pub inner_siblings: Vec<InnerDigest<C, COM>>,
+	}
+
+	impl<C, COM> MultiPathVar<C, COM>
+	where
+		C: Configuration<COM> + ?Sized,
+		InnerDigest<C, COM>: Clone,
+	{
+		/// Computes the root of the merkle tree relative to `leaf_digests` using `parameters`,
+		/// where `leaf_digests` are given in the same order as
+		/// [`leaf_indices`](Self::leaf_indices).
+		///
+		/// # Panics
+		///
+		/// This method panics if `self` is malformed or does not match `leaf_digests`.
+		#[inline]
+		pub fn root(
+			&self,
+			parameters: &Parameters<C, COM>,
+			leaf_digests: &[LeafDigest<C, COM>],
+			compiler: &mut COM,
+		) -> Root<C, COM> {
+			assert!(
+				leaf_digests.len() == self.leaf_indices.len()
+					&& is_strictly_increasing(&self.leaf_indices),
+				"Multi-path leaf indices are not allowed to be malformed."
+			);
+			let mut leaf_siblings = self.leaf_siblings.iter();
+			let level = join_level(
+				self.leaf_indices.iter().copied().zip(leaf_digests).collect(),
+				&mut leaf_siblings,
+				|lhs, rhs| parameters.join_leaves_with(lhs, rhs, compiler),
+			)
+			.expect("Multi-path leaf siblings are not allowed to be missing.");
+			let mut inner_siblings = self.inner_siblings.iter().cloned();
+			let mut level = (0..path_length::<C, _>())
+				.try_fold(level, |level, _| {
+					join_level(level, &mut inner_siblings, |lhs, rhs| {
+						parameters.join_with(lhs, rhs, compiler)
+					})
+				})
+				.expect("Multi-path inner siblings are not allowed to be missing.");
+			assert!(
+				level.len() == 1
+					&& level[0].0 == Node(0)
+					&& leaf_siblings.next().is_none()
+					&& inner_siblings.next().is_none(),
+				"Multi-paths are not allowed to be malformed."
+			);
+			level.remove(0).1
+		}
+
+		/// Returns `true` if `self` is a witness to the fact that every digest in `leaf_digests`
+		/// is stored in a merkle tree with the given `root`.
+		#[inline]
+		pub fn verify_digests(
+			&self,
+			parameters: &Parameters<C, COM>,
+			root: &Root<C, COM>,
+			leaf_digests: &[LeafDigest<C, COM>],
+			compiler: &mut COM,
+		) -> Bool<COM>
+		where
+			COM: Has<bool>,
+			Root<C, COM>: PartialEq<Root<C, COM>, COM>,
+		{
+			let computed_root = self.root(parameters, leaf_digests, compiler);
+			root.eq(&computed_root, compiler)
+		}
+
+		/// Returns `true` if `self` is a witness to the fact that every leaf in `leaves` is stored
+		/// in a merkle tree with the given `root`.
+		#[inline]
+		pub fn verify(
+			&self,
+			parameters: &Parameters<C, COM>,
+			root: &Root<C, COM>,
+			leaves: &[Leaf<C, COM>],
+			compiler: &mut COM,
+		) -> Bool<COM>
+		where
+			COM: Has<bool>,
+			Leaf<C, COM>: Sized,
+			Root<C, COM>: PartialEq<Root<C, COM>, COM>,
+		{
+			let leaf_digests = leaves
+				.iter()
+				.map(|leaf| parameters.digest_with(leaf, compiler))
+				.collect::<Vec<_>>();
+			self.verify_digests(parameters, root, &leaf_digests, compiler)
+		}
+	}
+
+	impl<C, COM> MultiPathVar<C, COM>
 	where
-		COM: Has<bool>,
-		Bool<COM>: Variable<Secret, COM, Type = bool>,
 		C: Configuration<COM> + Constant<COM> + ?Sized,
 		C::Type: Configuration,
-		InnerDigest<C, COM>: Variable<Secret, COM, Type = InnerDigest<C::Type>>,
-		LeafDigest<C, COM>: Variable<Secret, COM, Type = LeafDigest<C::Type>>,
 	{
-		type Type = Path<C::Type>;
-
+		/// Allocates a new [`MultiPathVar`] for the known multi-path `this` into `compiler`.
 		#[inline]
-		fn new_known(this: &Self::Type, compiler: &mut COM) -> Self {
+		pub fn new_known(this: &MultiPath<C::Type>, compiler: &mut COM) -> Self
+		where
+			InnerDigest<C, COM>: Variable<Secret, COM, Type = InnerDigest<C::Type>>,
+			LeafDigest<C, COM>: Variable<Secret, COM, Type = LeafDigest<C::Type>>,
+		{
 			Self {
-				sibling_digest: this.sibling_digest.as_known(compiler),
-				inner_path: this.inner_path.as_known(compiler),
+				leaf_indices: this.leaf_indices.clone(),
+				leaf_siblings: this.leaf_siblings.iter().map(|d| d.as_known(compiler)).collect(),
+				inner_siblings: this.inner_siblings.iter().map(|d| d.as_known(compiler)).collect(),
 			}
 		}
 
+		/// Allocates a new [`MultiPathVar`] with unknown siblings for the leaves at
+		/// `leaf_indices` into `compiler`.
 		#[inline]
-		fn new_unknown(compiler: &mut COM) -> Self {
+		pub fn new_unknown(leaf_indices: Vec<Node>, compiler: &mut COM) -> Self
+		where
+			InnerDigest<C, COM>: Variable<Secret, COM, Type = InnerDigest<C::Type>>,
+			LeafDigest<C, COM>: Variable<Secret, COM, Type = LeafDigest<C::Type>>,
+		{
+			let (leaf_count, inner_count) = MultiPath::<C::Type>::sibling_counts(&leaf_indices);
 			Self {
-				sibling_digest: compiler.allocate_unknown(),
-				inner_path: compiler.allocate_unknown(),
+				leaf_indices,
+				leaf_siblings: (0..leaf_count).map(|_| compiler.allocate_unknown()).collect(),
+				inner_siblings: (0..inner_count).map(|_| compiler.allocate_unknown()).collect(),
 			}
 		}
 	}
@@ -17,9 +17,13 @@
 //! Single Path Merkle Tree Storage
 
 use super::{
-	capacity, Configuration, CurrentPath, InnerDigest, LeafDigest, MerkleTree, Parameters, Root,
-	Tree,
+	capacity,
+	path::{CurrentInnerPath, InnerPath},
+	path_length, Configuration, CurrentPath, InnerDigest, LeafDigest, MerkleTree, Node, Parameters,
+	Root, Tree, WithCheckpoints,
 };
+use crate::util::MaybeSendSync;
+use alloc::vec::Vec;
 use core::{fmt::Debug, hash::Hash};
 
 /// Tree Length State
@@ -114,8 +118,9 @@ where
 impl<C> Tree<C> for SinglePath<C>
 where
 	C: Configuration + ?Sized,
-	LeafDigest<C>: Clone + Default,
-	InnerDigest<C>: Clone + Default,
+	Parameters<C>: MaybeSendSync,
+	LeafDigest<C>: Clone + Default + MaybeSendSync,
+	InnerDigest<C>: Clone + Default + MaybeSendSync,
 {
 	#[inline]
 	fn new(parameters: &Parameters<C>) -> Self {
@@ -170,6 +175,62 @@ where
 		}
 		Some(true)
 	}
+
+	#[inline]
+	fn extend_digests<L>(
+		&mut self,
+		parameters: &Parameters<C>,
+		leaf_digests: L,
+	) -> Result<(), L::IntoIter>
+	where
+		L: IntoIterator<Item = LeafDigest<C>>,
+	{
+		let leaf_digests = leaf_digests.into_iter();
+		let remaining = capacity::<C, _>() - self.len();
+		if !matches!(leaf_digests.size_hint().1, Some(max) if max <= remaining) {
+			return Err(leaf_digests)
+		}
+		let leaf_digests = leaf_digests.collect::<Vec<_>>();
+		assert!(
+			leaf_digests.len() <= remaining,
+			"Leaf digest iterators are not allowed to yield more items than their size hint."
+		);
+		if !leaf_digests.is_empty() {
+			self.root = raw::extend(
+				parameters,
+				&mut self.leaf_digest,
+				&mut self.current_path,
+				leaf_digests,
+			);
+		}
+		Ok(())
+	}
+}
+
+impl<C> WithCheckpoints<C> for SinglePath<C>
+where
+	C: Configuration + ?Sized,
+	LeafDigest<C>: Clone,
+	InnerDigest<C>: Clone,
+{
+	type Checkpoint = Self;
+
+	#[inline]
+	fn checkpoint(&self) -> Self::Checkpoint {
+		self.clone()
+	}
+
+	#[inline]
+	fn rollback_to(&mut self, parameters: &Parameters<C>, checkpoint: &Self::Checkpoint) -> bool {
+		// NOTE: A single path tree cannot recompute an older path from its current state, so the
+		//       checkpoint keeps a copy of the whole tree instead.
+		let _ = parameters;
+		if checkpoint.len() > self.len() {
+			return false
+		}
+		*self = checkpoint.clone();
+		true
+	}
 }
 
 /// Raw Merkle Tree Interfaces
@@ -219,4 +280,82 @@ pub mod raw {
 			Length::Full => None,
 		}
 	}
+
+	/// Inserts every digest in `leaves` into the tree updating the `leaf_digest` and the
+	/// `current_path`, computing every inner digest that they change exactly once, and returns the
+	/// new root.
+	///
+	/// # Panics
+	///
+	/// This function panics if `leaves` is empty. The caller must also make sure that `leaves`
+	/// fits into the remaining capacity of the tree.
+	#[inline]
+	pub fn extend<C>(
+		parameters: &Parameters<C>,
+		leaf_digest: &mut Option<LeafDigest<C>>,
+		current_path: &mut CurrentPath<C>,
+		mut leaves: Vec<LeafDigest<C>>,
+	) -> Root<C>
+	where
+		C: Configuration + ?Sized,
+		Parameters<C>: MaybeSendSync,
+		LeafDigest<C>: Clone + Default + MaybeSendSync,
+		InnerDigest<C>: Clone + Default + MaybeSendSync,
+	{
+		assert!(!leaves.is_empty(), "Batch insertions are not allowed to be empty.");
+		let start = match leaf_digest {
+			Some(_) => current_path.leaf_index().0 + 1,
+			_ => 0,
+		};
+		let last = start + leaves.len() - 1;
+		let mut first = start;
+		let mut previous_siblings = Vec::new();
+		let mut neighbor = None;
+		if let Some(previous) = leaf_digest.take() {
+			previous_siblings = InnerPath::from(current_path.inner_path.clone()).path;
+			let height = start.trailing_zeros() as usize;
+			if height == 0 {
+				leaves.insert(0, previous);
+				first -= 1;
+			} else if height <= path_length::<C, _>() {
+				// NOTE: The previous leaf is the last leaf of a complete subtree whose root is the
+				//       left neighbor of the new leaves at level `height - 1`, so we recompute it
+				//       from the previous path.
+				neighbor = Some(previous_siblings[..height - 1].iter().fold(
+					Node(start - 1).join_leaves(
+						parameters,
+						&previous,
+						&current_path.sibling_digest,
+					),
+					|acc, sibling| parameters.join(sibling, &acc),
+				));
+			}
+		}
+		let sibling_digest =
+			if last % 2 == 1 { leaves[last - 1 - first].clone() } else { Default::default() };
+		let mut digests = parameters.join_leaf_pairs(&leaves);
+		*leaf_digest = leaves.pop();
+		let mut path = Vec::new();
+		#[allow(clippy::needless_range_loop)] // NOTE: `level` also drives the index shifts.
+		for level in 0..path_length::<C, _>() {
+			first = start >> (level + 1);
+			if first % 2 == 1 {
+				let digest = if (start - 1) >> (level + 1) == first {
+					previous_siblings[level].clone()
+				} else {
+					neighbor.take().expect("The left neighbor is only needed once.")
+				};
+				digests.insert(0, digest);
+				first -= 1;
+			}
+			let index = last >> (level + 1);
+			if index % 2 == 1 {
+				path.push(digests[index - 1 - first].clone());
+			}
+			digests = parameters.join_pairs(&digests);
+		}
+		*current_path =
+			CurrentPath::from_inner(sibling_digest, CurrentInnerPath::new(Node(last), path));
+		digests.swap_remove(0)
+	}
 }
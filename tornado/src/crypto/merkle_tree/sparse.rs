@@ -0,0 +1,608 @@
+//! Sparse Merkle Trees
+//!
+//! A sparse merkle tree has one leaf for every possible key, at the position given by the bits of
+//! the key. The leaf of a key stores the digest of the key once it has been inserted and the
+//! default leaf digest before that, so the same [`SparsePath`] proves membership of inserted keys
+//! and non-membership of every other key. Only the nodes on the paths of inserted keys are stored,
+//! every other node is the root of an empty subtree and is computed once by [`empty_digests`].
+
+use super::{HashConfiguration, InnerDigest, Leaf, LeafDigest, Parameters, Root};
+use alloc::{collections::btree_map, vec::Vec};
+use core::{fmt::Debug, hash::Hash};
+
+#[cfg(feature = "serde")]
+use manta_util::serde::{Deserialize, Serialize};
+
+/// Sparse Merkle Tree Configuration
+///
+/// # Contract
+///
+/// Implementations must map distinct leaves to distinct key bits, and the digest of a leaf is not
+/// allowed to be equal to the default leaf digest, since that value marks the leaves of keys which
+/// were never inserted.
+pub trait Configuration<COM = ()>: HashConfiguration<COM> {
+	/// Key Bit Type
+	type Bit;
+
+	/// Depth of the Tree
+	///
+	/// The depth is the number of key bits, so the tree has `2^DEPTH` leaves and every path has
+	/// `DEPTH` siblings. Trees must always have depth at least `1`.
+	const DEPTH: usize;
+
+	/// Returns the [`DEPTH`](Self::DEPTH) little-endian key bits of `leaf` which choose its
+	/// position in the tree.
+	fn key_bits(leaf: &Leaf<Self, COM>, compiler: &mut COM) -> Vec<Self::Bit>;
+}
+
+/// Returns the key bits of `leaf`.
+///
+/// # Panics
+///
+/// This function panics if the key bits of `leaf` do not have length
+/// [`DEPTH`](Configuration::DEPTH).
+#[inline]
+fn key_bits<C>(leaf: &Leaf<C>) -> Vec<bool>
+where
+	C: Configuration<Bit = bool> + ?Sized,
+{
+	let bits = C::key_bits(leaf, &mut ());
+	assert_eq!(
+		bits.len(),
+		C::DEPTH,
+		"Key bits are not allowed to have a length other than the depth."
+	);
+	bits
+}
+
+/// Orders `digest` and its `sibling` by the key `bit` of `digest`.
+#[inline]
+fn order<T>(bit: bool, digest: T, sibling: T) -> (T, T) {
+	if bit {
+		(sibling, digest)
+	} else {
+		(digest, sibling)
+	}
+}
+
+/// Returns the digests of the empty subtrees of every height from `1` to
+/// [`DEPTH`](Configuration::DEPTH), where the last digest is the root of the empty tree.
+#[inline]
+pub fn empty_digests<C>(parameters: &Parameters<C>) -> Vec<InnerDigest<C>>
+where
+	C: Configuration + ?Sized,
+	LeafDigest<C>: Default,
+{
+	let mut digests = Vec::with_capacity(C::DEPTH);
+	let mut digest = parameters.join_leaves(&Default::default(), &Default::default());
+	for _ in 1..C::DEPTH {
+		let parent = parameters.join(&digest, &digest);
+		digests.push(digest);
+		digest = parent;
+	}
+	digests.push(digest);
+	digests
+}
+
+/// Sparse Merkle Tree Node Map
+///
+/// Nodes are keyed by the key bits of their subtree: the leaf of a key is stored at all of its
+/// key bits and the inner node at height `h` above the leaves is stored at the key bits from `h`
+/// onwards, so the root is stored at the empty key. Nodes which are not stored are roots of
+/// empty subtrees.
+pub trait NodeMap<C>
+where
+	C: HashConfiguration + ?Sized,
+{
+	/// Returns the leaf digest stored at `key`.
+	fn get_leaf(&self, key: &[bool]) -> Option<LeafDigest<C>>;
+
+	/// Sets the leaf digest stored at `key` to `leaf_digest`.
+	fn set_leaf(&mut self, key: &[bool], leaf_digest: LeafDigest<C>);
+
+	/// Returns the inner digest stored at `key`.
+	fn get_inner(&self, key: &[bool]) -> Option<InnerDigest<C>>;
+
+	/// Sets the inner digest stored at `key` to `inner_digest`.
+	fn set_inner(&mut self, key: &[bool], inner_digest: InnerDigest<C>);
+}
+
+impl<C, M> NodeMap<C> for &mut M
+where
+	C: HashConfiguration + ?Sized,
+	M: NodeMap<C>,
+{
+	#[inline]
+	fn get_leaf(&self, key: &[bool]) -> Option<LeafDigest<C>> {
+		(**self).get_leaf(key)
+	}
+
+	#[inline]
+	fn set_leaf(&mut self, key: &[bool], leaf_digest: LeafDigest<C>) {
+		(**self).set_leaf(key, leaf_digest)
+	}
+
+	#[inline]
+	fn get_inner(&self, key: &[bool]) -> Option<InnerDigest<C>> {
+		(**self).get_inner(key)
+	}
+
+	#[inline]
+	fn set_inner(&mut self, key: &[bool], inner_digest: InnerDigest<C>) {
+		(**self).set_inner(key, inner_digest)
+	}
+}
+
+/// B-Tree Node Map
+#[derive(derivative::Derivative)]
+#[derivative(
+	Clone(bound = "LeafDigest<C>: Clone, InnerDigest<C>: Clone"),
+	Debug(bound = "LeafDigest<C>: Debug, InnerDigest<C>: Debug"),
+	Default(bound = ""),
+	Eq(bound = "LeafDigest<C>: Eq, InnerDigest<C>: Eq"),
+	Hash(bound = "LeafDigest<C>: Hash, InnerDigest<C>: Hash"),
+	PartialEq(bound = "LeafDigest<C>: PartialEq, InnerDigest<C>: PartialEq")
+)]
+pub struct BTreeNodeMap<C>
+where
+	C: HashConfiguration + ?Sized,
+{
+	/// Leaf Digests
+	leaf_digests: btree_map::BTreeMap<Vec<bool>, LeafDigest<C>>,
+
+	/// Inner Digests
+	inner_digests: btree_map::BTreeMap<Vec<bool>, InnerDigest<C>>,
+}
+
+impl<C> NodeMap<C> for BTreeNodeMap<C>
+where
+	C: HashConfiguration + ?Sized,
+	LeafDigest<C>: Clone,
+	InnerDigest<C>: Clone,
+{
+	#[inline]
+	fn get_leaf(&self, key: &[bool]) -> Option<LeafDigest<C>> {
+		self.leaf_digests.get(key).cloned()
+	}
+
+	#[inline]
+	fn set_leaf(&mut self, key: &[bool], leaf_digest: LeafDigest<C>) {
+		self.leaf_digests.insert(key.to_vec(), leaf_digest);
+	}
+
+	#[inline]
+	fn get_inner(&self, key: &[bool]) -> Option<InnerDigest<C>> {
+		self.inner_digests.get(key).cloned()
+	}
+
+	#[inline]
+	fn set_inner(&mut self, key: &[bool], inner_digest: InnerDigest<C>) {
+		self.inner_digests.insert(key.to_vec(), inner_digest);
+	}
+}
+
+/// Sparse Merkle Tree Path
+///
+/// The same path proves membership of a key which was inserted into the tree and non-membership
+/// of a key which was not, since both only differ in the digest stored at the leaf of the key.
+#[cfg_attr(
+	feature = "serde",
+	derive(Deserialize, Serialize),
+	serde(
+		bound(
+			deserialize = "LeafDigest<C>: Deserialize<'de>, InnerDigest<C>: Deserialize<'de>",
+			serialize = "LeafDigest<C>: Serialize, InnerDigest<C>: Serialize"
+		),
+		crate = "manta_util::serde",
+		deny_unknown_fields
+	)
+)]
+#[derive(derivative::Derivative)]
+#[derivative(
+	Clone(bound = "LeafDigest<C>: Clone, InnerDigest<C>: Clone"),
+	Debug(bound = "LeafDigest<C>: Debug, InnerDigest<C>: Debug"),
+	Eq(bound = "LeafDigest<C>: Eq, InnerDigest<C>: Eq"),
+	Hash(bound = "LeafDigest<C>: Hash, InnerDigest<C>: Hash"),
+	PartialEq(bound = "LeafDigest<C>: PartialEq, InnerDigest<C>: PartialEq")
+)]
+pub struct SparsePath<C>
+where
+	C: Configuration + ?Sized,
+{
+	/// Sibling Digest
+	pub sibling_digest: LeafDigest<C>,
+
+	/// Inner Digest Path
+	///
+	/// Inner digests are stored from leaf to root, not including the root.
+	pub inner_path: Vec<InnerDigest<C>>,
+}
+
+impl<C> SparsePath<C>
+where
+	C: Configuration<Bit = bool> + ?Sized,
+{
+	/// Computes the root of the sparse merkle tree relative to `leaf_digest` stored at the leaf
+	/// with the given key `bits` using `parameters`.
+	///
+	/// # Panics
+	///
+	/// This method panics if `bits` or the inner path of `self` do not match the depth of the
+	/// tree.
+	#[inline]
+	pub fn root(
+		&self,
+		parameters: &Parameters<C>,
+		bits: &[bool],
+		leaf_digest: &LeafDigest<C>,
+	) -> Root<C> {
+		assert!(
+			bits.len() == C::DEPTH && self.inner_path.len() + 1 == C::DEPTH,
+			"Sparse paths are not allowed to be malformed."
+		);
+		let (lhs, rhs) = order(bits[0], leaf_digest, &self.sibling_digest);
+		let base = parameters.join_leaves(lhs, rhs);
+		bits[1..].iter().zip(&self.inner_path).fold(base, |acc, (bit, sibling)| {
+			let (lhs, rhs) = order(*bit, &acc, sibling);
+			parameters.join(lhs, rhs)
+		})
+	}
+
+	/// Returns `true` if `self` is a witness to the fact that `leaf` is stored in a sparse merkle
+	/// tree with the given `root`.
+	#[inline]
+	pub fn verify_membership(
+		&self,
+		parameters: &Parameters<C>,
+		root: &Root<C>,
+		leaf: &Leaf<C>,
+	) -> bool
+	where
+		InnerDigest<C>: PartialEq,
+	{
+		&self.root(parameters, &key_bits::<C>(leaf), &parameters.digest(leaf)) == root
+	}
+
+	/// Returns `true` if `self` is a witness to the fact that `leaf` is not stored in a sparse
+	/// merkle tree with the given `root`.
+	#[inline]
+	pub fn verify_non_membership(
+		&self,
+		parameters: &Parameters<C>,
+		root: &Root<C>,
+		leaf: &Leaf<C>,
+	) -> bool
+	where
+		LeafDigest<C>: Default,
+		InnerDigest<C>: PartialEq,
+	{
+		&self.root(parameters, &key_bits::<C>(leaf), &Default::default()) == root
+	}
+}
+
+/// Sparse Merkle Tree
+pub struct SparseMerkleTree<C, M = BTreeNodeMap<C>>
+where
+	C: Configuration + ?Sized,
+	M: NodeMap<C>,
+{
+	/// Merkle Tree Parameters
+	parameters: Parameters<C>,
+
+	/// Empty Subtree Digests
+	empty_digests: Vec<InnerDigest<C>>,
+
+	/// Node Map
+	map: M,
+}
+
+impl<C, M> SparseMerkleTree<C, M>
+where
+	C: Configuration<Bit = bool> + ?Sized,
+	M: NodeMap<C>,
+	LeafDigest<C>: Default + PartialEq,
+	InnerDigest<C>: Clone,
+{
+	/// Builds a new empty [`SparseMerkleTree`] over `parameters`.
+	#[inline]
+	pub fn new(parameters: Parameters<C>) -> Self
+	where
+		M: Default,
+	{
+		Self::from_map(parameters, Default::default())
+	}
+
+	/// Builds a new [`SparseMerkleTree`] over `parameters` from the nodes stored in `map`.
+	#[inline]
+	pub fn from_map(parameters: Parameters<C>, map: M) -> Self {
+		Self { empty_digests: empty_digests(&parameters), parameters, map }
+	}
+
+	/// Returns a shared reference to the parameters used by this tree.
+	#[inline]
+	pub fn parameters(&self) -> &Parameters<C> {
+		&self.parameters
+	}
+
+	/// Returns a shared reference to the node map of this tree.
+	#[inline]
+	pub fn map(&self) -> &M {
+		&self.map
+	}
+
+	/// Extracts the node map of this tree, dropping the parameters.
+	#[inline]
+	pub fn into_map(self) -> M {
+		self.map
+	}
+
+	/// Returns the root of this tree.
+	#[inline]
+	pub fn root(&self) -> Root<C> {
+		self.map
+			.get_inner(&[])
+			.unwrap_or_else(|| self.empty_digests[C::DEPTH - 1].clone())
+	}
+
+	/// Returns the leaf digest stored at `key`, which is the default value for keys which were
+	/// never inserted.
+	#[inline]
+	fn leaf_digest(&self, key: &[bool]) -> LeafDigest<C> {
+		self.map.get_leaf(key).unwrap_or_default()
+	}
+
+	/// Returns the sibling of the inner node at `height` on the path to the leaf with the given
+	/// key `bits`.
+	#[inline]
+	fn inner_sibling(&self, bits: &[bool], height: usize) -> InnerDigest<C> {
+		let mut key = bits[height..].to_vec();
+		key[0] = !key[0];
+		self.map
+			.get_inner(&key)
+			.unwrap_or_else(|| self.empty_digests[height - 1].clone())
+	}
+
+	/// Returns the path to the leaf with the given key `bits`.
+	#[inline]
+	fn path_for_bits(&self, bits: &[bool]) -> SparsePath<C> {
+		let mut sibling = bits.to_vec();
+		sibling[0] = !sibling[0];
+		SparsePath {
+			sibling_digest: self.leaf_digest(&sibling),
+			inner_path: (1..C::DEPTH).map(|height| self.inner_sibling(bits, height)).collect(),
+		}
+	}
+
+	/// Returns `true` if `leaf` is stored in this tree.
+	#[inline]
+	pub fn contains(&self, leaf: &Leaf<C>) -> bool {
+		self.leaf_digest(&key_bits::<C>(leaf)) != Default::default()
+	}
+
+	/// Returns the path to the leaf of `leaf` in this tree, which proves its membership if it was
+	/// inserted and its non-membership otherwise.
+	#[inline]
+	pub fn path(&self, leaf: &Leaf<C>) -> SparsePath<C> {
+		self.path_for_bits(&key_bits::<C>(leaf))
+	}
+
+	/// Inserts `leaf` into this tree, returning `false` if it was already stored.
+	///
+	/// # Panics
+	///
+	/// This method panics if the digest of `leaf` is the default leaf digest, since it could not
+	/// be told apart from a leaf which was never inserted.
+	#[inline]
+	pub fn insert(&mut self, leaf: &Leaf<C>) -> bool {
+		let bits = key_bits::<C>(leaf);
+		if self.leaf_digest(&bits) != Default::default() {
+			return false
+		}
+		let leaf_digest = self.parameters.digest(leaf);
+		assert!(
+			leaf_digest != Default::default(),
+			"Leaf digests are not allowed to be equal to the default leaf digest."
+		);
+		let path = self.path_for_bits(&bits);
+		let (lhs, rhs) = order(bits[0], &leaf_digest, &path.sibling_digest);
+		let mut acc = self.parameters.join_leaves(lhs, rhs);
+		self.map.set_leaf(&bits, leaf_digest);
+		for (height, sibling) in (1..C::DEPTH).zip(&path.inner_path) {
+			self.map.set_inner(&bits[height..], acc.clone());
+			let (lhs, rhs) = order(bits[height], &acc, sibling);
+			acc = self.parameters.join(lhs, rhs);
+		}
+		self.map.set_inner(&[], acc);
+		true
+	}
+}
+
+/// Constraint System Gadgets
+pub mod constraint {
+	use super::*;
+	use crate::crypto::eclair::{
+		alloc::{mode::Secret, Allocate, Allocator, Constant, Variable},
+		bool::{Bool, ConditionalSwap},
+		cmp::PartialEq,
+		Has,
+	};
+
+	/// Sparse Merkle Tree Path Variable
+	pub struct SparsePathVar<C, COM>
+	where
+		C: Configuration<COM> + ?Sized,
+	{
+		/// Sibling Digest
+		pub sibling_digest: LeafDigest<C, COM>,
+
+		/// Inner Digest Path
+		///
+		/// Inner digests are stored from leaf to root, not including the root.
+		pub inner_path: Vec<InnerDigest<C, COM>>,
+	}
+
+	impl<C, COM> SparsePathVar<C, COM>
+	where
+		C: Configuration<COM, Bit = Bool<COM>> + ?Sized,
+		COM: Has<bool>,
+		LeafDigest<C, COM>: ConditionalSwap<COM>,
+		InnerDigest<C, COM>: ConditionalSwap<COM>,
+	{
+		/// Computes the root of the sparse merkle tree relative to `leaf_digest` stored at the
+		/// leaf with the given key `bits` using `parameters`.
+		///
+		/// # Panics
+		///
+		/// This method panics if `bits` or the inner path of `self` do not match the depth of the
+		/// tree.
+		#[inline]
+		pub fn root(
+			&self,
+			parameters: &Parameters<C, COM>,
+			bits: &[Bool<COM>],
+			leaf_digest: &LeafDigest<C, COM>,
+			compiler: &mut COM,
+		) -> Root<C, COM> {
+			assert!(
+				bits.len() == C::DEPTH && self.inner_path.len() + 1 == C::DEPTH,
+				"Sparse paths are not allowed to be malformed."
+			);
+			let (lhs, rhs) =
+				ConditionalSwap::swap(&bits[0], leaf_digest, &self.sibling_digest, compiler);
+			let base = parameters.join_leaves_with(&lhs, &rhs, compiler);
+			bits[1..].iter().zip(&self.inner_path).fold(base, |acc, (bit, sibling)| {
+				let (lhs, rhs) = ConditionalSwap::swap(bit, &acc, sibling, compiler);
+				parameters.join_with(&lhs, &rhs, compiler)
+			})
+		}
+
+		/// Returns `true` if `self` is a witness to the fact that `leaf` is stored in a sparse
+		/// merkle tree with the given `root`.
+		#[inline]
+		pub fn verify_membership(
+			&self,
+			parameters: &Parameters<C, COM>,
+			root: &Root<C, COM>,
+			leaf: &Leaf<C, COM>,
+			compiler: &mut COM,
+		) -> Bool<COM>
+		where
+			Root<C, COM>: PartialEq<Root<C, COM>, COM>,
+		{
+			let bits = C::key_bits(leaf, compiler);
+			let leaf_digest = parameters.digest_with(leaf, compiler);
+			let computed_root = self.root(parameters, &bits, &leaf_digest, compiler);
+			root.eq(&computed_root, compiler)
+		}
+
+		/// Returns `true` if `self` is a witness to the fact that `leaf` is not stored in a sparse
+		/// merkle tree with the given `root`.
+		#[inline]
+		pub fn verify_non_membership(
+			&self,
+			parameters: &Parameters<C, COM>,
+			root: &Root<C, COM>,
+			leaf: &Leaf<C, COM>,
+			compiler: &mut COM,
+		) -> Bool<COM>
+		where
+			LeafDigest<C, COM>: Constant<COM>,
+			<LeafDigest<C, COM> as Constant<COM>>::Type: Default,
+			Root<C, COM>: PartialEq<Root<C, COM>, COM>,
+		{
+			let bits = C::key_bits(leaf, compiler);
+			let empty = LeafDigest::<C, COM>::new_constant(&Default::default(), compiler);
+			let computed_root = self.root(parameters, &bits, &empty, compiler);
+			root.eq(&computed_root, compiler)
+		}
+	}
+
+	impl<C, COM> Variable<Secret, COM> for SparsePathVar<C, COM>
+	where
+		C: Configuration<COM> + Constant<COM> + ?Sized,
+		C::Type: Configuration<Bit = bool>,
+		InnerDigest<C, COM>: Variable<Secret, COM, Type = InnerDigest<C::Type>>,
+		LeafDigest<C, COM>: Variable<Secret, COM, Type = LeafDigest<C::Type>>,
+	{
+		type Type = SparsePath<C::Type>;
+
+		#[inline]
+		fn new_known(this: &Self::Type, compiler: &mut COM) -> Self {
+			Self {
+				sibling_digest: this.sibling_digest.as_known(compiler),
+				inner_path: this.inner_path.iter().map(|d| d.as_known(compiler)).collect(),
+			}
+		}
+
+		#[inline]
+		fn new_unknown(compiler: &mut COM) -> Self {
+			Self {
+				sibling_digest: compiler.allocate_unknown(),
+				inner_path: (1..C::DEPTH).map(|_| compiler.allocate_unknown()).collect(),
+			}
+		}
+	}
+}
+
+/// Testing Framework
+pub mod test {
+	use super::*;
+
+	/// Tests that every leaf in `leaves` has a membership proof after it is inserted into a new
+	/// tree, that every leaf in `absent` has a non-membership proof, and that the path proving
+	/// non-membership of a leaf also computes the root after it is inserted.
+	///
+	/// # Panics
+	///
+	/// This function panics if `leaves` and `absent` are not disjoint.
+	#[inline]
+	pub fn assert_valid_sparse_paths<C>(
+		parameters: Parameters<C>,
+		leaves: &[Leaf<C>],
+		absent: &[Leaf<C>],
+	) where
+		C: Configuration<Bit = bool> + ?Sized,
+		Leaf<C>: Sized,
+		LeafDigest<C>: Clone + Default + PartialEq,
+		InnerDigest<C>: Clone + Debug + PartialEq,
+	{
+		let mut tree = SparseMerkleTree::<C>::new(parameters);
+		for leaf in leaves {
+			let path = tree.path(leaf);
+			assert!(
+				path.verify_non_membership(tree.parameters(), &tree.root(), leaf),
+				"Leaves must not be members before they are inserted."
+			);
+			let expected =
+				path.root(tree.parameters(), &key_bits::<C>(leaf), &tree.parameters().digest(leaf));
+			assert!(tree.insert(leaf), "Leaves must be inserted only once.");
+			assert!(!tree.insert(leaf), "Leaves must be inserted only once.");
+			assert_eq!(tree.root(), expected, "Non-membership paths must compute the next root.");
+		}
+		let root = tree.root();
+		for leaf in leaves {
+			let path = tree.path(leaf);
+			assert!(tree.contains(leaf), "Inserted leaves must be stored in the tree.");
+			assert!(
+				path.verify_membership(tree.parameters(), &root, leaf),
+				"Inserted leaves must have a membership proof."
+			);
+			assert!(
+				!path.verify_non_membership(tree.parameters(), &root, leaf),
+				"Inserted leaves must not have a non-membership proof."
+			);
+		}
+		for leaf in absent {
+			let path = tree.path(leaf);
+			assert!(!tree.contains(leaf), "Absent leaves must not be stored in the tree.");
+			assert!(
+				path.verify_non_membership(tree.parameters(), &root, leaf),
+				"Absent leaves must have a non-membership proof."
+			);
+			assert!(
+				!path.verify_membership(tree.parameters(), &root, leaf),
+				"Absent leaves must not have a membership proof."
+			);
+		}
+	}
+}
@@ -16,15 +16,21 @@
 
 //! Testing Framework
 
-use crate::crypto::{
-	merkle_tree::{
-		Configuration, HashConfiguration, IdentityLeafHash, InnerDigest, InnerHash,
-		InnerHashParameters, Leaf, LeafHashParameters, MerkleTree, Parameters, Path, Tree,
-		WithProofs,
+use crate::{
+	crypto::{
+		merkle_tree::{
+			full::{BTreeIndex, Full},
+			inner_tree::BTreeMap,
+			partial::Partial,
+			path_length, Configuration, CurrentPath, HashConfiguration, IdentityLeafHash,
+			InnerDigest, InnerHash, InnerHashParameters, Leaf, LeafDigest, LeafHashParameters,
+			MerkleTree, MultiPath, Parameters, Path, Tree, WithCheckpoints, WithProofs,
+		},
+		rand::{RngCore, Sample},
 	},
-	rand::{RngCore, Sample},
+	util::MaybeSendSync,
 };
-use alloc::string::String;
+use alloc::{collections::BTreeSet, string::String, vec::Vec};
 use core::{fmt::Debug, hash::Hash, marker::PhantomData};
 
 /// Hash Parameter Sampling
@@ -160,6 +166,229 @@ where
 	}
 }
 
+/// Tests multi-path construction by checking that the multi-path for the given `indices` on
+/// `tree` is valid for `leaves` and that it never stores more siblings than the individual paths.
+///
+/// The `leaves` must be given in the same order as `indices`.
+#[inline]
+pub fn assert_valid_multi_path<C, T>(tree: &MerkleTree<C, T>, indices: &[usize], leaves: &[Leaf<C>])
+where
+	C: Configuration + ?Sized,
+	T: Tree<C> + WithProofs<C>,
+	Leaf<C>: Clone + Sized,
+	LeafDigest<C>: Clone,
+	InnerDigest<C>: Clone + Debug + PartialEq,
+	MultiPath<C>: Debug,
+{
+	let multi_path = tree.multi_path(indices).expect("Only valid queries are accepted.");
+	let mut pairs = indices.iter().zip(leaves).collect::<Vec<_>>();
+	pairs.sort_by_key(|(index, _)| **index);
+	pairs.dedup_by_key(|(index, _)| **index);
+	let leaves = pairs.into_iter().map(|(_, leaf)| leaf.clone()).collect::<Vec<_>>();
+	let root = tree.root();
+	assert!(
+		tree.parameters().verify_multi_path(&multi_path, root, &leaves),
+		"Multi-path returned from tree was not valid: {:?}. Expected {:?}.",
+		multi_path,
+		root,
+	);
+	assert_eq!(
+		multi_path.leaf_siblings.len() + multi_path.inner_siblings.len(),
+		multi_path_sibling_count::<C>(indices),
+		"Multi-paths must store exactly the siblings which cannot be computed from their leaves."
+	);
+}
+
+/// Returns the number of siblings that a multi-path for the leaves at `indices` has to store,
+/// counting every node whose sibling is on the path of some leaf but which is not on the path of
+/// any leaf itself.
+#[inline]
+pub fn multi_path_sibling_count<C>(indices: &[usize]) -> usize
+where
+	C: Configuration + ?Sized,
+{
+	let mut nodes = indices.iter().copied().collect::<BTreeSet<_>>();
+	let mut count = 0;
+	for _ in 0..=path_length::<C, _>() {
+		count += nodes.iter().filter(|node| !nodes.contains(&(*node ^ 1))).count();
+		nodes = nodes.into_iter().map(|node| node >> 1).collect();
+	}
+	count
+}
+
+/// Tests that rolling a tree back to a checkpoint gives the same tree as rebuilding it from the
+/// truncated list of leaves, for `rounds` random split points of `leaves`.
+#[inline]
+pub fn assert_rollback_matches_rebuild<C, T, R>(
+	parameters: &Parameters<C>,
+	leaves: &[Leaf<C>],
+	rounds: usize,
+	rng: &mut R,
+) where
+	C: Configuration + ?Sized,
+	T: Tree<C> + WithCheckpoints<C>,
+	Parameters<C>: Clone,
+	InnerDigest<C>: Debug + PartialEq,
+	CurrentPath<C>: Debug + PartialEq,
+	Leaf<C>: Sized,
+	R: RngCore + ?Sized,
+{
+	for _ in 0..rounds {
+		let split = (rng.next_u64() % (leaves.len() as u64 + 1)) as usize;
+		let (prefix, suffix) = leaves.split_at(split);
+		let expected = MerkleTree::<C, T>::from_slice(parameters.clone(), prefix)
+			.expect("Only valid leaf lists are accepted.");
+		let mut tree = MerkleTree::<C, T>::from_slice(parameters.clone(), prefix)
+			.expect("Only valid leaf lists are accepted.");
+		let checkpoint = tree.checkpoint();
+		assert!(tree.extend_slice(suffix), "Only valid leaf lists are accepted.");
+		assert!(tree.rollback_to(&checkpoint), "Rolling back to an older state must succeed.");
+		assert_eq!(tree.len(), expected.len(), "Rolled back length must match.");
+		assert_eq!(tree.root(), expected.root(), "Rolled back root must match.");
+		assert_eq!(
+			tree.current_path(),
+			expected.current_path(),
+			"Rolled back current path must match."
+		);
+		assert!(tree.extend_slice(suffix), "Only valid leaf lists are accepted.");
+		let full = MerkleTree::<C, T>::from_slice(parameters.clone(), leaves)
+			.expect("Only valid leaf lists are accepted.");
+		assert_eq!(tree.root(), full.root(), "Re-extended root must match.");
+		assert_eq!(
+			tree.current_path(),
+			full.current_path(),
+			"Re-extended current path must match."
+		);
+	}
+}
+
+/// Tests that inserting `leaves` in random batches with [`extend_slice`](Tree::extend_slice)
+/// gives the same tree as pushing them one at a time, after every batch.
+#[inline]
+pub fn assert_batch_matches_push<C, T, R>(
+	parameters: &Parameters<C>,
+	leaves: &[Leaf<C>],
+	rng: &mut R,
+) where
+	C: Configuration + ?Sized,
+	T: Tree<C>,
+	Parameters<C>: Clone,
+	InnerDigest<C>: Debug + PartialEq,
+	CurrentPath<C>: Debug + PartialEq,
+	Leaf<C>: Sized,
+	R: RngCore + ?Sized,
+{
+	let mut batched = MerkleTree::<C, T>::new(parameters.clone());
+	let mut pushed = MerkleTree::<C, T>::new(parameters.clone());
+	let mut rest = leaves;
+	while !rest.is_empty() {
+		let count = 1 + (rng.next_u64() % rest.len() as u64) as usize;
+		let (batch, next) = rest.split_at(count);
+		assert!(batched.extend_slice(batch), "Only valid leaf lists are accepted.");
+		for leaf in batch {
+			assert!(pushed.push(leaf), "Only valid leaf lists are accepted.");
+		}
+		assert_eq!(batched.len(), pushed.len(), "Batched and pushed lengths must agree.");
+		assert_eq!(batched.root(), pushed.root(), "Batched and pushed roots must agree.");
+		assert_eq!(
+			batched.current_path(),
+			pushed.current_path(),
+			"Batched and pushed current paths must agree."
+		);
+		rest = next;
+	}
+}
+
+/// Tests that a [`Partial`] tree which marks a random subset of `leaves` agrees with a [`Full`]
+/// tree on the root, the current path and the paths of the marked leaves, and that unmarking every
+/// leaf prunes all but the current leaf pair.
+#[inline]
+pub fn assert_partial_matches_full<C, R>(
+	parameters: &Parameters<C>,
+	leaves: &[Leaf<C>],
+	rng: &mut R,
+) where
+	C: Configuration + ?Sized,
+	Parameters<C>: Clone + MaybeSendSync,
+	LeafDigest<C>: Clone + Default + PartialEq + MaybeSendSync,
+	InnerDigest<C>: Clone + Debug + Default + PartialEq + MaybeSendSync,
+	CurrentPath<C>: Debug + PartialEq,
+	Path<C>: Debug + PartialEq,
+	Leaf<C>: Sized,
+	R: RngCore + ?Sized,
+{
+	let mut partial = MerkleTree::<C, Partial<C>>::new(parameters.clone());
+	let mut full = MerkleTree::<C, Full<C>>::new(parameters.clone());
+	for leaf in leaves {
+		if rng.next_u32() % 4 == 0 {
+			assert!(partial.push_provable(leaf), "Only valid leaf lists are accepted.");
+		} else {
+			assert!(partial.push(leaf), "Only valid leaf lists are accepted.");
+		}
+		assert!(full.push(leaf), "Only valid leaf lists are accepted.");
+		assert_eq!(partial.root(), full.root(), "Partial and full roots must agree.");
+		assert_eq!(
+			partial.current_path(),
+			full.current_path(),
+			"Partial and full current paths must agree."
+		);
+	}
+	let marked = partial.tree.marked().collect::<Vec<_>>();
+	for index in &marked {
+		assert_eq!(
+			partial.path(*index).expect("Marked leaves must be provable."),
+			full.path(*index).expect("Full trees can prove every leaf."),
+			"Partial and full paths must agree for marked leaves."
+		);
+	}
+	for index in marked {
+		assert!(partial.tree.unmark(index), "Marked leaves must be unmarkable.");
+	}
+	assert!(
+		partial.tree.stored_leaf_count() <= 2,
+		"Unmarked partial trees must only keep the current leaf pair."
+	);
+	assert_eq!(partial.root(), full.root(), "Unmarking must not change the root.");
+}
+
+/// Tests that a [`Full`] tree with a [`BTreeIndex`] finds the same leaf positions as a [`Full`]
+/// tree which scans its leaves, after pushing and extending by `leaves` and after rolling back to
+/// half of them.
+#[inline]
+pub fn assert_indexed_positions<C>(parameters: &Parameters<C>, leaves: &[Leaf<C>])
+where
+	C: Configuration + ?Sized,
+	Parameters<C>: Clone + MaybeSendSync,
+	LeafDigest<C>: Clone + Default + Ord + MaybeSendSync,
+	InnerDigest<C>: Clone + Default + PartialEq + MaybeSendSync,
+	Leaf<C>: Sized,
+{
+	let mut scanned = MerkleTree::<C, Full<C>>::new(parameters.clone());
+	let mut indexed = MerkleTree::<C, Full<C, BTreeMap<C>, BTreeIndex<C>>>::new(parameters.clone());
+	let (head, tail) = leaves.split_at(leaves.len() / 2);
+	for leaf in head {
+		assert!(scanned.push(leaf), "Only valid leaf lists are accepted.");
+		assert!(indexed.push(leaf), "Only valid leaf lists are accepted.");
+	}
+	let checkpoint = indexed.checkpoint();
+	assert!(scanned.extend_slice(tail), "Only valid leaf lists are accepted.");
+	assert!(indexed.extend_slice(tail), "Only valid leaf lists are accepted.");
+	let assert_same_positions = |scanned: &MerkleTree<C, Full<C>>, indexed: &MerkleTree<C, _>| {
+		for leaf in leaves {
+			let leaf_digest = parameters.digest(leaf);
+			assert_eq!(
+				scanned.position(&leaf_digest),
+				indexed.position(&leaf_digest),
+				"Indexed and scanned positions must agree."
+			);
+		}
+	};
+	assert_same_positions(&scanned, &indexed);
+	assert!(scanned.rollback_to(&checkpoint), "Rolling back to an earlier state must succeed.");
+	assert!(indexed.rollback_to(&checkpoint), "Rolling back to an earlier state must succeed.");
+	assert_same_positions(&scanned, &indexed);
+}
+
 /// Test Inner Hash
 ///
 /// # Warning
@@ -16,13 +16,23 @@
 
 //! Merkle Tree Abstractions
 
-use super::path::{constraint::PathVar, CurrentPath, Path};
-use crate::crypto::eclair::{
-	self,
-	alloc::{Allocate, Constant},
-	bool::{Bool, ConditionalSwap},
-	Has,
+use super::path::{
+	constraint::{MultiPathVar, PathVar},
+	CurrentPath, MultiPath, Path,
 };
+use crate::{
+	crypto::{
+		accumulator::{self, MembershipProof},
+		eclair::{
+			self,
+			alloc::{Allocate, Constant},
+			bool::{AssertEq, Bool, ConditionalSwap},
+			Has, NonNative,
+		},
+	},
+	util::MaybeSendSync,
+};
+use alloc::vec::Vec;
 use core::{fmt::Debug, hash::Hash, marker::PhantomData};
 
 #[cfg(feature = "serde")]
@@ -443,6 +453,28 @@ where
 	}
 }
 
+/// Merkle Tree Checkpoint Mixin
+pub trait WithCheckpoints<C>
+where
+	C: Configuration + ?Sized,
+{
+	/// Checkpoint Type
+	type Checkpoint;
+
+	/// Returns a checkpoint of the current state of `self`.
+	fn checkpoint(&self) -> Self::Checkpoint;
+
+	/// Rolls `self` back to the state it was in when `checkpoint` was taken, returning `false` if
+	/// `checkpoint` is ahead of the current state of `self`.
+	///
+	/// # Implementation Note
+	///
+	/// A checkpoint only describes states that `self` has passed through. After rolling back past
+	/// a checkpoint and inserting different leaves, rolling back to that checkpoint is allowed to
+	/// restore a state that `self` was never in.
+	fn rollback_to(&mut self, parameters: &Parameters<C>, checkpoint: &Self::Checkpoint) -> bool;
+}
+
 /// Merkle Tree Root
 pub type Root<C, COM = ()> = InnerDigest<C, COM>;
 
@@ -545,6 +577,25 @@ where
 	{
 		path.verify(self, root, leaf, compiler)
 	}
+
+	/// Verify that `multi_path` witnesses the fact that every leaf in `leaves` is a member of a
+	/// merkle tree with the given `root`.
+	#[inline]
+	pub fn verify_multi_path_with(
+		&self,
+		multi_path: &MultiPathVar<C, COM>,
+		root: &Root<C, COM>,
+		leaves: &[Leaf<C, COM>],
+		compiler: &mut COM,
+	) -> Bool<COM>
+	where
+		C: Configuration<COM>,
+		COM: Has<bool>,
+		Leaf<C, COM>: Sized,
+		InnerDigest<C, COM>: Clone + eclair::cmp::PartialEq<InnerDigest<C, COM>, COM>,
+	{
+		multi_path.verify(self, root, leaves, compiler)
+	}
 }
 
 impl<C> Parameters<C>
@@ -569,6 +620,54 @@ where
 		C::InnerHash::join_leaves(&self.inner, lhs, rhs, &mut ())
 	}
 
+	/// Combines each consecutive pair of `digests` into a new inner digest using `self`, pairing
+	/// the last digest with the default value if `digests` has odd length.
+	///
+	/// With the `parallel` feature enabled, the pairs are combined in parallel.
+	#[inline]
+	pub fn join_pairs(&self, digests: &[InnerDigest<C>]) -> Vec<InnerDigest<C>>
+	where
+		Self: MaybeSendSync,
+		InnerDigest<C>: Default + MaybeSendSync,
+	{
+		let default = Default::default();
+		let join = |pair: &[InnerDigest<C>]| self.join(&pair[0], pair.get(1).unwrap_or(&default));
+		#[cfg(feature = "parallel")]
+		{
+			use rayon::prelude::*;
+			digests.par_chunks(2).map(join).collect()
+		}
+		#[cfg(not(feature = "parallel"))]
+		{
+			digests.chunks(2).map(join).collect()
+		}
+	}
+
+	/// Combines each consecutive pair of leaf `digests` into a new inner digest using `self`,
+	/// pairing the last digest with the default value if `digests` has odd length.
+	///
+	/// With the `parallel` feature enabled, the pairs are combined in parallel.
+	#[inline]
+	pub fn join_leaf_pairs(&self, digests: &[LeafDigest<C>]) -> Vec<InnerDigest<C>>
+	where
+		Self: MaybeSendSync,
+		LeafDigest<C>: Default + MaybeSendSync,
+		InnerDigest<C>: MaybeSendSync,
+	{
+		let default = Default::default();
+		let join =
+			|pair: &[LeafDigest<C>]| self.join_leaves(&pair[0], pair.get(1).unwrap_or(&default));
+		#[cfg(feature = "parallel")]
+		{
+			use rayon::prelude::*;
+			digests.par_chunks(2).map(join).collect()
+		}
+		#[cfg(not(feature = "parallel"))]
+		{
+			digests.chunks(2).map(join).collect()
+		}
+	}
+
 	/// Verify that `path` witnesses the fact that `leaf` is a member of a merkle tree with the
 	/// given `root`.
 	#[inline]
@@ -579,6 +678,23 @@ where
 	{
 		path.verify(self, root, leaf)
 	}
+
+	/// Verify that `multi_path` witnesses the fact that every leaf in `leaves` is a member of a
+	/// merkle tree with the given `root`.
+	#[inline]
+	pub fn verify_multi_path(
+		&self,
+		multi_path: &MultiPath<C>,
+		root: &Root<C>,
+		leaves: &[Leaf<C>],
+	) -> bool
+	where
+		C: Configuration,
+		Leaf<C>: Sized,
+		InnerDigest<C>: Clone + PartialEq,
+	{
+		multi_path.verify(self, root, leaves)
+	}
 }
 
 impl<C, COM> Constant<COM> for Parameters<C, COM>
@@ -596,61 +712,6 @@ where
 	}
 }
 
-/*
-
-/// Parameter Decode Error
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
-pub enum ParameterDecodeError<L, I> {
-	/// Leaf Decoding Error
-	Leaf(L),
-
-	/// Inner Decoding Error
-	Inner(I),
-}
-
-impl<C, COM> Decode for Parameters<C, COM>
-where
-	C: HashConfiguration<COM> + ?Sized,
-	LeafHashParameters<C, COM>: Decode,
-	InnerHashParameters<C, COM>: Decode,
-{
-	#[allow(clippy::type_complexity)] // NOTE: This is an implementation type so it doesn't matter.
-	type Error = ParameterDecodeError<
-		<LeafHashParameters<C, COM> as Decode>::Error,
-		<InnerHashParameters<C, COM> as Decode>::Error,
-	>;
-
-	#[inline]
-	fn decode<R>(mut reader: R) -> Result<Self, DecodeError<R::Error, Self::Error>>
-	where
-		R: Read,
-	{
-		Ok(Self::new(
-			LeafHashParameters::<C, COM>::decode(&mut reader)
-				.map_err(|err| err.map_decode(ParameterDecodeError::Leaf))?,
-			InnerHashParameters::<C, COM>::decode(&mut reader)
-				.map_err(|err| err.map_decode(ParameterDecodeError::Inner))?,
-		))
-	}
-}
-
-impl<C, COM> Encode for Parameters<C, COM>
-where
-	C: HashConfiguration<COM> + ?Sized,
-	LeafHashParameters<C, COM>: Encode,
-	InnerHashParameters<C, COM>: Encode,
-{
-	#[inline]
-	fn encode<W>(&self, mut writer: W) -> Result<(), W::Error>
-	where
-		W: Write,
-	{
-		self.leaf.encode(&mut writer)?;
-		self.inner.encode(&mut writer)?;
-		Ok(())
-	}
-}
-
 impl<C> accumulator::Model for Parameters<C>
 where
 	C: Configuration + ?Sized,
@@ -676,8 +737,8 @@ where
 impl<C, COM> accumulator::Model<COM> for Parameters<C, COM>
 where
 	C: Configuration<COM> + ?Sized,
-	COM: Has<bool>,
-	InnerDigest<C, COM>: ConditionalSwap<COM> + constraint::PartialEq<InnerDigest<C, COM>, COM>,
+	COM: Has<bool> + NonNative,
+	InnerDigest<C, COM>: ConditionalSwap<COM> + eclair::cmp::PartialEq<InnerDigest<C, COM>, COM>,
 	LeafDigest<C, COM>: ConditionalSwap<COM>,
 {
 	type Item = Leaf<C, COM>;
@@ -697,11 +758,29 @@ where
 	}
 }
 
+impl<C> accumulator::AssertValidVerification for Parameters<C>
+where
+	C: Configuration + ?Sized,
+	InnerDigest<C>: PartialEq,
+{
+	#[inline]
+	fn assert_valid(
+		&self,
+		item: &Self::Item,
+		witness: &Self::Witness,
+		output: &Self::Output,
+		compiler: &mut (),
+	) {
+		let root = witness.root(self, &self.digest(item));
+		compiler.assert_eq(output, &root)
+	}
+}
+
 impl<C, COM> accumulator::AssertValidVerification<COM> for Parameters<C, COM>
 where
-	COM: AssertEq,
+	COM: AssertEq + NonNative,
 	C: Configuration<COM> + ?Sized,
-	InnerDigest<C, COM>: ConditionalSwap<COM> + constraint::PartialEq<InnerDigest<C, COM>, COM>,
+	InnerDigest<C, COM>: ConditionalSwap<COM> + eclair::cmp::PartialEq<InnerDigest<C, COM>, COM>,
 	LeafDigest<C, COM>: ConditionalSwap<COM>,
 {
 	#[inline]
@@ -717,6 +796,61 @@ where
 	}
 }
 
+/*
+
+/// Parameter Decode Error
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum ParameterDecodeError<L, I> {
+	/// Leaf Decoding Error
+	Leaf(L),
+
+	/// Inner Decoding Error
+	Inner(I),
+}
+
+impl<C, COM> Decode for Parameters<C, COM>
+where
+	C: HashConfiguration<COM> + ?Sized,
+	LeafHashParameters<C, COM>: Decode,
+	InnerHashParameters<C, COM>: Decode,
+{
+	#[allow(clippy::type_complexity)] // NOTE: This is an implementation type so it doesn't matter.
+	type Error = ParameterDecodeError<
+		<LeafHashParameters<C, COM> as Decode>::Error,
+		<InnerHashParameters<C, COM> as Decode>::Error,
+	>;
+
+	#[inline]
+	fn decode<R>(mut reader: R) -> Result<Self, DecodeError<R::Error, Self::Error>>
+	where
+		R: Read,
+	{
+		Ok(Self::new(
+			LeafHashParameters::<C, COM>::decode(&mut reader)
+				.map_err(|err| err.map_decode(ParameterDecodeError::Leaf))?,
+			InnerHashParameters::<C, COM>::decode(&mut reader)
+				.map_err(|err| err.map_decode(ParameterDecodeError::Inner))?,
+		))
+	}
+}
+
+impl<C, COM> Encode for Parameters<C, COM>
+where
+	C: HashConfiguration<COM> + ?Sized,
+	LeafHashParameters<C, COM>: Encode,
+	InnerHashParameters<C, COM>: Encode,
+{
+	#[inline]
+	fn encode<W>(&self, mut writer: W) -> Result<(), W::Error>
+	where
+		W: Write,
+	{
+		self.leaf.encode(&mut writer)?;
+		self.inner.encode(&mut writer)?;
+		Ok(())
+	}
+}
+
 */
 
 /// Merkle Tree
@@ -952,38 +1086,53 @@ where
 		self.tree.path(&self.parameters, index)
 	}
 
-	/// Extracts the parameters of the merkle tree, dropping the internal tree.
+	/// Returns a [`MultiPath`] for the leaves stored at the given `indices` if they all exist,
+	/// sharing the siblings which are common to their paths.
+	///
+	/// See [`WithProofs::path`] for more.
 	#[inline]
-	pub fn into_parameters(self) -> Parameters<C> {
-		self.parameters
+	pub fn multi_path(&self, indices: &[usize]) -> Result<MultiPath<C>, PathError>
+	where
+		T: WithProofs<C>,
+		LeafDigest<C>: Clone,
+		InnerDigest<C>: Clone,
+	{
+		Ok(MultiPath::from_paths(
+			indices.iter().map(|i| self.path(*i)).collect::<Result<Vec<_>, _>>()?,
+		))
 	}
-}
 
-/*
+	/// Returns a checkpoint of the current state of the tree.
+	///
+	/// See [`WithCheckpoints::checkpoint`] for more.
+	#[inline]
+	pub fn checkpoint(&self) -> T::Checkpoint
+	where
+		T: WithCheckpoints<C>,
+	{
+		self.tree.checkpoint()
+	}
 
-impl<C, T> AsMut<T> for MerkleTree<C, T>
-where
-	C: Configuration + ?Sized,
-	T: Tree<C>,
-{
+	/// Rolls the tree back to the state it was in when `checkpoint` was taken, returning `false`
+	/// if `checkpoint` is ahead of the current state of the tree.
+	///
+	/// See [`WithCheckpoints::rollback_to`] for more.
 	#[inline]
-	fn as_mut(&mut self) -> &mut T {
-		&mut self.tree
+	pub fn rollback_to(&mut self, checkpoint: &T::Checkpoint) -> bool
+	where
+		T: WithCheckpoints<C>,
+	{
+		self.tree.rollback_to(&self.parameters, checkpoint)
 	}
-}
 
-impl<C, T> AsRef<T> for MerkleTree<C, T>
-where
-	C: Configuration + ?Sized,
-	T: Tree<C>,
-{
+	/// Extracts the parameters of the merkle tree, dropping the internal tree.
 	#[inline]
-	fn as_ref(&self) -> &T {
-		&self.tree
+	pub fn into_parameters(self) -> Parameters<C> {
+		self.parameters
 	}
 }
 
-impl<C, T> Accumulator for MerkleTree<C, T>
+impl<C, T> accumulator::Accumulator for MerkleTree<C, T>
 where
 	C: Configuration + ?Sized,
 	T: Tree<C> + WithProofs<C>,
@@ -1016,4 +1165,71 @@ where
 	}
 }
 
+impl<C, T> accumulator::ConstantCapacityAccumulator for MerkleTree<C, T>
+where
+	C: Configuration + ?Sized,
+	T: Tree<C> + WithProofs<C>,
+	InnerDigest<C>: Clone + PartialEq,
+{
+	#[inline]
+	fn capacity() -> usize {
+		capacity::<C, _>()
+	}
+}
+
+impl<C, T> accumulator::ExactSizeAccumulator for MerkleTree<C, T>
+where
+	C: Configuration + ?Sized,
+	T: Tree<C> + WithProofs<C>,
+	InnerDigest<C>: Clone + PartialEq,
+{
+	#[inline]
+	fn len(&self) -> usize {
+		self.len()
+	}
+}
+
+impl<C, T> accumulator::OptimizedAccumulator for MerkleTree<C, T>
+where
+	C: Configuration + ?Sized,
+	T: Tree<C> + WithProofs<C>,
+	InnerDigest<C>: Clone + PartialEq,
+{
+	#[inline]
+	fn insert_nonprovable(&mut self, item: &Self::Item) -> bool {
+		self.push(item)
+	}
+
+	#[inline]
+	fn remove_proof(&mut self, item: &Self::Item) -> bool {
+		self.position(&self.parameters.digest(item))
+			.map(|index| self.tree.remove_path(index))
+			.unwrap_or(false)
+	}
+}
+
+/*
+
+impl<C, T> AsMut<T> for MerkleTree<C, T>
+where
+	C: Configuration + ?Sized,
+	T: Tree<C>,
+{
+	#[inline]
+	fn as_mut(&mut self) -> &mut T {
+		&mut self.tree
+	}
+}
+
+impl<C, T> AsRef<T> for MerkleTree<C, T>
+where
+	C: Configuration + ?Sized,
+	T: Tree<C>,
+{
+	#[inline]
+	fn as_ref(&self) -> &T {
+		&self.tree
+	}
+}
+
 */
@@ -2,8 +2,16 @@
 
 pub mod accumulator;
 pub mod arkworks;
+pub mod circom;
+pub mod cost;
 pub mod eclair;
+pub mod hash;
 pub mod merkle_tree;
 pub mod poseidon;
 pub mod proofsystem;
 pub mod rand;
+pub mod rescue;
+
+#[cfg(feature = "solidity")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "solidity")))]
+pub mod solidity;
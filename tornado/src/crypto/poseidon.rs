@@ -1,6 +1,9 @@
 //! Poseidon Hash Function
 
-use crate::crypto::rand::{Rand, RngCore, Sample};
+use crate::crypto::{
+	hash::ArityHash,
+	rand::{Rand, RngCore, Sample},
+};
 use alloc::vec::Vec;
 use core::{iter, mem};
 
@@ -65,9 +68,16 @@ pub trait Specification<COM = ()> {
 	/// of full rounds and then the second set after the partial rounds.
 	const FULL_ROUNDS: usize;
 
-	/// Returns the domain tag for `arity`. We use different domain tags for different applications
-	/// to defend against rainbow table attacks.
-	fn domain_tag(arity: usize, compiler: &mut COM) -> Self::Field;
+	/// Returns `value` as a constant field element.
+	fn constant(value: u128, compiler: &mut COM) -> Self::Field;
+
+	/// Returns the domain tag for `arity` in the given `domain`. We use different domain tags for
+	/// different applications to defend against rainbow table attacks. See [`domain_tag`] for the
+	/// encoding of the tag.
+	#[inline]
+	fn domain_tag(arity: usize, domain: u64, compiler: &mut COM) -> Self::Field {
+		Self::constant(domain_tag(arity, domain), compiler)
+	}
 
 	/// Adds two field elements together.
 	fn add(lhs: &Self::Field, rhs: &Self::Field, compiler: &mut COM) -> Self::Field;
@@ -91,6 +101,16 @@ pub trait Specification<COM = ()> {
 	fn apply_sbox(point: &mut Self::Field, compiler: &mut COM);
 }
 
+/// Returns the domain tag for a fixed-length hash of `arity`-many inputs in the given `domain`.
+///
+/// The tag is `domain * 2^96 + (2^arity - 1)`, so the default domain `0` has the tag `2^arity - 1`
+/// and no tag in any domain collides with the variable-length [`Sponge`] tags of the form
+/// `2^64 + (outputs - 1)`.
+#[inline]
+pub fn domain_tag(arity: usize, domain: u64) -> u128 {
+	((domain as u128) << 96) | ((1 << arity) - 1)
+}
+
 /// Poseidon State Vector
 type State<S, COM> = Vec<<S as Specification<COM>>::Field>;
 
@@ -103,7 +123,53 @@ where
 	S::FULL_ROUNDS + S::PARTIAL_ROUNDS
 }
 
+/// Returns the sum of the products of the entries of `row` with the elements of `state`, using
+/// `mul_const` and `add` for the field arithmetic.
+///
+/// This is shared with [`rescue`](super::rescue), whose specification has its own arithmetic.
+#[inline]
+pub(crate) fn dot_product<F, P, COM>(
+	row: &[P],
+	state: &[F],
+	mul_const: fn(&F, &P, &mut COM) -> F,
+	add: fn(&F, &F, &mut COM) -> F,
+	compiler: &mut COM,
+) -> F {
+	// NOTE: clippy false-positive: Without `collect`, the two closures in `map` and `reduce` will
+	//       have simultaneous `&mut` access to `compiler`. Adding `collect` allows `map` to be done
+	//       before `reduce`.
+	#[allow(clippy::needless_collect)]
+	let linear_combination = state
+		.iter()
+		.zip(row)
+		.map(|(elem, entry)| mul_const(elem, entry, compiler))
+		.collect::<Vec<_>>();
+	linear_combination
+		.into_iter()
+		.reduce(|acc, next| add(&acc, &next, compiler))
+		.unwrap()
+}
+
+/// Computes the multiplication of the square `matrix`, stored in row-major order, against the
+/// `state`, using `mul_const` and `add` for the field arithmetic.
+#[inline]
+pub(crate) fn matrix_multiply<F, P, COM>(
+	matrix: &[P],
+	state: &mut Vec<F>,
+	mul_const: fn(&F, &P, &mut COM) -> F,
+	add: fn(&F, &F, &mut COM) -> F,
+	compiler: &mut COM,
+) {
+	let mut next = matrix
+		.chunks(state.len())
+		.map(|row| dot_product(row, state, mul_const, add, compiler))
+		.collect::<Vec<_>>();
+	mem::swap(&mut next, state);
+}
+
 /// Poseidon Hasher
+#[derive(derivative::Derivative)]
+#[derivative(Clone(bound = "S::ParameterField: Clone"))]
 pub struct Hasher<S, const ARITY: usize, COM = ()>
 where
 	S: Specification<COM>,
@@ -182,33 +248,20 @@ where
 	/// Computes the MDS matrix multiplication against the `state`.
 	#[inline]
 	fn mds_matrix_multiply(&self, state: &mut State<S, COM>, compiler: &mut COM) {
-		let width = Self::WIDTH;
-		let mut next = Vec::with_capacity(width);
-		for i in 0..width {
-			// NOTE: clippy false-positive: Without `collect`, the two closures in `map` and
-			//       `reduce` will have simultaneous `&mut` access to `compiler`. Adding `collect`
-			//       allows `map` to be done before `reduce`.
-			#[allow(clippy::needless_collect)]
-			let linear_combination = state
-				.iter()
-				.enumerate()
-				.map(|(j, elem)| S::mul_const(elem, &self.mds_matrix[width * i + j], compiler))
-				.collect::<Vec<_>>();
-			next.push(
-				linear_combination
-					.into_iter()
-					.reduce(|acc, next| S::add(&acc, &next, compiler))
-					.unwrap(),
-			);
-		}
-		mem::swap(&mut next, state);
+		matrix_multiply(&self.mds_matrix, state, S::mul_const, S::add, compiler)
 	}
 
-	/// Computes the first round of the Poseidon permutation from `trapdoor` and `input`.
+	/// Computes the first round of the Poseidon permutation from the tag of `domain` and `input`.
 	#[inline]
-	fn first_round(&self, input: [&S::Field; ARITY], compiler: &mut COM) -> State<S, COM> {
+	fn first_round(
+		&self,
+		domain: u64,
+		input: [&S::Field; ARITY],
+		compiler: &mut COM,
+	) -> State<S, COM> {
 		let mut state = Vec::with_capacity(Self::WIDTH);
-		for (i, point) in iter::once(&S::domain_tag(ARITY, compiler)).chain(input).enumerate() {
+		let tag = S::domain_tag(ARITY, domain, compiler);
+		for (i, point) in iter::once(&tag).chain(input).enumerate() {
 			let mut elem = S::add_const(point, &self.additive_round_keys[i], compiler);
 			S::apply_sbox(&mut elem, compiler);
 			state.push(elem);
@@ -239,33 +292,214 @@ where
 		self.mds_matrix_multiply(state, compiler);
 	}
 
-	/// Computes the hash over `input` in the given `compiler` and returns the untruncated state.
+	/// Computes every round after the first round of the Poseidon permutation on `state`.
 	#[inline]
-	fn hash_untruncated(&self, input: [&S::Field; ARITY], compiler: &mut COM) -> Vec<S::Field> {
-		let mut state = self.first_round(input, compiler);
+	fn remaining_rounds(&self, state: &mut State<S, COM>, compiler: &mut COM) {
 		for round in 1..Self::HALF_FULL_ROUNDS {
-			self.full_round(round, &mut state, compiler);
+			self.full_round(round, state, compiler);
 		}
 		for round in Self::HALF_FULL_ROUNDS..(Self::HALF_FULL_ROUNDS + S::PARTIAL_ROUNDS) {
-			self.partial_round(round, &mut state, compiler);
+			self.partial_round(round, state, compiler);
 		}
 		for round in
 			(Self::HALF_FULL_ROUNDS + S::PARTIAL_ROUNDS)..(S::FULL_ROUNDS + S::PARTIAL_ROUNDS)
 		{
-			self.full_round(round, &mut state, compiler);
+			self.full_round(round, state, compiler);
 		}
+	}
+
+	/// Applies the Poseidon permutation to `state`.
+	///
+	/// # Panics
+	///
+	/// This method panics if `state` does not have [`WIDTH`](Self::WIDTH)-many elements.
+	#[inline]
+	pub fn permute(&self, state: &mut Vec<S::Field>, compiler: &mut COM) {
+		assert_eq!(state.len(), Self::WIDTH, "State is not the correct size.");
+		self.full_round(0, state, compiler);
+		self.remaining_rounds(state, compiler);
+	}
+
+	/// Computes the hash over `input` in the given `domain` and `compiler` and returns the
+	/// untruncated state.
+	#[inline]
+	fn hash_untruncated(
+		&self,
+		domain: u64,
+		input: [&S::Field; ARITY],
+		compiler: &mut COM,
+	) -> Vec<S::Field> {
+		let mut state = self.first_round(domain, input, compiler);
+		self.remaining_rounds(&mut state, compiler);
 		state
 	}
 
-	/// Computes the hash over `input` in the given `compiler`.
+	/// Computes the hash over `input` in the given `compiler`, using the default domain `0`.
 	#[inline]
 	pub fn hash(&self, input: [&S::Field; ARITY], compiler: &mut COM) -> S::Field {
-		let mut state = self.hash_untruncated(input, compiler);
+		self.hash_in_domain(0, input, compiler)
+	}
+
+	/// Computes the hash over `input` in the given `domain` and `compiler`. Hashes in different
+	/// domains are independent even when they share the same parameters.
+	#[inline]
+	pub fn hash_in_domain(
+		&self,
+		domain: u64,
+		input: [&S::Field; ARITY],
+		compiler: &mut COM,
+	) -> S::Field {
+		let mut state = self.hash_untruncated(domain, input, compiler);
 		state.truncate(1);
 		state.remove(0)
 	}
 }
 
+impl<S, const ARITY: usize, COM> ArityHash<ARITY, COM> for Hasher<S, ARITY, COM>
+where
+	S: Specification<COM>,
+{
+	type Input = S::Field;
+	type Output = S::Field;
+
+	#[inline]
+	fn hash_in_domain(
+		&self,
+		domain: u64,
+		input: [&Self::Input; ARITY],
+		compiler: &mut COM,
+	) -> Self::Output {
+		Hasher::hash_in_domain(self, domain, input, compiler)
+	}
+}
+
+impl<S, const ARITY: usize, COM> Hasher<S, ARITY, COM>
+where
+	S: Specification<COM>,
+	S::Field: Clone,
+{
+	/// Starts a new [`Sponge`] over the permutation of `self` which is domain separated for
+	/// `outputs`-many output elements.
+	#[inline]
+	pub fn sponge(&self, outputs: usize, compiler: &mut COM) -> Sponge<'_, S, ARITY, COM> {
+		Sponge::new(self, outputs, compiler)
+	}
+
+	/// Computes the hash over an arbitrary number of `input` elements in the given `compiler` by
+	/// absorbing them into a [`Sponge`] and squeezing out a single element.
+	#[inline]
+	pub fn hash_variable_length<'i, I>(&self, input: I, compiler: &mut COM) -> S::Field
+	where
+		S::Field: 'i,
+		I: IntoIterator<Item = &'i S::Field>,
+	{
+		let mut sponge = self.sponge(1, compiler);
+		sponge.absorb_all(input, compiler);
+		sponge.squeeze(compiler)
+	}
+}
+
+/// Poseidon Sponge
+///
+/// The sponge uses the first element of the permutation state as its capacity and the remaining
+/// `ARITY` elements as its rate. The capacity is initialized with the variable-length domain tag
+/// `2^64 + (outputs - 1)` and the absorbed input is padded with a single one followed by zeros, so
+/// that inputs of different lengths and sponges with different numbers of outputs never collide.
+pub struct Sponge<'h, S, const ARITY: usize, COM = ()>
+where
+	S: Specification<COM>,
+{
+	/// Hasher
+	hasher: &'h Hasher<S, ARITY, COM>,
+
+	/// Permutation State
+	state: State<S, COM>,
+
+	/// Position in the Rate
+	position: usize,
+
+	/// Squeezing Flag
+	squeezing: bool,
+}
+
+impl<'h, S, const ARITY: usize, COM> Sponge<'h, S, ARITY, COM>
+where
+	S: Specification<COM>,
+	S::Field: Clone,
+{
+	/// Builds a new [`Sponge`] over the permutation of `hasher` which is domain separated for
+	/// `outputs`-many output elements.
+	///
+	/// # Panics
+	///
+	/// This method panics if `outputs` is zero.
+	#[inline]
+	pub fn new(hasher: &'h Hasher<S, ARITY, COM>, outputs: usize, compiler: &mut COM) -> Self {
+		assert!(outputs > 0, "Sponges must squeeze at least one output.");
+		let mut state = Vec::with_capacity(Hasher::<S, ARITY, COM>::WIDTH);
+		state.push(S::constant((1 << 64) + (outputs as u128 - 1), compiler));
+		for _ in 0..ARITY {
+			state.push(S::constant(0, compiler));
+		}
+		Self { hasher, state, position: 0, squeezing: false }
+	}
+
+	/// Permutes the state if the rate is full, returning the position of the next free element.
+	#[inline]
+	fn next_position(&mut self, compiler: &mut COM) -> usize {
+		if self.position == ARITY {
+			self.hasher.permute(&mut self.state, compiler);
+			self.position = 0;
+		}
+		self.position += 1;
+		self.position
+	}
+
+	/// Absorbs `input` into the sponge.
+	///
+	/// # Panics
+	///
+	/// This method panics if the sponge has already started squeezing.
+	#[inline]
+	pub fn absorb(&mut self, input: &S::Field, compiler: &mut COM) {
+		assert!(!self.squeezing, "Absorbing after squeezing is not allowed.");
+		let position = self.next_position(compiler);
+		S::add_assign(&mut self.state[position], input, compiler);
+	}
+
+	/// Absorbs every element of `input` into the sponge.
+	///
+	/// # Panics
+	///
+	/// This method panics if the sponge has already started squeezing.
+	#[inline]
+	pub fn absorb_all<'i, I>(&mut self, input: I, compiler: &mut COM)
+	where
+		S::Field: 'i,
+		I: IntoIterator<Item = &'i S::Field>,
+	{
+		for point in input {
+			self.absorb(point, compiler);
+		}
+	}
+
+	/// Squeezes the next output element out of the sponge, padding the absorbed input the first
+	/// time this method is called.
+	#[inline]
+	pub fn squeeze(&mut self, compiler: &mut COM) -> S::Field {
+		if !self.squeezing {
+			let position = self.next_position(compiler);
+			let one = S::constant(1, compiler);
+			S::add_assign(&mut self.state[position], &one, compiler);
+			self.hasher.permute(&mut self.state, compiler);
+			self.position = 0;
+			self.squeezing = true;
+		}
+		let position = self.next_position(compiler);
+		self.state[position].clone()
+	}
+}
+
 impl<D, S, const ARITY: usize, COM> Sample<D> for Hasher<S, ARITY, COM>
 where
 	D: Clone,
@@ -296,6 +530,365 @@ where
 	}
 }
 
+/// Returns the product of the square matrices `lhs` and `rhs` of the given `width`, stored in
+/// row-major order.
+#[inline]
+fn matrix_product<F>(lhs: &[F], rhs: &[F], width: usize) -> Vec<F>
+where
+	F: Field,
+{
+	let mut product = Vec::with_capacity(width * width);
+	for i in 0..width {
+		for j in 0..width {
+			let mut entry = F::zero();
+			for k in 0..width {
+				entry.add_assign(&F::mul(&lhs[width * i + k], &rhs[width * k + j]));
+			}
+			product.push(entry);
+		}
+	}
+	product
+}
+
+/// Returns the inverse of the square `matrix` of the given `width`, stored in row-major order,
+/// returning `None` if `matrix` is not invertible.
+#[inline]
+fn matrix_inverse<F>(matrix: &[F], width: usize) -> Option<Vec<F>>
+where
+	F: Clone + Field,
+{
+	let mut matrix = matrix.to_vec();
+	let mut inverse = (0..width * width)
+		.map(|i| if i / width == i % width { F::one() } else { F::zero() })
+		.collect::<Vec<_>>();
+	for column in 0..width {
+		let pivot = (column..width).find(|row| !matrix[width * row + column].is_zero())?;
+		for k in 0..width {
+			matrix.swap(width * pivot + k, width * column + k);
+			inverse.swap(width * pivot + k, width * column + k);
+		}
+		let scale = matrix[width * column + column].inverse()?;
+		for k in 0..width {
+			matrix[width * column + k] = F::mul(&matrix[width * column + k], &scale);
+			inverse[width * column + k] = F::mul(&inverse[width * column + k], &scale);
+		}
+		for row in (0..width).filter(|row| *row != column) {
+			let factor = matrix[width * row + column].clone();
+			if factor.is_zero() {
+				continue;
+			}
+			for k in 0..width {
+				matrix[width * row + k] =
+					F::sub(&matrix[width * row + k], &F::mul(&factor, &matrix[width * column + k]));
+				inverse[width * row + k] = F::sub(
+					&inverse[width * row + k],
+					&F::mul(&factor, &inverse[width * column + k]),
+				);
+			}
+		}
+	}
+	Some(inverse)
+}
+
+/// Sparse Matrix
+///
+/// Sparse matrices have the form `[[a, w], [v, I]]` where `a` is a single entry, `w` is a row,
+/// `v` is a column, and `I` is the identity matrix.
+#[derive(Clone)]
+struct SparseMatrix<F> {
+	/// First Row
+	///
+	/// This is `a` followed by the entries of `w`.
+	row: Vec<F>,
+
+	/// First Column
+	///
+	/// This is the column `v`, without the entry `a` which is stored in `row`.
+	column: Vec<F>,
+}
+
+impl<F> SparseMatrix<F> {
+	/// Computes the multiplication of `self` against the `state`.
+	#[inline]
+	fn multiply<S, COM>(&self, state: &mut State<S, COM>, compiler: &mut COM)
+	where
+		S: Specification<COM, ParameterField = F>,
+	{
+		let first = dot_product(&self.row, state, S::mul_const, S::add, compiler);
+		let (head, tail) = state.split_at_mut(1);
+		for (elem, entry) in tail.iter_mut().zip(&self.column) {
+			let product = S::mul_const(&head[0], entry, compiler);
+			S::add_assign(elem, &product, compiler);
+		}
+		head[0] = first;
+	}
+}
+
+/// Optimized Poseidon Hasher
+///
+/// Computes the same permutation as the [`Hasher`] it is built from, but with fewer operations in
+/// the partial rounds. Since only the first element of the state goes through the S-BOX in a
+/// partial round, the round keys of the other elements are moved into the round which follows it,
+/// so that every partial round only has one round key. The MDS matrix `M` of every partial round
+/// is factored into `A * diag(1, N)` where `A` is a sparse matrix, and the `diag(1, N)` part
+/// commutes with the partial round before it, so it is merged into the matrix of the previous
+/// round. The last full round before the partial rounds ends up with a dense matrix which absorbs
+/// all of the merged factors.
+pub struct OptimizedHasher<S, const ARITY: usize, COM = ()>
+where
+	S: Specification<COM>,
+{
+	/// Additive Round Keys
+	///
+	/// Full rounds have [`WIDTH`](Self::WIDTH)-many keys while partial rounds have one key each.
+	additive_round_keys: Vec<S::ParameterField>,
+
+	/// MDS Matrix
+	mds_matrix: Vec<S::ParameterField>,
+
+	/// Pre-Sparse Matrix
+	///
+	/// This is the matrix of the last full round before the partial rounds.
+	pre_sparse_matrix: Vec<S::ParameterField>,
+
+	/// Sparse Matrices of the Partial Rounds
+	sparse_matrices: Vec<SparseMatrix<S::ParameterField>>,
+}
+
+impl<S, const ARITY: usize, COM> OptimizedHasher<S, ARITY, COM>
+where
+	S: Specification<COM>,
+{
+	/// Width of the State Buffer
+	pub const WIDTH: usize = ARITY + 1;
+
+	/// Half Number of Full Rounds
+	pub const HALF_FULL_ROUNDS: usize = S::FULL_ROUNDS / 2;
+
+	/// Returns the additive keys for the full round with the given `round` index.
+	#[inline]
+	fn full_round_keys(&self, round: usize) -> &[S::ParameterField] {
+		let width = Self::WIDTH;
+		let start = if round < Self::HALF_FULL_ROUNDS {
+			round * width
+		} else {
+			round * width - S::PARTIAL_ROUNDS * (width - 1)
+		};
+		&self.additive_round_keys[start..start + width]
+	}
+
+	/// Returns the matrix for the full round with the given `round` index.
+	#[inline]
+	fn full_round_matrix(&self, round: usize) -> &[S::ParameterField] {
+		if round + 1 == Self::HALF_FULL_ROUNDS {
+			&self.pre_sparse_matrix
+		} else {
+			&self.mds_matrix
+		}
+	}
+
+	/// Computes the first round of the Poseidon permutation from the tag of `domain` and `input`.
+	#[inline]
+	fn first_round(
+		&self,
+		domain: u64,
+		input: [&S::Field; ARITY],
+		compiler: &mut COM,
+	) -> State<S, COM> {
+		let mut state = Vec::with_capacity(Self::WIDTH);
+		let tag = S::domain_tag(ARITY, domain, compiler);
+		let keys = self.full_round_keys(0);
+		for (i, point) in iter::once(&tag).chain(input).enumerate() {
+			let mut elem = S::add_const(point, &keys[i], compiler);
+			S::apply_sbox(&mut elem, compiler);
+			state.push(elem);
+		}
+		matrix_multiply(self.full_round_matrix(0), &mut state, S::mul_const, S::add, compiler);
+		state
+	}
+
+	/// Computes a full round at the given `round` index on the internal permutation `state`.
+	#[inline]
+	fn full_round(&self, round: usize, state: &mut State<S, COM>, compiler: &mut COM) {
+		let keys = self.full_round_keys(round);
+		for (i, elem) in state.iter_mut().enumerate() {
+			S::add_const_assign(elem, &keys[i], compiler);
+			S::apply_sbox(elem, compiler);
+		}
+		matrix_multiply(self.full_round_matrix(round), state, S::mul_const, S::add, compiler);
+	}
+
+	/// Computes a partial round at the given `round` index on the internal permutation `state`.
+	#[inline]
+	fn partial_round(&self, round: usize, state: &mut State<S, COM>, compiler: &mut COM) {
+		let index = round - Self::HALF_FULL_ROUNDS;
+		S::add_const_assign(
+			&mut state[0],
+			&self.additive_round_keys[Self::HALF_FULL_ROUNDS * Self::WIDTH + index],
+			compiler,
+		);
+		S::apply_sbox(&mut state[0], compiler);
+		self.sparse_matrices[index].multiply::<S, COM>(state, compiler);
+	}
+
+	/// Computes every round after the first round of the Poseidon permutation on `state`.
+	#[inline]
+	fn remaining_rounds(&self, state: &mut State<S, COM>, compiler: &mut COM) {
+		for round in 1..Self::HALF_FULL_ROUNDS {
+			self.full_round(round, state, compiler);
+		}
+		for round in Self::HALF_FULL_ROUNDS..(Self::HALF_FULL_ROUNDS + S::PARTIAL_ROUNDS) {
+			self.partial_round(round, state, compiler);
+		}
+		for round in
+			(Self::HALF_FULL_ROUNDS + S::PARTIAL_ROUNDS)..(S::FULL_ROUNDS + S::PARTIAL_ROUNDS)
+		{
+			self.full_round(round, state, compiler);
+		}
+	}
+
+	/// Applies the Poseidon permutation to `state`.
+	///
+	/// # Panics
+	///
+	/// This method panics if `state` does not have [`WIDTH`](Self::WIDTH)-many elements.
+	#[inline]
+	pub fn permute(&self, state: &mut Vec<S::Field>, compiler: &mut COM) {
+		assert_eq!(state.len(), Self::WIDTH, "State is not the correct size.");
+		self.full_round(0, state, compiler);
+		self.remaining_rounds(state, compiler);
+	}
+
+	/// Computes the hash over `input` in the given `compiler`, using the default domain `0`.
+	#[inline]
+	pub fn hash(&self, input: [&S::Field; ARITY], compiler: &mut COM) -> S::Field {
+		self.hash_in_domain(0, input, compiler)
+	}
+
+	/// Computes the hash over `input` in the given `domain` and `compiler`, which is equal to
+	/// [`Hasher::hash_in_domain`] for the hasher that `self` was built from.
+	#[inline]
+	pub fn hash_in_domain(
+		&self,
+		domain: u64,
+		input: [&S::Field; ARITY],
+		compiler: &mut COM,
+	) -> S::Field {
+		let mut state = self.first_round(domain, input, compiler);
+		self.remaining_rounds(&mut state, compiler);
+		state.truncate(1);
+		state.remove(0)
+	}
+}
+
+impl<S, const ARITY: usize, COM> ArityHash<ARITY, COM> for OptimizedHasher<S, ARITY, COM>
+where
+	S: Specification<COM>,
+{
+	type Input = S::Field;
+	type Output = S::Field;
+
+	#[inline]
+	fn hash_in_domain(
+		&self,
+		domain: u64,
+		input: [&Self::Input; ARITY],
+		compiler: &mut COM,
+	) -> Self::Output {
+		OptimizedHasher::hash_in_domain(self, domain, input, compiler)
+	}
+}
+
+impl<S, const ARITY: usize, COM> OptimizedHasher<S, ARITY, COM>
+where
+	S: Specification<COM>,
+	S::ParameterField: Clone + Field,
+{
+	/// Builds a new [`OptimizedHasher`] which computes the same permutation as `hasher`, returning
+	/// `None` if the MDS matrix of `hasher` without its first row and column is not invertible.
+	///
+	/// # Panics
+	///
+	/// This method panics if the [`Specification`] has fewer than two full rounds.
+	#[inline]
+	pub fn new(hasher: &Hasher<S, ARITY, COM>) -> Option<Self> {
+		assert!(
+			Self::HALF_FULL_ROUNDS > 0,
+			"Specifications with fewer than two full rounds are not allowed to be optimized."
+		);
+		let width = Self::WIDTH;
+		let inner_width = width - 1;
+		let mds_matrix = &hasher.mds_matrix;
+		let partial_rounds = Self::HALF_FULL_ROUNDS..(Self::HALF_FULL_ROUNDS + S::PARTIAL_ROUNDS);
+		let mut keys = hasher.additive_round_keys.clone();
+		for round in partial_rounds.clone() {
+			let (current, next) = keys.split_at_mut((round + 1) * width);
+			let current = &current[round * width..];
+			for (i, key) in next[..width].iter_mut().enumerate() {
+				for j in 1..width {
+					key.add_assign(&Field::mul(&mds_matrix[width * i + j], &current[j]));
+				}
+			}
+		}
+		let additive_round_keys = keys
+			.chunks(width)
+			.enumerate()
+			.flat_map(
+				|(round, keys)| {
+					if partial_rounds.contains(&round) {
+						&keys[..1]
+					} else {
+						keys
+					}
+				},
+			)
+			.cloned()
+			.collect();
+		let mut matrix = mds_matrix.clone();
+		let mut sparse_matrices = Vec::with_capacity(S::PARTIAL_ROUNDS);
+		for _ in partial_rounds {
+			let inner = matrix
+				.chunks(width)
+				.skip(1)
+				.flat_map(|row| row[1..].iter().cloned())
+				.collect::<Vec<_>>();
+			let inner_inverse = matrix_inverse(&inner, inner_width)?;
+			let mut row = Vec::with_capacity(width);
+			row.push(matrix[0].clone());
+			for k in 0..inner_width {
+				let mut entry = S::ParameterField::zero();
+				for j in 0..inner_width {
+					entry.add_assign(&Field::mul(
+						&matrix[1 + j],
+						&inner_inverse[inner_width * j + k],
+					));
+				}
+				row.push(entry);
+			}
+			let column = matrix.chunks(width).skip(1).map(|row| row[0].clone()).collect();
+			sparse_matrices.push(SparseMatrix { row, column });
+			let mut factor = Vec::with_capacity(width * width);
+			for i in 0..width {
+				for j in 0..width {
+					factor.push(match (i, j) {
+						(0, 0) => S::ParameterField::one(),
+						(0, _) | (_, 0) => S::ParameterField::zero(),
+						_ => inner[inner_width * (i - 1) + (j - 1)].clone(),
+					});
+				}
+			}
+			matrix = matrix_product(&factor, mds_matrix, width);
+		}
+		sparse_matrices.reverse();
+		Some(Self {
+			additive_round_keys,
+			mds_matrix: mds_matrix.clone(),
+			pre_sparse_matrix: matrix,
+			sparse_matrices,
+		})
+	}
+}
+
 /// Arkworks Backend
 pub mod arkworks {
 	use super::*;
@@ -304,10 +897,7 @@ pub mod arkworks {
 		eclair::alloc::Constant,
 	};
 	use ark_ff::{BigInteger, Field, FpParameters, PrimeField};
-	use ark_r1cs_std::{
-		alloc::AllocVar,
-		fields::{fp::FpVar, FieldVar},
-	};
+	use ark_r1cs_std::fields::{fp::FpVar, FieldVar};
 
 	/// Compiler Type
 	type Compiler<S> = R1CS<<S as Specification>::Field>;
@@ -403,8 +993,8 @@ pub mod arkworks {
 		const FULL_ROUNDS: usize = S::FULL_ROUNDS;
 
 		#[inline]
-		fn domain_tag(arity: usize, _: &mut ()) -> Self::Field {
-			Fp(S::Field::from(((1 << arity) - 1) as u64))
+		fn constant(value: u128, _: &mut ()) -> Self::Field {
+			Fp(S::Field::from(value))
 		}
 
 		#[inline]
@@ -454,11 +1044,8 @@ pub mod arkworks {
 		const FULL_ROUNDS: usize = S::FULL_ROUNDS;
 
 		#[inline]
-		fn domain_tag(arity: usize, compiler: &mut Compiler<S>) -> Self::Field {
-			FpVar::new_witness(compiler.cs.clone(), || {
-				Ok(S::Field::from(((1 << arity) - 1) as u64))
-			})
-			.unwrap()
+		fn constant(value: u128, _: &mut Compiler<S>) -> Self::Field {
+			FpVar::Constant(S::Field::from(value))
 		}
 
 		#[inline]
@@ -526,4 +1113,238 @@ pub mod arkworks {
 			}
 		}
 	}
+
+	impl<S, const ARITY: usize> Constant<Compiler<S>> for super::OptimizedHasher<S, ARITY, Compiler<S>>
+	where
+		S: Specification,
+	{
+		type Type = super::OptimizedHasher<S, ARITY>;
+
+		#[inline]
+		fn new_constant(this: &Self::Type, compiler: &mut Compiler<S>) -> Self {
+			let _ = compiler;
+			Self {
+				additive_round_keys: this.additive_round_keys.clone(),
+				mds_matrix: this.mds_matrix.clone(),
+				pre_sparse_matrix: this.pre_sparse_matrix.clone(),
+				sparse_matrices: this.sparse_matrices.clone(),
+			}
+		}
+	}
+}
+
+/// Cost Estimation Backend
+pub mod cost {
+	use super::arkworks::Specification;
+	use crate::crypto::{
+		arkworks::Fp,
+		cost::{CostCompiler, FieldVar},
+		eclair::alloc::Constant,
+	};
+
+	impl<S> super::Specification<CostCompiler> for S
+	where
+		S: Specification,
+	{
+		type Field = FieldVar<Fp<S::Field>>;
+		type ParameterField = Fp<S::Field>;
+
+		const PARTIAL_ROUNDS: usize = S::PARTIAL_ROUNDS;
+		const FULL_ROUNDS: usize = S::FULL_ROUNDS;
+
+		#[inline]
+		fn constant(value: u128, _: &mut CostCompiler) -> Self::Field {
+			let _ = value;
+			FieldVar::constant()
+		}
+
+		#[inline]
+		fn add(lhs: &Self::Field, rhs: &Self::Field, compiler: &mut CostCompiler) -> Self::Field {
+			lhs.add(rhs, compiler)
+		}
+
+		#[inline]
+		fn add_const(
+			lhs: &Self::Field,
+			rhs: &Self::ParameterField,
+			compiler: &mut CostCompiler,
+		) -> Self::Field {
+			let _ = rhs;
+			lhs.add(&FieldVar::constant(), compiler)
+		}
+
+		#[inline]
+		fn mul(lhs: &Self::Field, rhs: &Self::Field, compiler: &mut CostCompiler) -> Self::Field {
+			lhs.mul(rhs, compiler)
+		}
+
+		#[inline]
+		fn mul_const(
+			lhs: &Self::Field,
+			rhs: &Self::ParameterField,
+			compiler: &mut CostCompiler,
+		) -> Self::Field {
+			let _ = rhs;
+			lhs.mul(&FieldVar::constant(), compiler)
+		}
+
+		#[inline]
+		fn add_assign(lhs: &mut Self::Field, rhs: &Self::Field, compiler: &mut CostCompiler) {
+			*lhs = lhs.add(rhs, compiler);
+		}
+
+		#[inline]
+		fn add_const_assign(
+			lhs: &mut Self::Field,
+			rhs: &Self::ParameterField,
+			compiler: &mut CostCompiler,
+		) {
+			let _ = rhs;
+			*lhs = lhs.add(&FieldVar::constant(), compiler);
+		}
+
+		#[inline]
+		fn apply_sbox(point: &mut Self::Field, compiler: &mut CostCompiler) {
+			*point = point.pow(S::SBOX_EXPONENT, compiler);
+		}
+	}
+
+	impl<S, const ARITY: usize> Constant<CostCompiler> for super::Hasher<S, ARITY, CostCompiler>
+	where
+		S: Specification,
+	{
+		type Type = super::Hasher<S, ARITY>;
+
+		#[inline]
+		fn new_constant(this: &Self::Type, compiler: &mut CostCompiler) -> Self {
+			let _ = compiler;
+			Self {
+				additive_round_keys: this.additive_round_keys.clone(),
+				mds_matrix: this.mds_matrix.clone(),
+			}
+		}
+	}
+
+	impl<S, const ARITY: usize> Constant<CostCompiler>
+		for super::OptimizedHasher<S, ARITY, CostCompiler>
+	where
+		S: Specification,
+	{
+		type Type = super::OptimizedHasher<S, ARITY>;
+
+		#[inline]
+		fn new_constant(this: &Self::Type, compiler: &mut CostCompiler) -> Self {
+			let _ = compiler;
+			Self {
+				additive_round_keys: this.additive_round_keys.clone(),
+				mds_matrix: this.mds_matrix.clone(),
+				pre_sparse_matrix: this.pre_sparse_matrix.clone(),
+				sparse_matrices: this.sparse_matrices.clone(),
+			}
+		}
+	}
+}
+
+/// Testing Framework
+pub mod test {
+	use super::*;
+	use crate::crypto::{
+		arkworks::{Fp, FpVar, R1CS},
+		cost::{self, CostCompiler},
+		eclair::alloc::{mode::Secret, Allocate, Allocator},
+	};
+	use ark_r1cs_std::R1CSVar;
+	use core::{array, fmt::Debug};
+
+	/// Asserts that the [`OptimizedHasher`] built from a random [`Hasher`] computes the same
+	/// permutation and the same hashes in every domain.
+	#[inline]
+	pub fn assert_optimized_equivalence<S, const ARITY: usize, R>(rng: &mut R)
+	where
+		S: Specification,
+		S::Field: Clone + Debug + PartialEq + Sample,
+		S::ParameterField: Clone + Field + Sample,
+		R: RngCore + ?Sized,
+	{
+		let hasher = rng.gen::<_, Hasher<S, ARITY>>();
+		let optimized = OptimizedHasher::new(&hasher)
+			.expect("Random MDS matrices are invertible with overwhelming probability.");
+		let input = array::from_fn::<S::Field, ARITY, _>(|_| rng.gen());
+		for domain in [0, 1, 42] {
+			assert_eq!(
+				optimized.hash_in_domain(domain, array::from_fn(|i| &input[i]), &mut ()),
+				hasher.hash_in_domain(domain, array::from_fn(|i| &input[i]), &mut ()),
+				"Optimized hash does not match the hash in domain {:?}.",
+				domain
+			);
+		}
+		let mut state = (0..Hasher::<S, ARITY>::WIDTH).map(|_| rng.gen()).collect::<Vec<_>>();
+		let mut optimized_state = state.clone();
+		hasher.permute(&mut state, &mut ());
+		optimized.permute(&mut optimized_state, &mut ());
+		assert_eq!(optimized_state, state, "Optimized permutation does not match.");
+	}
+
+	/// Asserts that the [`OptimizedHasher`] computes the same hash as the [`Hasher`] inside of the
+	/// arkworks compiler without any extra constraints, and that its estimated cost has the same
+	/// number of multiplications and fewer linear combinations.
+	#[inline]
+	pub fn assert_optimized_circuit<S, const ARITY: usize, R>(rng: &mut R)
+	where
+		S: arkworks::Specification,
+		R: RngCore + ?Sized,
+	{
+		let hasher = rng.gen::<_, Hasher<S, ARITY>>();
+		let optimized = OptimizedHasher::new(&hasher)
+			.expect("Random MDS matrices are invertible with overwhelming probability.");
+		let input = array::from_fn::<Fp<S::Field>, ARITY, _>(|_| rng.gen());
+		let expected = hasher.hash(array::from_fn(|i| &input[i]), &mut ());
+		let mut compiler = R1CS::<S::Field>::for_prove();
+		let input_var = input.map(|x| x.as_known::<Secret, FpVar<S::Field>>(&mut compiler));
+		let hasher_var: Hasher<S, ARITY, R1CS<S::Field>> = hasher.as_constant(&mut compiler);
+		let before = compiler.num_constraints();
+		let output = hasher_var.hash(array::from_fn(|i| &input_var[i]), &mut compiler);
+		let constraints = compiler.num_constraints() - before;
+		let optimized_var: OptimizedHasher<S, ARITY, R1CS<S::Field>> =
+			optimized.as_constant(&mut compiler);
+		let before = compiler.num_constraints();
+		let optimized_output = optimized_var.hash(array::from_fn(|i| &input_var[i]), &mut compiler);
+		let optimized_constraints = compiler.num_constraints() - before;
+		assert_eq!(output.value().ok(), Some(expected.0), "Circuit hash does not match.");
+		assert_eq!(
+			optimized_output.value().ok(),
+			Some(expected.0),
+			"Optimized circuit hash does not match."
+		);
+		assert!(
+			optimized_constraints <= constraints,
+			"Optimized hash is not allowed to use more constraints: {} > {}.",
+			optimized_constraints,
+			constraints
+		);
+		assert_eq!(compiler.check(), Ok(()));
+		let mut compiler = CostCompiler::new();
+		let input_var =
+			[(); ARITY].map(|_| compiler.allocate_unknown::<Secret, cost::FieldVar<_>>());
+		let hasher_var: Hasher<S, ARITY, CostCompiler> = hasher.as_constant(&mut compiler);
+		hasher_var.hash(array::from_fn(|i| &input_var[i]), &mut compiler);
+		let cost = compiler.cost();
+		let mut compiler = CostCompiler::new();
+		let input_var =
+			[(); ARITY].map(|_| compiler.allocate_unknown::<Secret, cost::FieldVar<_>>());
+		let optimized_var: OptimizedHasher<S, ARITY, CostCompiler> =
+			optimized.as_constant(&mut compiler);
+		optimized_var.hash(array::from_fn(|i| &input_var[i]), &mut compiler);
+		let optimized_cost = compiler.cost();
+		assert_eq!(
+			optimized_cost.multiplications, cost.multiplications,
+			"Optimized hash must have the same number of multiplications."
+		);
+		assert!(
+			optimized_cost.linear_combinations < cost.linear_combinations,
+			"Optimized hash must have fewer linear combinations: {} >= {}.",
+			optimized_cost.linear_combinations,
+			cost.linear_combinations
+		);
+	}
 }
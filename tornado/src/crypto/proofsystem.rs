@@ -14,6 +14,10 @@ pub trait ProofSystem {
 
 	fn for_prove() -> Self::Compiler;
 
+	/// Checks that `compiler`, built with [`for_prove`](Self::for_prove), is satisfied by its known
+	/// variables without generating a proof.
+	fn check(compiler: &Self::Compiler) -> Result<(), Self::Error>;
+
 	fn compile<R>(
 		compiler: Self::Compiler,
 		rng: &mut R,
@@ -39,12 +43,30 @@ pub trait ProofSystem {
 /// Arkworks Backend
 pub mod arkworks {
 	use super::*;
-	use crate::crypto::arkworks::R1CS;
+	use crate::crypto::arkworks::{Fp, SynthesisError, Unsatisfied, R1CS};
+	use alloc::vec::Vec;
 	use ark_ec::PairingEngine;
 	use ark_groth16::{Groth16 as ArkGroth16, Proof, ProvingKey, VerifyingKey};
 	use ark_snark::SNARK;
 	use core::marker::PhantomData;
 
+	/// Groth16 Error
+	#[derive(Clone, Debug, Eq, PartialEq)]
+	pub enum Error {
+		/// Synthesis Error
+		Synthesis(SynthesisError),
+
+		/// Unsatisfied Constraint System
+		Unsatisfied(Unsatisfied),
+	}
+
+	impl From<SynthesisError> for Error {
+		#[inline]
+		fn from(err: SynthesisError) -> Self {
+			Self::Synthesis(err)
+		}
+	}
+
 	/// Arkworks Groth16 Proof System
 	pub struct Groth16<E>(PhantomData<E>)
 	where
@@ -57,9 +79,9 @@ pub mod arkworks {
 		type Compiler = R1CS<E::Fr>;
 		type ProvingKey = ProvingKey<E>;
 		type VerifyingKey = VerifyingKey<E>;
-		type Input = E::Fr;
+		type Input = Fp<E::Fr>;
 		type Proof = Proof<E>;
-		type Error = ();
+		type Error = Error;
 
 		#[inline]
 		fn for_compile() -> Self::Compiler {
@@ -71,6 +93,11 @@ pub mod arkworks {
 			Self::Compiler::for_prove()
 		}
 
+		#[inline]
+		fn check(compiler: &Self::Compiler) -> Result<(), Self::Error> {
+			compiler.check().map_err(Error::Unsatisfied)
+		}
+
 		#[inline]
 		fn compile<R>(
 			compiler: Self::Compiler,
@@ -80,7 +107,7 @@ pub mod arkworks {
 			R: CryptoRng + RngCore + ?Sized,
 		{
 			let (proving_key, verifying_key) =
-				ArkGroth16::circuit_specific_setup(compiler, &mut rng).map_err(|_| ())?;
+				ArkGroth16::circuit_specific_setup(compiler, &mut rng)?;
 			Ok((proving_key, verifying_key))
 		}
 
@@ -93,7 +120,9 @@ pub mod arkworks {
 		where
 			R: CryptoRng + RngCore + ?Sized,
 		{
-			ArkGroth16::prove(proving_key, compiler, &mut rng).map_err(|_| ())
+			#[cfg(feature = "debug")]
+			Self::check(&compiler)?;
+			Ok(ArkGroth16::prove(proving_key, compiler, &mut rng)?)
 		}
 
 		#[inline]
@@ -102,7 +131,8 @@ pub mod arkworks {
 			input: &[Self::Input],
 			proof: &Self::Proof,
 		) -> Result<bool, Self::Error> {
-			ArkGroth16::verify(verifying_key, input, proof).map_err(|_| ())
+			let input = input.iter().map(|x| x.0).collect::<Vec<_>>();
+			Ok(ArkGroth16::verify(verifying_key, &input, proof)?)
 		}
 	}
 }
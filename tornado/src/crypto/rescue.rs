@@ -0,0 +1,502 @@
+//! Rescue-Prime Hash Function
+//!
+//! Every round of Rescue-Prime applies the S-BOX `x^α` to the whole state, followed by the MDS
+//! matrix and a set of round constants, and then does the same with the inverse S-BOX `x^(1/α)`.
+//! The inverse S-BOX is expensive to compute natively, but inside of a circuit the root is
+//! allocated as a witness and checked by raising it back to the power `α`, so both halves of a
+//! round cost the same number of constraints. This makes Rescue-Prime an alternative to
+//! [`poseidon`](super::poseidon) with far fewer rounds.
+
+use crate::crypto::{
+	hash::ArityHash,
+	poseidon::{domain_tag, matrix_multiply},
+	rand::{Rand, RngCore, Sample},
+};
+use alloc::vec::Vec;
+use core::iter;
+
+/// Rescue-Prime Permutation Specification
+pub trait Specification<COM = ()> {
+	/// Field Type used for Permutation State
+	type Field;
+
+	/// Field Type used for Constant Parameters
+	type ParameterField;
+
+	/// Number of Rounds
+	const ROUNDS: usize;
+
+	/// Returns `value` as a constant field element.
+	fn constant(value: u128, compiler: &mut COM) -> Self::Field;
+
+	/// Adds two field elements together.
+	fn add(lhs: &Self::Field, rhs: &Self::Field, compiler: &mut COM) -> Self::Field;
+
+	/// Multiplies a field element `lhs` with a constant `rhs`
+	fn mul_const(lhs: &Self::Field, rhs: &Self::ParameterField, compiler: &mut COM) -> Self::Field;
+
+	/// Adds the `rhs` constant to `lhs` field element, updating the value in `lhs`
+	fn add_const_assign(lhs: &mut Self::Field, rhs: &Self::ParameterField, compiler: &mut COM);
+
+	/// Applies the S-BOX to `point`.
+	fn apply_sbox(point: &mut Self::Field, compiler: &mut COM);
+
+	/// Applies the inverse of the S-BOX to `point`.
+	fn apply_inverse_sbox(point: &mut Self::Field, compiler: &mut COM);
+}
+
+/// Rescue-Prime State Vector
+type State<S, COM> = Vec<<S as Specification<COM>>::Field>;
+
+/// Rescue-Prime Hasher
+pub struct Hasher<S, const ARITY: usize, COM = ()>
+where
+	S: Specification<COM>,
+{
+	/// Round Constants
+	///
+	/// Every round has two sets of constants, one for each half of the round.
+	round_constants: Vec<S::ParameterField>,
+
+	/// MDS Matrix
+	mds_matrix: Vec<S::ParameterField>,
+}
+
+impl<S, const ARITY: usize, COM> Hasher<S, ARITY, COM>
+where
+	S: Specification<COM>,
+{
+	/// Width of the State Buffer
+	pub const WIDTH: usize = ARITY + 1;
+
+	/// Number of Entries in the MDS Matrix
+	pub const MDS_MATRIX_SIZE: usize = Self::WIDTH * Self::WIDTH;
+
+	/// Total Number of Round Constants
+	pub const ROUND_CONSTANTS_COUNT: usize = 2 * S::ROUNDS * Self::WIDTH;
+
+	/// Builds a new [`Hasher`] from `round_constants` and `mds_matrix`.
+	///
+	/// # Panics
+	///
+	/// This method panics if the input vectors are not the correct size for the specified
+	/// [`Specification`].
+	#[inline]
+	pub fn new(
+		round_constants: Vec<S::ParameterField>,
+		mds_matrix: Vec<S::ParameterField>,
+	) -> Self {
+		assert_eq!(
+			round_constants.len(),
+			Self::ROUND_CONSTANTS_COUNT,
+			"Round Constants are not the correct size."
+		);
+		assert_eq!(mds_matrix.len(), Self::MDS_MATRIX_SIZE, "MDS Matrix is not the correct size.");
+		Self { round_constants, mds_matrix }
+	}
+
+	/// Computes the MDS matrix multiplication against the `state`.
+	#[inline]
+	fn mds_matrix_multiply(&self, state: &mut State<S, COM>, compiler: &mut COM) {
+		matrix_multiply(&self.mds_matrix, state, S::mul_const, S::add, compiler)
+	}
+
+	/// Adds the round constants with the given `index` to the `state`.
+	#[inline]
+	fn add_round_constants(&self, index: usize, state: &mut State<S, COM>, compiler: &mut COM) {
+		let start = index * Self::WIDTH;
+		for (elem, constant) in state.iter_mut().zip(&self.round_constants[start..]) {
+			S::add_const_assign(elem, constant, compiler);
+		}
+	}
+
+	/// Computes the round at the given `round` index on the internal permutation `state`.
+	#[inline]
+	fn round(&self, round: usize, state: &mut State<S, COM>, compiler: &mut COM) {
+		for elem in state.iter_mut() {
+			S::apply_sbox(elem, compiler);
+		}
+		self.mds_matrix_multiply(state, compiler);
+		self.add_round_constants(2 * round, state, compiler);
+		for elem in state.iter_mut() {
+			S::apply_inverse_sbox(elem, compiler);
+		}
+		self.mds_matrix_multiply(state, compiler);
+		self.add_round_constants(2 * round + 1, state, compiler);
+	}
+
+	/// Applies the Rescue-Prime permutation to `state`.
+	///
+	/// # Panics
+	///
+	/// This method panics if `state` does not have [`WIDTH`](Self::WIDTH)-many elements.
+	#[inline]
+	pub fn permute(&self, state: &mut Vec<S::Field>, compiler: &mut COM) {
+		assert_eq!(state.len(), Self::WIDTH, "State is not the correct size.");
+		for round in 0..S::ROUNDS {
+			self.round(round, state, compiler);
+		}
+	}
+}
+
+impl<S, const ARITY: usize, COM> ArityHash<ARITY, COM> for Hasher<S, ARITY, COM>
+where
+	S: Specification<COM>,
+	S::Field: Clone,
+{
+	type Input = S::Field;
+	type Output = S::Field;
+
+	/// Computes the hash over `input` in the given `domain` and `compiler`, by permuting the
+	/// domain tag followed by `input` and returning the first element after the tag.
+	#[inline]
+	fn hash_in_domain(
+		&self,
+		domain: u64,
+		input: [&Self::Input; ARITY],
+		compiler: &mut COM,
+	) -> Self::Output {
+		let mut state = iter::once(S::constant(domain_tag(ARITY, domain), compiler))
+			.chain(input.into_iter().cloned())
+			.collect();
+		self.permute(&mut state, compiler);
+		state.truncate(2);
+		state.remove(1)
+	}
+}
+
+impl<D, S, const ARITY: usize, COM> Sample<D> for Hasher<S, ARITY, COM>
+where
+	D: Clone,
+	S: Specification<COM>,
+	S::ParameterField: Sample<D>,
+{
+	/// Samples random Rescue-Prime parameters.
+	///
+	/// # Warning
+	///
+	/// This method samples the individual field elements of the parameters set, instead of
+	/// producing an actually correct/safe set of round constants and MDS matrix.
+	#[inline]
+	fn sample<R>(distribution: D, rng: &mut R) -> Self
+	where
+		R: RngCore + ?Sized,
+	{
+		Self {
+			round_constants: rng
+				.sample_iter(iter::repeat(distribution.clone()).take(Self::ROUND_CONSTANTS_COUNT))
+				.collect(),
+			mds_matrix: rng
+				.sample_iter(iter::repeat(distribution).take(Self::MDS_MATRIX_SIZE))
+				.collect(),
+		}
+	}
+}
+
+/// Arkworks Backend
+pub mod arkworks {
+	use crate::crypto::{
+		arkworks::{Fp, R1CS},
+		eclair::alloc::Constant,
+	};
+	use alloc::vec::Vec;
+	use ark_ff::{Field, FpParameters, PrimeField};
+	use ark_r1cs_std::{
+		alloc::AllocVar,
+		eq::EqGadget,
+		fields::{fp::FpVar, FieldVar},
+		R1CSVar,
+	};
+	use ark_relations::ns;
+
+	/// Compiler Type
+	type Compiler<S> = R1CS<<S as Specification>::Field>;
+
+	/// Rescue-Prime Permutation Specification
+	pub trait Specification {
+		/// Field Type
+		type Field: PrimeField;
+
+		/// Number of Rounds
+		const ROUNDS: usize;
+
+		/// S-BOX Exponent
+		///
+		/// # Contract
+		///
+		/// The exponent must be coprime to `p - 1` where `p` is the modulus of the field, so that
+		/// the S-BOX is invertible.
+		const SBOX_EXPONENT: u64;
+	}
+
+	/// Returns the exponent of the inverse S-BOX of `S`, which is the inverse of the S-BOX exponent
+	/// modulo `p - 1`, as little-endian limbs.
+	///
+	/// # Panics
+	///
+	/// This function panics if the S-BOX exponent is not coprime to `p - 1`.
+	#[inline]
+	fn inverse_sbox_exponent<S>() -> Vec<u64>
+	where
+		S: Specification,
+	{
+		let exponent = S::SBOX_EXPONENT as u128;
+		let mut order = <S::Field as PrimeField>::Params::MODULUS.as_ref().to_vec();
+		order[0] -= 1;
+		order.push(0);
+		for multiple in 1..exponent {
+			let mut carry = 1;
+			let mut numerator = order
+				.iter()
+				.map(|limb| {
+					let value = (*limb as u128) * multiple + carry;
+					carry = value >> 64;
+					value as u64
+				})
+				.collect::<Vec<_>>();
+			let mut remainder = 0;
+			for limb in numerator.iter_mut().rev() {
+				let value = (remainder << 64) | (*limb as u128);
+				*limb = (value / exponent) as u64;
+				remainder = value % exponent;
+			}
+			if remainder == 0 {
+				return numerator
+			}
+		}
+		panic!("S-BOX exponents are not allowed to share a factor with the order of the field.")
+	}
+
+	impl<S> super::Specification for S
+	where
+		S: Specification,
+	{
+		type Field = Fp<S::Field>;
+		type ParameterField = Fp<S::Field>;
+
+		const ROUNDS: usize = S::ROUNDS;
+
+		#[inline]
+		fn constant(value: u128, _: &mut ()) -> Self::Field {
+			Fp(S::Field::from(value))
+		}
+
+		#[inline]
+		fn add(lhs: &Self::Field, rhs: &Self::Field, _: &mut ()) -> Self::Field {
+			Fp(lhs.0 + rhs.0)
+		}
+
+		#[inline]
+		fn mul_const(lhs: &Self::Field, rhs: &Self::ParameterField, _: &mut ()) -> Self::Field {
+			Fp(lhs.0 * rhs.0)
+		}
+
+		#[inline]
+		fn add_const_assign(lhs: &mut Self::Field, rhs: &Self::ParameterField, _: &mut ()) {
+			lhs.0 += rhs.0;
+		}
+
+		#[inline]
+		fn apply_sbox(point: &mut Self::Field, _: &mut ()) {
+			point.0 = point.0.pow(&[S::SBOX_EXPONENT]);
+		}
+
+		#[inline]
+		fn apply_inverse_sbox(point: &mut Self::Field, _: &mut ()) {
+			point.0 = point.0.pow(inverse_sbox_exponent::<S>());
+		}
+	}
+
+	impl<S> super::Specification<Compiler<S>> for S
+	where
+		S: Specification,
+	{
+		type Field = FpVar<S::Field>;
+		type ParameterField = Fp<S::Field>;
+
+		const ROUNDS: usize = S::ROUNDS;
+
+		#[inline]
+		fn constant(value: u128, _: &mut Compiler<S>) -> Self::Field {
+			FpVar::Constant(S::Field::from(value))
+		}
+
+		#[inline]
+		fn add(lhs: &Self::Field, rhs: &Self::Field, _: &mut Compiler<S>) -> Self::Field {
+			lhs + rhs
+		}
+
+		#[inline]
+		fn mul_const(
+			lhs: &Self::Field,
+			rhs: &Self::ParameterField,
+			_: &mut Compiler<S>,
+		) -> Self::Field {
+			lhs * FpVar::Constant(rhs.0)
+		}
+
+		#[inline]
+		fn add_const_assign(
+			lhs: &mut Self::Field,
+			rhs: &Self::ParameterField,
+			_: &mut Compiler<S>,
+		) {
+			*lhs += FpVar::Constant(rhs.0)
+		}
+
+		#[inline]
+		fn apply_sbox(point: &mut Self::Field, _: &mut Compiler<S>) {
+			*point = point
+				.pow_by_constant(&[S::SBOX_EXPONENT])
+				.expect("Exponentiation is not allowed to fail.");
+		}
+
+		#[inline]
+		fn apply_inverse_sbox(point: &mut Self::Field, compiler: &mut Compiler<S>) {
+			let exponent = inverse_sbox_exponent::<S>();
+			if let FpVar::Constant(value) = point {
+				*value = value.pow(exponent);
+				return
+			}
+			let root = FpVar::new_witness(ns!(compiler.cs, "inverse sbox witness"), || {
+				Ok(point.value()?.pow(&exponent))
+			})
+			.expect("Variable allocation is not allowed to fail.");
+			root.pow_by_constant(&[S::SBOX_EXPONENT])
+				.and_then(|power| power.enforce_equal(point))
+				.expect("Inverse S-BOX constraints are not allowed to fail.");
+			*point = root;
+		}
+	}
+
+	impl<S, const ARITY: usize> Constant<Compiler<S>> for super::Hasher<S, ARITY, Compiler<S>>
+	where
+		S: Specification,
+	{
+		type Type = super::Hasher<S, ARITY>;
+
+		#[inline]
+		fn new_constant(this: &Self::Type, compiler: &mut Compiler<S>) -> Self {
+			let _ = compiler;
+			Self {
+				round_constants: this.round_constants.clone(),
+				mds_matrix: this.mds_matrix.clone(),
+			}
+		}
+	}
+}
+
+/// Cost Estimation Backend
+pub mod cost {
+	use super::arkworks::Specification;
+	use crate::crypto::{
+		arkworks::Fp,
+		cost::{CostCompiler, FieldVar},
+		eclair::alloc::Constant,
+	};
+
+	impl<S> super::Specification<CostCompiler> for S
+	where
+		S: Specification,
+	{
+		type Field = FieldVar<Fp<S::Field>>;
+		type ParameterField = Fp<S::Field>;
+
+		const ROUNDS: usize = S::ROUNDS;
+
+		#[inline]
+		fn constant(value: u128, _: &mut CostCompiler) -> Self::Field {
+			let _ = value;
+			FieldVar::constant()
+		}
+
+		#[inline]
+		fn add(lhs: &Self::Field, rhs: &Self::Field, compiler: &mut CostCompiler) -> Self::Field {
+			lhs.add(rhs, compiler)
+		}
+
+		#[inline]
+		fn mul_const(
+			lhs: &Self::Field,
+			rhs: &Self::ParameterField,
+			compiler: &mut CostCompiler,
+		) -> Self::Field {
+			let _ = rhs;
+			lhs.mul(&FieldVar::constant(), compiler)
+		}
+
+		#[inline]
+		fn add_const_assign(
+			lhs: &mut Self::Field,
+			rhs: &Self::ParameterField,
+			compiler: &mut CostCompiler,
+		) {
+			let _ = rhs;
+			*lhs = lhs.add(&FieldVar::constant(), compiler);
+		}
+
+		#[inline]
+		fn apply_sbox(point: &mut Self::Field, compiler: &mut CostCompiler) {
+			*point = point.pow(S::SBOX_EXPONENT, compiler);
+		}
+
+		#[inline]
+		fn apply_inverse_sbox(point: &mut Self::Field, compiler: &mut CostCompiler) {
+			*point = point.root(S::SBOX_EXPONENT, compiler);
+		}
+	}
+
+	impl<S, const ARITY: usize> Constant<CostCompiler> for super::Hasher<S, ARITY, CostCompiler>
+	where
+		S: Specification,
+	{
+		type Type = super::Hasher<S, ARITY>;
+
+		#[inline]
+		fn new_constant(this: &Self::Type, compiler: &mut CostCompiler) -> Self {
+			let _ = compiler;
+			Self {
+				round_constants: this.round_constants.clone(),
+				mds_matrix: this.mds_matrix.clone(),
+			}
+		}
+	}
+}
+
+/// Testing Framework
+pub mod test {
+	use super::*;
+	use crate::crypto::{
+		arkworks::{Fp, FpVar, R1CS},
+		eclair::alloc::{mode::Secret, Allocate},
+	};
+	use ark_r1cs_std::R1CSVar;
+	use core::array;
+
+	/// Asserts that a random [`Hasher`] computes the same hash natively and inside of the arkworks
+	/// compiler in every domain, and that the inverse S-BOX witnesses satisfy the constraint system.
+	#[inline]
+	pub fn assert_circuit_consistency<S, const ARITY: usize, R>(rng: &mut R)
+	where
+		S: arkworks::Specification,
+		R: RngCore + ?Sized,
+	{
+		let hasher = rng.gen::<_, Hasher<S, ARITY>>();
+		let input = array::from_fn::<Fp<S::Field>, ARITY, _>(|_| rng.gen());
+		let mut compiler = R1CS::<S::Field>::for_prove();
+		let input_var = input.map(|x| x.as_known::<Secret, FpVar<S::Field>>(&mut compiler));
+		let hasher_var: Hasher<S, ARITY, R1CS<S::Field>> = hasher.as_constant(&mut compiler);
+		for domain in [0, 1, 42] {
+			let expected = hasher.hash_in_domain(domain, array::from_fn(|i| &input[i]), &mut ());
+			let output =
+				hasher_var.hash_in_domain(domain, array::from_fn(|i| &input_var[i]), &mut compiler);
+			assert_eq!(
+				output.value().ok(),
+				Some(expected.0),
+				"Circuit hash does not match in domain {:?}.",
+				domain
+			);
+		}
+		assert_eq!(compiler.check(), Ok(()));
+	}
+}
@@ -0,0 +1,337 @@
+//! Solidity Verifier Export
+//!
+//! Ethereum only exposes pairing precompiles for the BN254 curve, so the export in this module is
+//! restricted to [`Groth16`] keys and proofs over [`Bn254`]. The generated contract exposes a
+//! single `verifyProof(uint256[2],uint256[2][2],uint256[2],uint256[N])` function whose arguments
+//! are produced by [`calldata`].
+
+use crate::crypto::{
+	arkworks::Fp,
+	proofsystem::{arkworks::Groth16, ProofSystem},
+};
+use alloc::{format, string::String, vec::Vec};
+use ark_bn254::{Fq2, FqParameters, FrParameters, G1Affine, G2Affine};
+use ark_ff::{BigInteger, BigInteger256, FpParameters, PrimeField, Zero};
+use core::fmt::Write;
+
+pub use ark_bn254::{Bn254, Fr};
+
+/// Groth16 Proof System over BN254
+pub type ProofSystemBn254 = Groth16<Bn254>;
+
+/// Verifying Key
+pub type VerifyingKey = <ProofSystemBn254 as ProofSystem>::VerifyingKey;
+
+/// Proof
+pub type Proof = <ProofSystemBn254 as ProofSystem>::Proof;
+
+/// Size of an ABI-encoded `uint256` Word
+pub const WORD_SIZE: usize = 32;
+
+/// Number of Words in an ABI-encoded [`Proof`]
+pub const PROOF_WORDS: usize = 8;
+
+/// Encodes `x` as a big-endian `uint256` word.
+#[inline]
+fn word<B>(x: B) -> [u8; WORD_SIZE]
+where
+	B: BigInteger,
+{
+	let mut word = [0; WORD_SIZE];
+	let bytes = x.to_bytes_be();
+	word[WORD_SIZE - bytes.len()..].copy_from_slice(&bytes);
+	word
+}
+
+/// Decodes a big-endian `uint256` word into a canonical field element, returning `None` if `word`
+/// is not smaller than the field modulus.
+#[inline]
+fn decode_field<F>(word: &[u8]) -> Option<F>
+where
+	F: PrimeField<BigInt = BigInteger256>,
+{
+	let mut limbs = [0; 4];
+	for (limb, chunk) in limbs.iter_mut().zip(word.rchunks_exact(8)) {
+		*limb = u64::from_be_bytes(chunk.try_into().ok()?);
+	}
+	F::from_repr(BigInteger256(limbs))
+}
+
+/// Returns the ABI words of `point`, encoding the point at infinity as `(0, 0)`.
+#[inline]
+fn g1_words(point: &G1Affine) -> [[u8; WORD_SIZE]; 2] {
+	if point.is_zero() {
+		return [[0; WORD_SIZE]; 2];
+	}
+	[word(point.x.into_repr()), word(point.y.into_repr())]
+}
+
+/// Returns the ABI words of `point` in the `(x.c1, x.c0, y.c1, y.c0)` order expected by the
+/// pairing precompile, encoding the point at infinity as all zeros.
+#[inline]
+fn g2_words(point: &G2Affine) -> [[u8; WORD_SIZE]; 4] {
+	if point.is_zero() {
+		return [[0; WORD_SIZE]; 4];
+	}
+	[
+		word(point.x.c1.into_repr()),
+		word(point.x.c0.into_repr()),
+		word(point.y.c1.into_repr()),
+		word(point.y.c0.into_repr()),
+	]
+}
+
+/// Decodes a G1 point from its ABI `words`, returning `None` if it is not on the curve.
+#[inline]
+fn decode_g1(words: &[&[u8]]) -> Option<G1Affine> {
+	let x = decode_field(words[0])?;
+	let y = decode_field(words[1])?;
+	let point = G1Affine::new(x, y, x.is_zero() && y.is_zero());
+	point.is_on_curve().then_some(point)
+}
+
+/// Decodes a G2 point from its ABI `words`, returning `None` if it is not on the curve or not in
+/// the prime-order subgroup.
+#[inline]
+fn decode_g2(words: &[&[u8]]) -> Option<G2Affine> {
+	let x = Fq2::new(decode_field(words[1])?, decode_field(words[0])?);
+	let y = Fq2::new(decode_field(words[3])?, decode_field(words[2])?);
+	let point = G2Affine::new(x, y, x.is_zero() && y.is_zero());
+	(point.is_on_curve() && point.is_in_correct_subgroup_assuming_on_curve()).then_some(point)
+}
+
+/// Returns `word` as a Solidity hexadecimal literal.
+#[inline]
+fn literal(word: &[u8; WORD_SIZE]) -> String {
+	let mut literal = String::with_capacity(2 + 2 * WORD_SIZE);
+	literal.push_str("0x");
+	for byte in word {
+		write!(literal, "{:02x}", byte).expect("Writing to a string is not allowed to fail.");
+	}
+	literal
+}
+
+/// Generates the Solidity source of a contract named `name` which verifies proofs against
+/// `verifying_key`.
+#[inline]
+pub fn verifier_contract(name: &str, verifying_key: &VerifyingKey) -> String {
+	let input_count = verifying_key.gamma_abc_g1.len() - 1;
+	let [alpha_x, alpha_y] = g1_words(&verifying_key.alpha_g1);
+	let g2_constants = [
+		("BETA", g2_words(&verifying_key.beta_g2)),
+		("GAMMA", g2_words(&verifying_key.gamma_g2)),
+		("DELTA", g2_words(&verifying_key.delta_g2)),
+	];
+	let mut source = String::new();
+	let mut line = |indent: usize, text: &str| {
+		for _ in 0..indent {
+			source.push_str("    ");
+		}
+		source.push_str(text);
+		source.push('\n');
+	};
+	line(0, "// SPDX-License-Identifier: GPL-3.0");
+	line(0, "pragma solidity ^0.8.0;");
+	line(0, "");
+	line(0, "/// Groth16 Verifier generated by subtornado");
+	line(0, &format!("contract {} {{", name));
+	line(1, &format!("uint256 constant SCALAR_FIELD = {};", literal(&word(FrParameters::MODULUS))));
+	line(1, &format!("uint256 constant BASE_FIELD = {};", literal(&word(FqParameters::MODULUS))));
+	line(1, &format!("uint256 constant ALPHA_X = {};", literal(&alpha_x)));
+	line(1, &format!("uint256 constant ALPHA_Y = {};", literal(&alpha_y)));
+	for (label, [x1, x0, y1, y0]) in &g2_constants {
+		line(1, &format!("uint256 constant {}_X1 = {};", label, literal(x1)));
+		line(1, &format!("uint256 constant {}_X0 = {};", label, literal(x0)));
+		line(1, &format!("uint256 constant {}_Y1 = {};", label, literal(y1)));
+		line(1, &format!("uint256 constant {}_Y0 = {};", label, literal(y0)));
+	}
+	for (i, point) in verifying_key.gamma_abc_g1.iter().enumerate() {
+		let [x, y] = g1_words(point);
+		line(1, &format!("uint256 constant IC{}_X = {};", i, literal(&x)));
+		line(1, &format!("uint256 constant IC{}_Y = {};", i, literal(&y)));
+	}
+	line(0, "");
+	line(1, "function ecAdd(uint256[2] memory p, uint256[2] memory q) internal view returns (uint256[2] memory r) {");
+	line(2, "uint256[4] memory input = [p[0], p[1], q[0], q[1]];");
+	line(2, "bool success;");
+	line(2, "assembly { success := staticcall(gas(), 0x06, input, 0x80, r, 0x40) }");
+	line(2, "require(success, \"ecAdd failed\");");
+	line(1, "}");
+	line(0, "");
+	line(1, "function ecMul(uint256[2] memory p, uint256 s) internal view returns (uint256[2] memory r) {");
+	line(2, "uint256[3] memory input = [p[0], p[1], s];");
+	line(2, "bool success;");
+	line(2, "assembly { success := staticcall(gas(), 0x07, input, 0x60, r, 0x40) }");
+	line(2, "require(success, \"ecMul failed\");");
+	line(1, "}");
+	line(0, "");
+	line(1, &format!(
+		"function verifyProof(uint256[2] calldata a, uint256[2][2] calldata b, uint256[2] calldata c, uint256[{}] calldata input) public view returns (bool) {{",
+		input_count
+	));
+	line(2, "uint256[2] memory x = [IC0_X, IC0_Y];");
+	for i in 0..input_count {
+		line(2, &format!("require(input[{}] < SCALAR_FIELD, \"input out of range\");", i));
+		line(2, &format!("x = ecAdd(x, ecMul([IC{}_X, IC{}_Y], input[{}]));", i + 1, i + 1, i));
+	}
+	line(2, "uint256[24] memory p;");
+	let pairing_input = [
+		"a[0]",
+		"a[0] == 0 && a[1] == 0 ? 0 : BASE_FIELD - (a[1] % BASE_FIELD)",
+		"b[0][0]",
+		"b[0][1]",
+		"b[1][0]",
+		"b[1][1]",
+		"ALPHA_X",
+		"ALPHA_Y",
+		"BETA_X1",
+		"BETA_X0",
+		"BETA_Y1",
+		"BETA_Y0",
+		"x[0]",
+		"x[1]",
+		"GAMMA_X1",
+		"GAMMA_X0",
+		"GAMMA_Y1",
+		"GAMMA_Y0",
+		"c[0]",
+		"c[1]",
+		"DELTA_X1",
+		"DELTA_X0",
+		"DELTA_Y1",
+		"DELTA_Y0",
+	];
+	for (i, value) in pairing_input.iter().enumerate() {
+		line(2, &format!("p[{}] = {};", i, value));
+	}
+	line(2, "uint256[1] memory out;");
+	line(2, "bool success;");
+	line(2, "assembly { success := staticcall(gas(), 0x08, p, 0x300, out, 0x20) }");
+	line(2, "return success && out[0] == 1;");
+	line(1, "}");
+	line(0, "}");
+	source
+}
+
+/// Encodes `proof` and `input` as the ABI arguments of `verifyProof`, excluding the four-byte
+/// function selector.
+#[inline]
+pub fn calldata(proof: &Proof, input: &[Fr]) -> Vec<u8> {
+	let mut calldata = Vec::with_capacity(WORD_SIZE * (PROOF_WORDS + input.len()));
+	for point_word in g1_words(&proof.a)
+		.iter()
+		.chain(g2_words(&proof.b).iter())
+		.chain(g1_words(&proof.c).iter())
+	{
+		calldata.extend_from_slice(point_word);
+	}
+	for x in input {
+		calldata.extend_from_slice(&word(x.into_repr()));
+	}
+	calldata
+}
+
+/// Decodes `calldata` produced by [`calldata`] back into a proof and its public input, returning
+/// `None` if `calldata` is malformed.
+#[inline]
+pub fn decode_calldata(calldata: &[u8]) -> Option<(Proof, Vec<Fr>)> {
+	if calldata.len() % WORD_SIZE != 0 || calldata.len() < WORD_SIZE * PROOF_WORDS {
+		return None;
+	}
+	let words = calldata.chunks_exact(WORD_SIZE).collect::<Vec<_>>();
+	let proof = Proof {
+		a: decode_g1(&words[0..2])?,
+		b: decode_g2(&words[2..6])?,
+		c: decode_g1(&words[6..8])?,
+	};
+	let input = words[PROOF_WORDS..]
+		.iter()
+		.map(|w| decode_field(w))
+		.collect::<Option<Vec<_>>>()?;
+	Some((proof, input))
+}
+
+/// Verifies the `calldata` natively against `verifying_key`, mirroring the checks performed by
+/// the contract generated by [`verifier_contract`].
+#[inline]
+pub fn verify_calldata(verifying_key: &VerifyingKey, calldata: &[u8]) -> bool {
+	match decode_calldata(calldata) {
+		Some((proof, input)) if input.len() + 1 == verifying_key.gamma_abc_g1.len() => {
+			let input = input.into_iter().map(Fp).collect::<Vec<_>>();
+			matches!(ProofSystemBn254::verify(verifying_key, &input, &proof), Ok(true))
+		},
+		_ => false,
+	}
+}
+
+/// Testing Framework
+pub mod test {
+	use super::*;
+	use crate::crypto::{
+		arkworks::FpVar,
+		eclair::{
+			alloc::{
+				mode::{Public, Secret},
+				Allocate, Allocator,
+			},
+			bool::AssertEq,
+		},
+		rand::{CryptoRng, Rand, RngCore},
+	};
+
+	/// Compiles a circuit proving knowledge of a square root of its public input.
+	#[inline]
+	fn square_root_circuit(
+		compiler: &mut <ProofSystemBn254 as ProofSystem>::Compiler,
+		root: Option<Fp<Fr>>,
+	) {
+		let (root, square): (FpVar<Fr>, FpVar<Fr>) = match root {
+			Some(root) => (
+				root.as_known::<Secret, _>(compiler),
+				Fp(root.0 * root.0).as_known::<Public, _>(compiler),
+			),
+			_ => {
+				(compiler.allocate_unknown::<Secret, _>(), compiler.allocate_unknown::<Public, _>())
+			},
+		};
+		let product = &root * &root;
+		compiler.assert_eq(&square, &product);
+	}
+
+	/// Tests that the [`calldata`] emitted for a fresh proof round-trips through
+	/// [`decode_calldata`] and is accepted by [`Groth16::verify`] natively, while calldata with a
+	/// tampered public input is rejected.
+	#[inline]
+	pub fn assert_valid_calldata<R>(rng: &mut R)
+	where
+		R: CryptoRng + RngCore + ?Sized,
+	{
+		let mut compiler = ProofSystemBn254::for_compile();
+		square_root_circuit(&mut compiler, None);
+		let (proving_key, verifying_key) =
+			ProofSystemBn254::compile(compiler, rng).expect("Unable to generate keys.");
+		let root = rng.gen::<_, Fp<Fr>>();
+		let mut compiler = ProofSystemBn254::for_prove();
+		square_root_circuit(&mut compiler, Some(root));
+		let proof = ProofSystemBn254::prove(&proving_key, compiler, rng)
+			.expect("Unable to generate proof.");
+		let input = [root.0 * root.0];
+		let encoded = calldata(&proof, &input);
+		assert_eq!(encoded.len(), WORD_SIZE * (PROOF_WORDS + input.len()));
+		let (decoded_proof, decoded_input) =
+			decode_calldata(&encoded).expect("Calldata produced by `calldata` must decode.");
+		assert_eq!(decoded_proof, proof, "Proof did not round-trip through calldata.");
+		assert_eq!(decoded_input, input, "Input did not round-trip through calldata.");
+		assert!(
+			verify_calldata(&verifying_key, &encoded),
+			"Calldata was rejected by the native verifier."
+		);
+		let tampered = calldata(&proof, &[input[0] + Fr::from(1u64)]);
+		assert!(
+			!verify_calldata(&verifying_key, &tampered),
+			"Calldata with a tampered input was accepted by the native verifier."
+		);
+		let contract = verifier_contract("Verifier", &verifying_key);
+		assert!(contract.contains("uint256[1] calldata input"));
+	}
+}
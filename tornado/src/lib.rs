@@ -3,6 +3,7 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
 extern crate alloc;
+extern crate self as tornado;
 
 pub mod circuit;
 pub mod config;
@@ -10,4 +11,8 @@ pub mod crypto;
 pub mod parameters;
 pub mod util;
 
+#[cfg(feature = "vectors")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "vectors")))]
+pub mod vectors;
+
 pub use ark_serialize;
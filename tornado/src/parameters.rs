@@ -1,16 +1,105 @@
 //! Parameters
 
 use crate::{
-	circuit::{claim_keys, mint_keys},
-	config::{Config, Parameters, ProvingKey, VerifyingKey},
+	circuit,
+	config::{Config, MerkleTreeHeight, Parameters, ProvingKey, VerifyingKey},
 	crypto::rand::{Rand, SeedableRng},
 };
 use rand_chacha::ChaCha20Rng;
 
-pub fn generate() -> (Parameters, (ProvingKey, VerifyingKey), (ProvingKey, VerifyingKey)) {
-	let mut rng = ChaCha20Rng::from_seed([8; 32]);
-	let parameters: Parameters = rng.gen();
-	let mint_keys = mint_keys::<Config, _>(&parameters, &mut rng).unwrap();
-	let claim_keys = claim_keys::<Config, _>(&parameters, &mut rng).unwrap();
-	(parameters, mint_keys, claim_keys)
+/// Seed for Parameter and Key Generation
+const SEED: [u8; 32] = [8; 32];
+
+/// Mint Key Generation Stream
+const MINT_STREAM: u64 = 1;
+
+/// Claim Key Generation Stream
+const CLAIM_STREAM: u64 = 2;
+
+/// Transfer Key Generation Stream
+const TRANSFER_STREAM: u64 = 3;
+
+/// Partial Claim Key Generation Stream
+const PARTIAL_CLAIM_STREAM: u64 = 4;
+
+/// Proving and Verifying Key Pair
+pub type KeyPair = (ProvingKey, VerifyingKey);
+
+/// Samples the circuit parameters, without generating any proving or verifying keys.
+///
+/// The parameters do not depend on the height of the merkle tree, so every height samples the
+/// same hash parameters.
+#[inline]
+pub fn parameters<H>() -> Parameters<H> {
+	ChaCha20Rng::from_seed(SEED).gen()
+}
+
+/// Returns the seeded generator for the key generation `stream`.
+///
+/// Every circuit draws the randomness for its keys from its own stream, so that the keys of one
+/// circuit can be generated without running the setup of any other circuit first.
+#[inline]
+fn key_rng(stream: u64) -> ChaCha20Rng {
+	let mut rng = ChaCha20Rng::from_seed(SEED);
+	rng.set_stream(stream);
+	rng
+}
+
+/// Generates the mint keys.
+#[inline]
+pub fn mint_keys<H>() -> KeyPair
+where
+	H: MerkleTreeHeight,
+{
+	circuit::mint_keys::<Config<H>, _>(&parameters(), &mut key_rng(MINT_STREAM))
+		.expect("Key generation is not allowed to fail.")
+}
+
+/// Generates the claim keys for a merkle tree with height given by `H`.
+#[inline]
+pub fn claim_keys<H>() -> KeyPair
+where
+	H: MerkleTreeHeight,
+{
+	circuit::claim_keys::<Config<H>, _>(&parameters(), &mut key_rng(CLAIM_STREAM))
+		.expect("Key generation is not allowed to fail.")
+}
+
+/// Generates the transfer keys for a merkle tree with height given by `H`.
+#[inline]
+pub fn transfer_keys<H>() -> KeyPair
+where
+	H: MerkleTreeHeight,
+{
+	circuit::transfer_keys::<Config<H>, _>(&parameters(), &mut key_rng(TRANSFER_STREAM))
+		.expect("Key generation is not allowed to fail.")
+}
+
+/// Generates the partial claim keys for a merkle tree with height given by `H`.
+#[inline]
+pub fn partial_claim_keys<H>() -> KeyPair
+where
+	H: MerkleTreeHeight,
+{
+	circuit::partial_claim_keys::<Config<H>, _>(&parameters(), &mut key_rng(PARTIAL_CLAIM_STREAM))
+		.expect("Key generation is not allowed to fail.")
+}
+
+/// Samples the circuit parameters and generates the mint, claim, transfer and partial claim keys
+/// for a merkle tree with height given by `H`.
+///
+/// Every circuit except mint verifies membership proofs in the merkle tree, so their keys are only
+/// valid for trees of height `H`. Verifiers which only need the keys of one circuit should use the
+/// function for that circuit instead, like [`claim_keys`].
+pub fn generate<H>() -> (Parameters<H>, KeyPair, KeyPair, KeyPair, KeyPair)
+where
+	H: MerkleTreeHeight,
+{
+	(
+		parameters(),
+		mint_keys::<H>(),
+		claim_keys::<H>(),
+		transfer_keys::<H>(),
+		partial_claim_keys::<H>(),
+	)
 }
@@ -15,3 +15,25 @@ where
 		_ => unreachable!("{} {:?}.", INTO_UNCHECKED_ERROR_MESSAGE, N),
 	}
 }
+
+/// Thread-Safety Bound for Optional Parallelism
+///
+/// With the `parallel` feature enabled, this trait is equivalent to `Send + Sync` so that code
+/// which is generic over it can distribute work across threads. Otherwise, every type implements
+/// it and the bound has no effect.
+#[cfg(feature = "parallel")]
+pub trait MaybeSendSync: Send + Sync {}
+
+#[cfg(feature = "parallel")]
+impl<T> MaybeSendSync for T where T: Send + Sync + ?Sized {}
+
+/// Thread-Safety Bound for Optional Parallelism
+///
+/// With the `parallel` feature enabled, this trait is equivalent to `Send + Sync` so that code
+/// which is generic over it can distribute work across threads. Otherwise, every type implements
+/// it and the bound has no effect.
+#[cfg(not(feature = "parallel"))]
+pub trait MaybeSendSync {}
+
+#[cfg(not(feature = "parallel"))]
+impl<T> MaybeSendSync for T where T: ?Sized {}
@@ -0,0 +1,219 @@
+//! Known-Answer Test Vectors
+//!
+//! The vectors in `vectors/hashes.json` are computed with the reference [`parameters`] which are
+//! sampled from the fixed parameter seed. Field elements are encoded as `0x`-prefixed big-endian
+//! hex strings of their canonical integer representation so that other implementations can check
+//! their outputs against this crate without depending on the `arkworks` serialization format.
+//!
+//! [`parameters`]: crate::parameters::parameters
+
+use crate::{
+	config::{ConstraintField, Scalar},
+	crypto::arkworks::Fp,
+};
+use alloc::vec::Vec;
+use ark_ff::{BigInteger, PrimeField};
+use serde_json::Value;
+
+/// Hash Test Vectors JSON
+pub const HASHES: &str = include_str!("../vectors/hashes.json");
+
+/// UTXO Test Vector
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct UtxoVector {
+	/// Secret Key
+	pub key: Scalar,
+
+	/// Value
+	pub value: Scalar,
+
+	/// Expected UTXO
+	pub utxo: Scalar,
+}
+
+/// Void Number Test Vector
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct VoidNumberVector {
+	/// Secret Key
+	pub key: Scalar,
+
+	/// UTXO
+	pub utxo: Scalar,
+
+	/// Expected Void Number
+	pub void_number: Scalar,
+}
+
+/// Merkle Tree Inner Hash Test Vector
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct InnerHashVector {
+	/// Left Input
+	pub lhs: Scalar,
+
+	/// Right Input
+	pub rhs: Scalar,
+
+	/// Expected Output
+	pub output: Scalar,
+}
+
+/// Hash Test Vectors
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Vectors {
+	/// UTXO Vectors
+	pub utxo: Vec<UtxoVector>,
+
+	/// Void Number Vectors
+	pub void_number: Vec<VoidNumberVector>,
+
+	/// Merkle Tree Inner Hash Vectors
+	pub inner_hash: Vec<InnerHashVector>,
+}
+
+/// Decodes a `0x`-prefixed big-endian hex string into a scalar, returning `None` if `hex` is not
+/// well-formed or is not the canonical encoding of a field element.
+#[inline]
+pub fn decode_scalar(hex: &str) -> Option<Scalar> {
+	let hex = hex.strip_prefix("0x")?;
+	if hex.len() != 64 {
+		return None
+	}
+	let bytes = (0..hex.len())
+		.step_by(2)
+		.map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+		.collect::<Option<Vec<_>>>()?;
+	let scalar = ConstraintField::from_be_bytes_mod_order(&bytes);
+	(scalar.into_repr().to_bytes_be() == bytes).then_some(Fp(scalar))
+}
+
+/// Returns the scalar stored under `key` in the vector `entry`.
+#[inline]
+fn scalar(entry: &Value, key: &str) -> Scalar {
+	entry[key]
+		.as_str()
+		.and_then(decode_scalar)
+		.expect("Test vectors are not allowed to contain malformed field elements.")
+}
+
+/// Returns the list of vector entries stored under `key`.
+#[inline]
+fn entries<'v>(vectors: &'v Value, key: &str) -> &'v [Value] {
+	vectors[key].as_array().expect("Test vectors are not allowed to be missing.")
+}
+
+/// Parses the test vectors from [`HASHES`].
+#[inline]
+pub fn vectors() -> Vectors {
+	let vectors = serde_json::from_str::<Value>(HASHES)
+		.expect("Test vectors are not allowed to be malformed JSON.");
+	Vectors {
+		utxo: entries(&vectors, "utxo")
+			.iter()
+			.map(|entry| UtxoVector {
+				key: scalar(entry, "key"),
+				value: scalar(entry, "value"),
+				utxo: scalar(entry, "utxo"),
+			})
+			.collect(),
+		void_number: entries(&vectors, "void_number")
+			.iter()
+			.map(|entry| VoidNumberVector {
+				key: scalar(entry, "key"),
+				utxo: scalar(entry, "utxo"),
+				void_number: scalar(entry, "void_number"),
+			})
+			.collect(),
+		inner_hash: entries(&vectors, "inner_hash")
+			.iter()
+			.map(|entry| InnerHashVector {
+				lhs: scalar(entry, "lhs"),
+				rhs: scalar(entry, "rhs"),
+				output: scalar(entry, "output"),
+			})
+			.collect(),
+	}
+}
+
+/// Testing Framework
+pub mod test {
+	use super::*;
+	use crate::{
+		circuit::Parameters as _,
+		config::{Compiler, DefaultHeight, MerkleTreeInnerHash, ParametersVar, ScalarVar},
+		crypto::{
+			eclair::alloc::{mode::Secret, Allocate},
+			merkle_tree::InnerHash,
+		},
+		parameters::parameters,
+	};
+	use ark_r1cs_std::R1CSVar;
+
+	/// Returns the known value of `variable`.
+	#[inline]
+	fn known_value(variable: &ScalarVar) -> Scalar {
+		Fp(variable.value().expect("Variables in prove mode are not allowed to be unknown."))
+	}
+
+	/// Asserts that the native hashes over the reference parameters match the test vectors.
+	#[inline]
+	pub fn assert_native_vectors() {
+		let parameters = parameters::<DefaultHeight>();
+		let vectors = vectors();
+		for vector in vectors.utxo {
+			assert_eq!(parameters.utxo(&vector.key, &vector.value, &mut ()), vector.utxo);
+		}
+		for vector in vectors.void_number {
+			assert_eq!(
+				parameters.void_number(&vector.key, &vector.utxo, &mut ()),
+				vector.void_number
+			);
+		}
+		for vector in vectors.inner_hash {
+			assert_eq!(
+				MerkleTreeInnerHash::join(
+					&parameters.merkle_tree_parameters.inner,
+					&vector.lhs,
+					&vector.rhs,
+					&mut ()
+				),
+				vector.output
+			);
+		}
+	}
+
+	/// Asserts that the in-circuit hashes over the reference parameters match the test vectors
+	/// and that the resulting constraint system is satisfied.
+	#[inline]
+	pub fn assert_circuit_vectors() {
+		let vectors = vectors();
+		let mut compiler = Compiler::for_prove();
+		let parameters: ParametersVar = parameters::<DefaultHeight>().as_constant(&mut compiler);
+		for vector in vectors.utxo {
+			let key = vector.key.as_known::<Secret, _>(&mut compiler);
+			let value = vector.value.as_known::<Secret, _>(&mut compiler);
+			assert_eq!(known_value(&parameters.utxo(&key, &value, &mut compiler)), vector.utxo);
+		}
+		for vector in vectors.void_number {
+			let key = vector.key.as_known::<Secret, _>(&mut compiler);
+			let utxo = vector.utxo.as_known::<Secret, _>(&mut compiler);
+			assert_eq!(
+				known_value(&parameters.void_number(&key, &utxo, &mut compiler)),
+				vector.void_number
+			);
+		}
+		for vector in vectors.inner_hash {
+			let lhs = vector.lhs.as_known::<Secret, _>(&mut compiler);
+			let rhs = vector.rhs.as_known::<Secret, _>(&mut compiler);
+			assert_eq!(
+				known_value(&MerkleTreeInnerHash::join(
+					&parameters.merkle_tree_parameters.inner,
+					&lhs,
+					&rhs,
+					&mut compiler
+				)),
+				vector.output
+			);
+		}
+		assert_eq!(compiler.check(), Ok(()));
+	}
+}